@@ -1,6 +1,6 @@
 #[macro_use]
 extern crate rocket;
-use anyhow::Result;
+use anyhow::{Result, bail};
 // --- Make sure all your modules are declared ---
 
 mod migration;
@@ -11,14 +11,20 @@ mod router;
 mod tasks;
 mod workflow;
 
+use crate::operations::import_export::run_import_export_cli;
+use crate::operations::password::run_set_password_cli;
 use crate::process::initialization::initialize;
 use crate::public::constant::runtime::{INDEX_RUNTIME, ROCKET_RUNTIME};
 use crate::public::error_data::handle_error;
 use crate::public::tui::{DASHBOARD, tui_task};
 use crate::tasks::BATCH_COORDINATOR;
+use crate::tasks::batcher::reconcile::ReconcileTask;
 use crate::tasks::batcher::start_watcher::StartWatcherTask;
 use crate::tasks::batcher::update_tree::UpdateTreeTask;
-use crate::tasks::looper::start_expire_check_loop;
+use crate::tasks::looper::{
+    start_backup_loop, start_expire_check_loop, start_reference_verify_loop,
+    start_watcher_health_check_loop,
+};
 
 use public::constant::redb::DATA_TABLE;
 use public::db::tree::TREE;
@@ -31,11 +37,21 @@ use router::{
     delete::generate_delete_routes, get::generate_get_routes, post::generate_post_routes,
     put::generate_put_routes,
 };
+use std::path::Path;
 use std::thread;
 use std::time::Instant;
 
 async fn build_rocket() -> rocket::Rocket<rocket::Build> {
-    rocket::build()
+    // Rocket trusts X-Real-IP unless told otherwise, which lets clients pick their own IP to
+    // dodge the login throttle; Rocket.toml files from older releases have no `ip_header` line
+    let mut figment = rocket::Config::figment();
+    let ip_header_unset = figment
+        .find_metadata("ip_header")
+        .is_none_or(|metadata| metadata.name == "rocket::Config::default()");
+    if ip_header_unset {
+        figment = figment.merge(("ip_header", false));
+    }
+    rocket::custom(figment)
         .attach(cache_control_fairing())
         .mount(
             "/assets",
@@ -48,13 +64,66 @@ async fn build_rocket() -> rocket::Rocket<rocket::Build> {
         .mount("/", generate_fairing_routes())
 }
 
+/// Subcommands open the database themselves, which redb refuses while the server holds it
+fn ensure_server_stopped() -> Result<()> {
+    let database_path = Path::new("./db/index.redb");
+    if !database_path.exists() {
+        return Ok(());
+    }
+    match redb::Database::create(database_path) {
+        Err(redb::DatabaseError::DatabaseAlreadyOpen) => {
+            bail!("The database is in use. Stop the server before running this command.")
+        }
+        // Anything else is reported by the migration that opens it next
+        _ => Ok(()),
+    }
+}
+
+/// Set up logging and folders like the server does, printing log lines to stderr
+fn initialize_cli() {
+    let mut rx = initialize();
+    thread::spawn(move || {
+        while let Some(line) = rx.blocking_recv() {
+            eprintln!("{}", line);
+        }
+    });
+}
+
 fn main() -> Result<()> {
+    // Subcommands work on the database directly, so the server must not be running
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let subcommand = args.first().map(String::as_str);
+    if matches!(subcommand, Some("import-export" | "set-password"))
+        && let Err(e) = ensure_server_stopped()
+    {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
     // Perform Migration Check and Execution
     if let Err(e) = migration::migrate() {
         eprintln!("Error during migration:\n{:?}", e);
         eprintln!("Migration failed. Please check the logs above.");
         std::process::exit(1);
     }
+    if let Err(e) = migration::schema::upgrade_schema() {
+        eprintln!("Error during schema upgrade:\n{:?}", e);
+        eprintln!("Schema upgrade failed. Please check the logs above.");
+        std::process::exit(1);
+    }
+
+    if subcommand == Some("import-export") {
+        let dry_run = args.iter().any(|arg| arg == "--dry-run");
+        let Some(path) = args.iter().skip(1).find(|arg| *arg != "--dry-run") else {
+            eprintln!("Usage: urocissa import-export <file> [--dry-run]");
+            std::process::exit(1);
+        };
+        initialize_cli();
+        return run_import_export_cli(Path::new(path), dry_run);
+    }
+    if subcommand == Some("set-password") {
+        return run_set_password_cli();
+    }
 
     let worker_handle = thread::spawn(|| {
         INDEX_RUNTIME.block_on(async {
@@ -78,8 +147,12 @@ fn main() -> Result<()> {
             }
             txn.commit().unwrap();
             BATCH_COORDINATOR.execute_batch_detached(StartWatcherTask);
+            BATCH_COORDINATOR.execute_batch_detached(ReconcileTask);
             BATCH_COORDINATOR.execute_batch_detached(UpdateTreeTask);
             start_expire_check_loop();
+            start_watcher_health_check_loop();
+            start_reference_verify_loop();
+            start_backup_loop();
 
             if let Some(sc) = superconsole::SuperConsole::new() {
                 INDEX_RUNTIME.spawn(async move {
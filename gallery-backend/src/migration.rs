@@ -1,6 +1,9 @@
 //! Database Migration Module
 //!
 //! Handles the migration from redb 2.6.x (Old Schema) to redb 3.1.x (New AbstractData Schema).
+//! Upgrades between AbstractData layouts live in [`schema`].
+
+pub mod schema;
 
 use anyhow::{Context, Result};
 use arrayvec::ArrayString;
@@ -182,6 +185,9 @@ fn transform_database_to_abstract_data(old_data: OldDatabase) -> AbstractData {
             file: a.file.clone(),
             modified: a.modified,
             scan_time: a.scan_time,
+            missing: false,
+            reference: false,
+            changed: false,
         })
         .collect();
 
@@ -211,6 +217,8 @@ fn transform_database_to_abstract_data(old_data: OldDatabase) -> AbstractData {
             is_favorite,
             is_archived,
             is_trashed,
+            date_override: None,
+            owner: None,
         };
 
         let metadata = VideoMetadata {
@@ -243,6 +251,8 @@ fn transform_database_to_abstract_data(old_data: OldDatabase) -> AbstractData {
             is_favorite,
             is_archived,
             is_trashed,
+            date_override: None,
+            owner: None,
         };
 
         let metadata = ImageMetadata {
@@ -303,6 +313,8 @@ fn transform_album_to_abstract_data(old_album: OldAlbum) -> AbstractData {
         is_favorite,
         is_archived,
         is_trashed,
+        date_override: None,
+        owner: None,
     };
 
     let metadata = AlbumMetadata {
@@ -316,6 +328,7 @@ fn transform_album_to_abstract_data(old_album: OldAlbum) -> AbstractData {
         item_count: old_album.item_count,
         item_size: old_album.item_size,
         share_list,
+        granted_users: HashSet::new(),
     };
 
     AbstractData::Album(AlbumCombined { object, metadata })
@@ -477,6 +490,7 @@ pub fn migrate() -> Result<()> {
         println!("Album migration completed. Total: {}", processed_count);
     }
 
+    schema::write_schema_version(&write_txn)?;
     write_txn.commit()?;
 
     println!("Migration completed successfully.");
@@ -0,0 +1,628 @@
+//! DATA_TABLE Schema Versioning
+//!
+//! Records are encoded with bitcode, which has no notion of defaulted fields: once a field is
+//! added to a stored struct, bytes written by an older build no longer decode. The layout in use
+//! is recorded in SETTING_TABLE, and older layouts are re-encoded in a single pass at startup.
+
+use anyhow::{Context, Result, bail};
+use arrayvec::ArrayString;
+use bitcode::{Decode, Encode};
+use redb::{ReadableDatabase, ReadableTable, TableDefinition, TableError, TypeName, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use super::OLD_DB_PATH;
+use crate::operations::password::{hash_share_password, is_legacy_share_password};
+use crate::operations::utils::path::canonical_path;
+use crate::public::constant::redb::SETTING_TABLE;
+use crate::public::db::tree::setting::SCHEMA_VERSION;
+use crate::public::structure::abstract_data::AbstractData;
+use crate::public::structure::album::{Share, combined::AlbumCombined, metadata::AlbumMetadata};
+use crate::public::structure::common::FileModify;
+use crate::public::structure::image::{combined::ImageCombined, metadata::ImageMetadata};
+use crate::public::structure::object::{ObjectSchema, ObjectType};
+use crate::public::structure::video::{combined::VideoCombined, metadata::VideoMetadata};
+
+/// Layout written by this build.
+/// - 0: 0.20.1 and earlier, before the version was recorded
+/// - 1: date override and owner on objects; missing, reference and changed on aliases;
+///   granted users on albums
+/// - 2: share passwords stored as Argon2 hashes instead of plaintext
+/// - 3: alias paths stored canonical, like the sync paths they are matched against
+/// - 4: video duration filled in for every video, not only newly indexed ones
+pub const CURRENT_SCHEMA_VERSION: u32 = 4;
+
+// ==================================================================================
+// Version 0 Data Structures (Snapshot for 0.20.1)
+// ==================================================================================
+
+mod v0 {
+    use super::*;
+
+    #[derive(Debug, Clone, Encode, Decode)]
+    pub struct FileModify {
+        pub file: String,
+        pub modified: u128,
+        pub scan_time: u128,
+    }
+
+    #[derive(Debug, Clone, Encode, Decode)]
+    pub struct ObjectSchema {
+        pub id: ArrayString<64>,
+        pub obj_type: ObjectType,
+        pub pending: bool,
+        pub thumbhash: Option<Vec<u8>>,
+        pub description: Option<String>,
+        pub tags: HashSet<String>,
+        pub is_favorite: bool,
+        pub is_archived: bool,
+        pub is_trashed: bool,
+    }
+
+    #[derive(Debug, Clone, Encode, Decode)]
+    pub struct ImageMetadata {
+        pub id: ArrayString<64>,
+        pub size: u64,
+        pub width: u32,
+        pub height: u32,
+        pub ext: String,
+        pub phash: Option<Vec<u8>>,
+        pub albums: HashSet<ArrayString<64>>,
+        pub exif_vec: BTreeMap<String, String>,
+        pub alias: Vec<FileModify>,
+    }
+
+    #[derive(Debug, Clone, Encode, Decode)]
+    pub struct VideoMetadata {
+        pub id: ArrayString<64>,
+        pub size: u64,
+        pub width: u32,
+        pub height: u32,
+        pub ext: String,
+        pub duration: f64,
+        pub albums: HashSet<ArrayString<64>>,
+        pub exif_vec: BTreeMap<String, String>,
+        pub alias: Vec<FileModify>,
+    }
+
+    #[derive(Debug, Clone, Encode, Decode)]
+    pub struct AlbumMetadata {
+        pub id: ArrayString<64>,
+        pub title: Option<String>,
+        pub created_time: i64,
+        pub start_time: Option<i64>,
+        pub end_time: Option<i64>,
+        pub last_modified_time: i64,
+        pub cover: Option<ArrayString<64>>,
+        pub item_count: usize,
+        pub item_size: u64,
+        pub share_list: HashMap<ArrayString<64>, Share>,
+    }
+
+    #[derive(Debug, Clone, Encode, Decode)]
+    pub struct ImageCombined {
+        pub object: ObjectSchema,
+        pub metadata: ImageMetadata,
+    }
+
+    #[derive(Debug, Clone, Encode, Decode)]
+    pub struct VideoCombined {
+        pub object: ObjectSchema,
+        pub metadata: VideoMetadata,
+    }
+
+    #[derive(Debug, Clone, Encode, Decode)]
+    pub struct AlbumCombined {
+        pub object: ObjectSchema,
+        pub metadata: AlbumMetadata,
+    }
+
+    #[derive(Debug, Clone, Encode, Decode)]
+    pub enum AbstractData {
+        Image(ImageCombined),
+        Video(VideoCombined),
+        Album(AlbumCombined),
+    }
+}
+
+fn upgrade_file_modify_v0(old: v0::FileModify) -> FileModify {
+    FileModify {
+        file: old.file,
+        modified: old.modified,
+        scan_time: old.scan_time,
+        missing: false,
+        reference: false,
+        changed: false,
+    }
+}
+
+fn upgrade_object_v0(old: v0::ObjectSchema) -> ObjectSchema {
+    ObjectSchema {
+        id: old.id,
+        obj_type: old.obj_type,
+        pending: old.pending,
+        thumbhash: old.thumbhash,
+        description: old.description,
+        tags: old.tags,
+        is_favorite: old.is_favorite,
+        is_archived: old.is_archived,
+        is_trashed: old.is_trashed,
+        date_override: None,
+        owner: None,
+    }
+}
+
+fn upgrade_v0(old: v0::AbstractData) -> AbstractData {
+    match old {
+        v0::AbstractData::Image(img) => AbstractData::Image(ImageCombined {
+            object: upgrade_object_v0(img.object),
+            metadata: ImageMetadata {
+                id: img.metadata.id,
+                size: img.metadata.size,
+                width: img.metadata.width,
+                height: img.metadata.height,
+                ext: img.metadata.ext,
+                phash: img.metadata.phash,
+                albums: img.metadata.albums,
+                exif_vec: img.metadata.exif_vec,
+                alias: img
+                    .metadata
+                    .alias
+                    .into_iter()
+                    .map(upgrade_file_modify_v0)
+                    .collect(),
+            },
+        }),
+        v0::AbstractData::Video(vid) => AbstractData::Video(VideoCombined {
+            object: upgrade_object_v0(vid.object),
+            metadata: VideoMetadata {
+                id: vid.metadata.id,
+                size: vid.metadata.size,
+                width: vid.metadata.width,
+                height: vid.metadata.height,
+                ext: vid.metadata.ext,
+                duration: vid.metadata.duration,
+                albums: vid.metadata.albums,
+                exif_vec: vid.metadata.exif_vec,
+                alias: vid
+                    .metadata
+                    .alias
+                    .into_iter()
+                    .map(upgrade_file_modify_v0)
+                    .collect(),
+            },
+        }),
+        v0::AbstractData::Album(alb) => AbstractData::Album(AlbumCombined {
+            object: upgrade_object_v0(alb.object),
+            metadata: AlbumMetadata {
+                id: alb.metadata.id,
+                title: alb.metadata.title,
+                created_time: alb.metadata.created_time,
+                start_time: alb.metadata.start_time,
+                end_time: alb.metadata.end_time,
+                last_modified_time: alb.metadata.last_modified_time,
+                cover: alb.metadata.cover,
+                item_count: alb.metadata.item_count,
+                item_size: alb.metadata.item_size,
+                share_list: alb.metadata.share_list,
+                granted_users: HashSet::new(),
+            },
+        }),
+    }
+}
+
+/// Decode a record written with the layout of `version`
+fn decode_record(version: u32, bytes: &[u8]) -> Result<AbstractData> {
+    match version {
+        0 => Ok(upgrade_v0(
+            bitcode::decode::<v0::AbstractData>(bytes).context("Not a version 0 record")?,
+        )),
+        _ => bitcode::decode::<AbstractData>(bytes).context("Not a current record"),
+    }
+}
+
+/// Hash share passwords that were stored in plaintext before version 2
+fn hash_legacy_share_passwords(abstract_data: &mut AbstractData) -> Result<()> {
+    if let AbstractData::Album(album) = abstract_data {
+        for share in album.metadata.share_list.values_mut() {
+            if share
+                .password
+                .as_deref()
+                .is_some_and(is_legacy_share_password)
+            {
+                share.password = hash_share_password(share.password.take())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Canonicalize alias paths, which were stored as found (possibly relative) before version 3
+fn canonicalize_aliases(abstract_data: &mut AbstractData) {
+    if let Some(alias) = abstract_data.alias_mut() {
+        for file_modify in alias.iter_mut() {
+            file_modify.file = canonical_path(Path::new(&file_modify.file))
+                .to_string_lossy()
+                .into_owned();
+        }
+    }
+}
+
+/// Fill in video durations from the stored ffprobe output; before version 4 they were left at 0
+fn backfill_video_duration(abstract_data: &mut AbstractData) {
+    if let AbstractData::Video(vid) = abstract_data
+        && vid.metadata.duration == 0.0
+        && let Some(duration) = vid
+            .metadata
+            .exif_vec
+            .get("duration")
+            .and_then(|duration| duration.trim().parse::<f64>().ok())
+    {
+        vid.metadata.duration = duration;
+    }
+}
+
+/// Decode a record of `version` and apply every upgrade step since
+fn upgrade_record(version: u32, bytes: &[u8]) -> Result<AbstractData> {
+    let mut abstract_data = decode_record(version, bytes)?;
+    if version < 2 {
+        hash_legacy_share_passwords(&mut abstract_data)?;
+    }
+    if version < 3 {
+        canonicalize_aliases(&mut abstract_data);
+    }
+    if version < 4 {
+        backfill_video_duration(&mut abstract_data);
+    }
+    Ok(abstract_data)
+}
+
+// ==================================================================================
+// Upgrade Logic
+// ==================================================================================
+
+/// DATA_TABLE values as undecoded bytes, under the same type name so redb opens the table
+#[derive(Debug)]
+struct RawAbstractData;
+
+impl Value for RawAbstractData {
+    type SelfType<'a>
+        = &'a [u8]
+    where
+        Self: 'a;
+    type AsBytes<'a>
+        = &'a [u8]
+    where
+        Self: 'a;
+
+    fn fixed_width() -> Option<usize> {
+        None
+    }
+
+    fn from_bytes<'a>(data: &'a [u8]) -> Self::SelfType<'a>
+    where
+        Self: 'a,
+    {
+        data
+    }
+
+    fn as_bytes<'a, 'b: 'a>(value: &'a Self::SelfType<'b>) -> Self::AsBytes<'a> {
+        value
+    }
+
+    fn type_name() -> TypeName {
+        AbstractData::type_name()
+    }
+}
+
+const RAW_DATA_TABLE: TableDefinition<&str, RawAbstractData> = TableDefinition::new("database");
+
+/// Version of the records in DATA_TABLE; databases that never recorded one are version 0
+fn read_schema_version(database: &redb::Database) -> Result<u32> {
+    let read_txn = database
+        .begin_read()
+        .context("Failed to begin read transaction")?;
+    let table = match read_txn.open_table(SETTING_TABLE) {
+        Ok(table) => table,
+        Err(TableError::TableDoesNotExist(_)) => return Ok(0),
+        Err(err) => return Err(err).context("Failed to open SETTING_TABLE"),
+    };
+    match table.get(SCHEMA_VERSION)? {
+        Some(guard) => guard
+            .value()
+            .parse()
+            .with_context(|| format!("Invalid schema version {:?}", guard.value())),
+        None => Ok(0),
+    }
+}
+
+/// Record that the database holds records of the current layout
+pub fn write_schema_version(write_txn: &redb::WriteTransaction) -> Result<()> {
+    let mut table = write_txn
+        .open_table(SETTING_TABLE)
+        .context("Failed to open SETTING_TABLE")?;
+    table.insert(SCHEMA_VERSION, CURRENT_SCHEMA_VERSION.to_string().as_str())?;
+    Ok(())
+}
+
+/// Re-encode every DATA_TABLE record in the current layout. Everything happens in one write
+/// transaction, so an interrupted upgrade leaves the old records and version in place.
+fn upgrade_database(database: &redb::Database) -> Result<usize> {
+    let version = read_schema_version(database)?;
+    if version == CURRENT_SCHEMA_VERSION {
+        return Ok(0);
+    }
+    if version > CURRENT_SCHEMA_VERSION {
+        bail!(
+            "Database schema version {} is newer than this build supports ({})",
+            version,
+            CURRENT_SCHEMA_VERSION
+        );
+    }
+
+    let write_txn = database
+        .begin_write()
+        .context("Failed to begin write transaction")?;
+    let upgraded_count = {
+        let mut table = write_txn
+            .open_table(RAW_DATA_TABLE)
+            .context("Failed to open DATA_TABLE")?;
+
+        let mut upgraded_list = Vec::new();
+        for entry in table.iter()? {
+            let (key, value) = entry?;
+            let abstract_data = upgrade_record(version, value.value())
+                .with_context(|| format!("Failed to upgrade record {}", key.value()))?;
+            upgraded_list.push((key.value().to_string(), bitcode::encode(&abstract_data)));
+        }
+
+        for (key, bytes) in &upgraded_list {
+            table.insert(key.as_str(), bytes.as_slice())?;
+        }
+        upgraded_list.len()
+    };
+    write_schema_version(&write_txn)?;
+    write_txn
+        .commit()
+        .context("Failed to commit schema upgrade")?;
+    Ok(upgraded_count)
+}
+
+/// Bring `./db/index.redb` to the current layout before anything else opens it
+pub fn upgrade_schema() -> Result<()> {
+    if let Some(parent) = Path::new(OLD_DB_PATH).parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {:?}", parent))?;
+    }
+    let database = redb::Database::create(OLD_DB_PATH)
+        .with_context(|| format!("Failed to open {}", OLD_DB_PATH))?;
+
+    let version = read_schema_version(&database)?;
+    let upgraded_count = upgrade_database(&database)?;
+    if version != CURRENT_SCHEMA_VERSION {
+        println!(
+            "[INFO] Upgraded {} records from schema version {} to {}.",
+            upgraded_count, version, CURRENT_SCHEMA_VERSION
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::public::constant::redb::DATA_TABLE;
+
+    fn temp_database() -> (redb::Database, std::path::PathBuf) {
+        let path =
+            std::env::temp_dir().join(format!("urocissa-schema-{}.redb", uuid::Uuid::new_v4()));
+        (redb::Database::create(&path).unwrap(), path)
+    }
+
+    fn v0_image() -> v0::AbstractData {
+        let id = ArrayString::from(&"a".repeat(64)).unwrap();
+        v0::AbstractData::Image(v0::ImageCombined {
+            object: v0::ObjectSchema {
+                id,
+                obj_type: ObjectType::Image,
+                pending: false,
+                thumbhash: Some(vec![1, 2, 3]),
+                description: Some("beach".to_string()),
+                tags: HashSet::from(["holiday".to_string()]),
+                is_favorite: true,
+                is_archived: false,
+                is_trashed: false,
+            },
+            metadata: v0::ImageMetadata {
+                id,
+                size: 1024,
+                width: 640,
+                height: 480,
+                ext: "jpg".to_string(),
+                phash: Some(vec![9; 8]),
+                albums: HashSet::new(),
+                exif_vec: BTreeMap::from([("Make".to_string(), "Canon".to_string())]),
+                alias: vec![v0::FileModify {
+                    file: "/photos/beach.jpg".to_string(),
+                    modified: 10,
+                    scan_time: 20,
+                }],
+            },
+        })
+    }
+
+    fn v0_album() -> v0::AbstractData {
+        let id = ArrayString::from(&"b".repeat(64)).unwrap();
+        v0::AbstractData::Album(v0::AlbumCombined {
+            object: v0::ObjectSchema {
+                id,
+                obj_type: ObjectType::Album,
+                pending: false,
+                thumbhash: None,
+                description: None,
+                tags: HashSet::new(),
+                is_favorite: false,
+                is_archived: false,
+                is_trashed: false,
+            },
+            metadata: v0::AlbumMetadata {
+                id,
+                title: Some("Trip".to_string()),
+                created_time: 5,
+                start_time: None,
+                end_time: None,
+                last_modified_time: 6,
+                cover: None,
+                item_count: 0,
+                item_size: 0,
+                share_list: HashMap::new(),
+            },
+        })
+    }
+
+    #[test]
+    fn current_layout_rejects_version_0_bytes() {
+        let bytes = bitcode::encode(&v0_image());
+        assert!(bitcode::decode::<AbstractData>(&bytes).is_err());
+    }
+
+    #[test]
+    fn decodes_version_0_record() {
+        let bytes = bitcode::encode(&v0_image());
+        let AbstractData::Image(img) = decode_record(0, &bytes).unwrap() else {
+            panic!("expected an image");
+        };
+        assert_eq!(img.object.description.as_deref(), Some("beach"));
+        assert!(img.object.is_favorite);
+        assert_eq!(img.object.date_override, None);
+        assert_eq!(img.object.owner, None);
+        assert_eq!(img.metadata.exif_vec["Make"], "Canon");
+        assert_eq!(img.metadata.alias.len(), 1);
+        assert_eq!(img.metadata.alias[0].file, "/photos/beach.jpg");
+        assert!(!img.metadata.alias[0].missing);
+        assert!(!img.metadata.alias[0].reference);
+    }
+
+    #[test]
+    fn upgrades_version_0_database_once() {
+        let (database, path) = temp_database();
+        let records = [v0_image(), v0_album()];
+        {
+            let write_txn = database.begin_write().unwrap();
+            {
+                let mut table = write_txn.open_table(RAW_DATA_TABLE).unwrap();
+                for (key, record) in ["a".repeat(64), "b".repeat(64)].iter().zip(&records) {
+                    table
+                        .insert(key.as_str(), bitcode::encode(record).as_slice())
+                        .unwrap();
+                }
+            }
+            write_txn.commit().unwrap();
+        }
+
+        assert_eq!(upgrade_database(&database).unwrap(), 2);
+        assert_eq!(
+            read_schema_version(&database).unwrap(),
+            CURRENT_SCHEMA_VERSION
+        );
+
+        let read_txn = database.begin_read().unwrap();
+        let table = read_txn.open_table(DATA_TABLE).unwrap();
+        let album = table.get("b".repeat(64).as_str()).unwrap().unwrap().value();
+        let AbstractData::Album(alb) = album else {
+            panic!("expected an album");
+        };
+        assert_eq!(alb.metadata.title.as_deref(), Some("Trip"));
+        assert!(alb.metadata.granted_users.is_empty());
+        drop(table);
+        drop(read_txn);
+
+        assert_eq!(upgrade_database(&database).unwrap(), 0);
+        drop(database);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn hashes_plaintext_share_passwords() {
+        let v0::AbstractData::Album(mut alb) = v0_album() else {
+            unreachable!();
+        };
+        let share_id = ArrayString::from("share").unwrap();
+        alb.metadata.share_list.insert(
+            share_id,
+            Share {
+                url: share_id,
+                password: Some("secret".to_string()),
+                ..Default::default()
+            },
+        );
+        let bytes = bitcode::encode(&v0::AbstractData::Album(alb));
+
+        let AbstractData::Album(alb) = upgrade_record(0, &bytes).unwrap() else {
+            panic!("expected an album");
+        };
+        let stored_password = alb.metadata.share_list[&share_id].password.clone().unwrap();
+        assert!(!is_legacy_share_password(&stored_password));
+        assert!(crate::operations::password::verify_share_password(
+            "secret",
+            &stored_password
+        ));
+    }
+
+    #[test]
+    fn canonicalizes_relative_aliases() {
+        let v0::AbstractData::Image(mut img) = v0_image() else {
+            unreachable!();
+        };
+        img.metadata.alias[0].file = "./photos/../beach.jpg".to_string();
+        let bytes = bitcode::encode(&v0::AbstractData::Image(img));
+
+        let AbstractData::Image(img) = upgrade_record(0, &bytes).unwrap() else {
+            panic!("expected an image");
+        };
+        let expected = std::fs::canonicalize(".").unwrap().join("beach.jpg");
+        assert_eq!(img.metadata.alias[0].file, expected.to_string_lossy());
+    }
+
+    #[test]
+    fn backfills_video_duration() {
+        let v0::AbstractData::Image(img) = v0_image() else {
+            unreachable!();
+        };
+        let video = v0::AbstractData::Video(v0::VideoCombined {
+            object: v0::ObjectSchema {
+                obj_type: ObjectType::Video,
+                ..img.object
+            },
+            metadata: v0::VideoMetadata {
+                id: img.metadata.id,
+                size: img.metadata.size,
+                width: img.metadata.width,
+                height: img.metadata.height,
+                ext: "mp4".to_string(),
+                duration: 0.0,
+                albums: HashSet::new(),
+                exif_vec: BTreeMap::from([("duration".to_string(), "12.480000".to_string())]),
+                alias: img.metadata.alias,
+            },
+        });
+        let bytes = bitcode::encode(&video);
+
+        let AbstractData::Video(vid) = upgrade_record(0, &bytes).unwrap() else {
+            panic!("expected a video");
+        };
+        assert_eq!(vid.metadata.duration, 12.48);
+    }
+
+    #[test]
+    fn stamps_empty_database() {
+        let (database, path) = temp_database();
+        assert_eq!(upgrade_database(&database).unwrap(), 0);
+        assert_eq!(
+            read_schema_version(&database).unwrap(),
+            CURRENT_SCHEMA_VERSION
+        );
+        drop(database);
+        std::fs::remove_file(path).unwrap();
+    }
+}
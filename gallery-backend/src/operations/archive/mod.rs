@@ -0,0 +1,274 @@
+use crate::operations::utils::uri::uri_encode;
+use crate::public::error_data::handle_error;
+use crate::public::storage::fetch_download;
+use crate::public::structure::abstract_data::AbstractData;
+use anyhow::{Context, Result};
+use chrono::{Local, SecondsFormat, TimeZone};
+use rocket::form::FromFormField;
+use rocket::http::Header;
+use rocket::response::stream::ByteStream;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use tokio::sync::mpsc::{self, Sender};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Bytes handed to the response at a time
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// Metadata file written next to every original in a ZIP download
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, FromFormField)]
+#[serde(rename_all = "camelCase")]
+pub enum SidecarFormat {
+    Json,
+    Xmp,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonSidecar<'a> {
+    hash: &'a str,
+    description: Option<&'a str>,
+    tags: Vec<&'a str>,
+    is_favorite: bool,
+}
+
+/// A streamed ZIP offered as a file download
+#[derive(Responder)]
+#[response(content_type = "application/zip")]
+pub struct ZipDownload<T> {
+    inner: T,
+    disposition: Header<'static>,
+}
+
+/// `Write` end of a streamed response; fails once the client has gone away
+struct ChannelWriter {
+    sender: Sender<Vec<u8>>,
+}
+
+impl ChannelWriter {
+    fn new(sender: Sender<Vec<u8>>) -> BufWriter<Self> {
+        BufWriter::with_capacity(CHUNK_SIZE, Self { sender })
+    }
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sender
+            .blocking_send(buf.to_vec())
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Download was cancelled"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Write the originals of `data_list` as a ZIP without seeking, so it can be streamed.
+/// Files are stored as they are since photos and videos are already compressed.
+fn write_zip<W: Write>(
+    writer: W,
+    data_list: Vec<AbstractData>,
+    sidecar_opt: Option<SidecarFormat>,
+) -> Result<()> {
+    let mut zip = ZipWriter::new_stream(writer);
+    let mut used_names = HashSet::new();
+    for abstract_data in data_list {
+        if matches!(abstract_data, AbstractData::Album(_)) {
+            continue;
+        }
+        let imported_path = fetch_download(&abstract_data)?;
+        let mut source = File::open(&imported_path)
+            .with_context(|| format!("Failed to open {:?}", imported_path))?;
+        let size = source.metadata()?.len();
+
+        let file_name = entry_name(&abstract_data, &mut used_names);
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .large_file(size >= u32::MAX as u64);
+        zip.start_file(file_name.as_str(), options)?;
+        io::copy(&mut source, &mut zip)
+            .with_context(|| format!("Failed to add {:?} to the archive", imported_path))?;
+
+        if let Some(sidecar) = sidecar_opt {
+            let (sidecar_name, content) = match sidecar {
+                SidecarFormat::Json => {
+                    (format!("{}.json", file_name), json_sidecar(&abstract_data)?)
+                }
+                SidecarFormat::Xmp => (format!("{}.xmp", file_name), xmp_sidecar(&abstract_data)),
+            };
+            let options =
+                SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+            zip.start_file(sidecar_name, options)?;
+            zip.write_all(content.as_bytes())?;
+        }
+    }
+    zip.finish()?.flush()?;
+    Ok(())
+}
+
+/// File name of the newest alias, prefixed with the hash if another entry already took it
+fn entry_name(abstract_data: &AbstractData, used_names: &mut HashSet<String>) -> String {
+    let hash = abstract_data.hash();
+    let file_name = abstract_data
+        .alias()
+        .last()
+        .and_then(|file_modify| Path::new(&file_modify.file).file_name())
+        .and_then(|file_name| file_name.to_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}.{}", hash, abstract_data.ext()));
+    if used_names.insert(file_name.to_lowercase()) {
+        return file_name;
+    }
+    let file_name = format!("{}-{}", &hash[0..8], file_name);
+    used_names.insert(file_name.to_lowercase());
+    file_name
+}
+
+fn sorted_tags(abstract_data: &AbstractData) -> Vec<&str> {
+    let mut tags: Vec<&str> = abstract_data.tag().iter().map(String::as_str).collect();
+    tags.sort_unstable();
+    tags
+}
+
+fn json_sidecar(abstract_data: &AbstractData) -> Result<String> {
+    let object = abstract_data.object();
+    let sidecar = JsonSidecar {
+        hash: &object.id,
+        description: object.description.as_deref(),
+        tags: sorted_tags(abstract_data),
+        is_favorite: object.is_favorite,
+    };
+    Ok(serde_json::to_string_pretty(&sidecar)?)
+}
+
+/// Tags become `dc:subject` keywords, the description `dc:description` and a corrected date
+/// `exif:DateTimeOriginal`, which is what most photo managers read from XMP sidecars
+fn xmp_sidecar(abstract_data: &AbstractData) -> String {
+    let object = abstract_data.object();
+    let mut xmp = String::from(concat!(
+        "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n",
+        "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n",
+        " <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n",
+        "  <rdf:Description rdf:about=\"\"\n",
+        "    xmlns:dc=\"http://purl.org/dc/elements/1.1/\"\n",
+        "    xmlns:exif=\"http://ns.adobe.com/exif/1.0/\"\n",
+        "    xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\">\n",
+    ));
+    if object.is_favorite {
+        xmp.push_str("   <xmp:Rating>5</xmp:Rating>\n");
+    }
+    if let Some(date_time) = object
+        .date_override
+        .and_then(|millis| i64::try_from(millis).ok())
+        .and_then(|millis| Local.timestamp_millis_opt(millis).single())
+    {
+        xmp.push_str(&format!(
+            "   <exif:DateTimeOriginal>{}</exif:DateTimeOriginal>\n",
+            date_time.to_rfc3339_opts(SecondsFormat::Secs, false)
+        ));
+    }
+    if let Some(description) = &object.description {
+        xmp.push_str("   <dc:description>\n    <rdf:Alt>\n");
+        xmp.push_str(&format!(
+            "     <rdf:li xml:lang=\"x-default\">{}</rdf:li>\n",
+            xml_escape(description)
+        ));
+        xmp.push_str("    </rdf:Alt>\n   </dc:description>\n");
+    }
+    let tags = sorted_tags(abstract_data);
+    if !tags.is_empty() {
+        xmp.push_str("   <dc:subject>\n    <rdf:Bag>\n");
+        for tag in tags {
+            xmp.push_str(&format!("     <rdf:li>{}</rdf:li>\n", xml_escape(tag)));
+        }
+        xmp.push_str("    </rdf:Bag>\n   </dc:subject>\n");
+    }
+    xmp.push_str("  </rdf:Description>\n </rdf:RDF>\n</x:xmpmeta>\n<?xpacket end=\"w\"?>\n");
+    xmp
+}
+
+/// Build the ZIP on a blocking thread and stream it out as it is written
+pub fn stream_zip(
+    name: &str,
+    data_list: Vec<AbstractData>,
+    sidecar_opt: Option<SidecarFormat>,
+) -> ZipDownload<ByteStream![Vec<u8>]> {
+    let (sender, mut receiver) = mpsc::channel(4);
+    tokio::task::spawn_blocking(move || {
+        // A failure midway leaves a truncated archive, which clients reject
+        if let Err(err) = write_zip(ChannelWriter::new(sender), data_list, sidecar_opt) {
+            handle_error(err.context("Failed to write ZIP download"));
+        }
+    });
+    let byte_stream = ByteStream! {
+        while let Some(chunk) = receiver.recv().await {
+            yield chunk;
+        }
+    };
+    ZipDownload {
+        inner: byte_stream,
+        disposition: Header::new("Content-Disposition", content_disposition(name)),
+    }
+}
+
+/// `filename` keeps to ASCII for old clients, `filename*` carries the real name
+fn content_disposition(name: &str) -> String {
+    let ascii_name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == ' ' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!(
+        "attachment; filename=\"{}.zip\"; filename*=UTF-8''{}.zip",
+        ascii_name,
+        uri_encode(name)
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xmp_sidecar_carries_the_date_override() {
+        let mut abstract_data = AbstractData::generate_random_data();
+        assert!(!xmp_sidecar(&abstract_data).contains("DateTimeOriginal"));
+
+        let millis = 1_700_000_000_000_i64;
+        abstract_data.object_mut().date_override = Some(millis as u128);
+        let expected = Local
+            .timestamp_millis_opt(millis)
+            .unwrap()
+            .to_rfc3339_opts(SecondsFormat::Secs, false);
+        assert!(xmp_sidecar(&abstract_data).contains(&format!(
+            "<exif:DateTimeOriginal>{}</exif:DateTimeOriginal>",
+            expected
+        )));
+    }
+
+    #[test]
+    fn content_disposition_keeps_the_real_name_in_filename_star() {
+        assert_eq!(
+            content_disposition("Trip 旅/2024"),
+            "attachment; filename=\"Trip __2024.zip\"; filename*=UTF-8''Trip%20%E6%97%85%2F2024.zip"
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use crate::operations::open_db::open_data_table;
+use crate::public::config::PRIVATE_CONFIG;
+use crate::public::constant::redb::DATA_TABLE;
+use crate::public::db::tree::TREE;
+use crate::public::structure::abstract_data::AbstractData;
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{Local, Utc};
+use log::info;
+use redb::ReadableTable;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{BufRead, BufWriter, Write};
+use std::path::Path;
+
+/// A backup is JSON Lines: this header, one `BackupEntry` per DATA_TABLE row,
+/// then a `BackupFooter` so a truncated file is never mistaken for a complete one
+pub const BACKUP_FORMAT: &str = "urocissa-backup";
+pub const BACKUP_VERSION: u32 = 1;
+
+const BACKUP_FILE_PREFIX: &str = "backup-";
+const BACKUP_FILE_SUFFIX: &str = ".jsonl";
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupHeader {
+    pub format: String,
+    pub version: u32,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupEntry {
+    pub key: String,
+    pub value: AbstractData,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BackupFooter {
+    pub entry_count: usize,
+}
+
+pub fn backup_header_line() -> Result<Vec<u8>> {
+    let header = BackupHeader {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        created_at: Utc::now().timestamp_millis(),
+    };
+    to_line(&header)
+}
+
+pub fn backup_entry_line(key: &str, value: AbstractData) -> Result<Vec<u8>> {
+    to_line(&BackupEntry {
+        key: key.to_string(),
+        value,
+    })
+}
+
+pub fn backup_footer_line(entry_count: usize) -> Result<Vec<u8>> {
+    to_line(&BackupFooter { entry_count })
+}
+
+fn to_line<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    let mut line = serde_json::to_vec(value).context("Failed to serialize backup line")?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+/// Write a backup of DATA_TABLE, read from a single transaction so it is consistent
+pub fn write_backup<W: Write>(mut writer: W) -> Result<usize> {
+    let data_table = open_data_table();
+    writer.write_all(&backup_header_line()?)?;
+    let mut entry_count = 0;
+    for entry in data_table.iter()? {
+        let (key, value) = entry?;
+        writer.write_all(&backup_entry_line(key.value(), value.value())?)?;
+        entry_count += 1;
+    }
+    writer.write_all(&backup_footer_line(entry_count)?)?;
+    writer.flush()?;
+    Ok(entry_count)
+}
+
+/// Replace DATA_TABLE with the contents of a backup. Everything happens in one write
+/// transaction that is only committed once the whole backup has been validated, so a
+/// bad backup leaves the database untouched.
+pub fn restore_backup<R: BufRead>(reader: R) -> Result<usize> {
+    let mut lines = reader.lines();
+
+    let header_line = lines
+        .next()
+        .ok_or_else(|| anyhow!("Backup is empty"))?
+        .context("Failed to read backup header")?;
+    let header: BackupHeader =
+        serde_json::from_str(&header_line).context("Backup header is not valid")?;
+    if header.format != BACKUP_FORMAT {
+        bail!("Not a backup file: format is {:?}", header.format);
+    }
+    if header.version != BACKUP_VERSION {
+        bail!("Unsupported backup version {}", header.version);
+    }
+
+    let txn = TREE
+        .in_disk
+        .begin_write()
+        .context("Failed to begin write transaction")?;
+    let entry_count = {
+        let mut table = txn
+            .open_table(DATA_TABLE)
+            .context("Failed to open DATA_TABLE")?;
+        table.retain(|_, _| false)?;
+
+        let mut seen_keys = HashSet::new();
+        let mut footer_opt = None;
+        for (line_index, line) in lines.enumerate() {
+            let line_number = line_index + 2;
+            let line = line.with_context(|| format!("Failed to read line {}", line_number))?;
+            if footer_opt.is_some() {
+                bail!(
+                    "Unexpected content after the footer at line {}",
+                    line_number
+                );
+            }
+            if let Ok(footer) = serde_json::from_str::<BackupFooter>(&line) {
+                footer_opt = Some(footer);
+                continue;
+            }
+
+            let entry: BackupEntry = serde_json::from_str(&line)
+                .with_context(|| format!("Line {} is not a valid backup entry", line_number))?;
+            if entry.key != entry.value.hash().as_str() {
+                bail!(
+                    "Key {} at line {} does not match its data",
+                    entry.key,
+                    line_number
+                );
+            }
+            if !seen_keys.insert(entry.key.clone()) {
+                bail!("Duplicate key {} at line {}", entry.key, line_number);
+            }
+            table.insert(entry.key.as_str(), entry.value)?;
+        }
+
+        let footer = footer_opt.ok_or_else(|| anyhow!("Backup is truncated: footer missing"))?;
+        if footer.entry_count != seen_keys.len() {
+            bail!(
+                "Backup is incomplete: footer expects {} entries, found {}",
+                footer.entry_count,
+                seen_keys.len()
+            );
+        }
+        seen_keys.len()
+    };
+    txn.commit().context("Failed to commit restore")?;
+    Ok(entry_count)
+}
+
+/// Write a backup into `backup_path` and keep only the newest `backup_keep` files
+pub fn run_scheduled_backup(backup_path: &Path) -> Result<()> {
+    fs::create_dir_all(backup_path)
+        .with_context(|| format!("Failed to create backup directory {:?}", backup_path))?;
+
+    let file_name = format!(
+        "{}{}{}",
+        BACKUP_FILE_PREFIX,
+        Local::now().format("%Y%m%d-%H%M%S"),
+        BACKUP_FILE_SUFFIX
+    );
+    let temp_path = backup_path.join(format!("{}.tmp", file_name));
+    let final_path = backup_path.join(&file_name);
+
+    let file = File::create(&temp_path)
+        .with_context(|| format!("Failed to create backup file {:?}", temp_path))?;
+    let entry_count = write_backup(BufWriter::new(file))
+        .with_context(|| format!("Failed to write backup file {:?}", temp_path))?;
+    fs::rename(&temp_path, &final_path)
+        .with_context(|| format!("Failed to move backup into {:?}", final_path))?;
+    info!("Backed up {} entries to {:?}", entry_count, final_path);
+
+    // Timestamps in the names sort chronologically
+    let mut backup_list: Vec<_> = fs::read_dir(backup_path)?
+        .filter_map(|dir_entry| dir_entry.ok())
+        .map(|dir_entry| dir_entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with(BACKUP_FILE_PREFIX) && name.ends_with(BACKUP_FILE_SUFFIX)
+                })
+        })
+        .collect();
+    backup_list.sort();
+    let remove_count = backup_list
+        .len()
+        .saturating_sub(PRIVATE_CONFIG.backup_keep.max(1));
+    for path in backup_list.into_iter().take(remove_count) {
+        fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove old backup {:?}", path))?;
+        info!("Removed old backup {:?}", path);
+    }
+    Ok(())
+}
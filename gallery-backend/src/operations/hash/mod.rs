@@ -31,3 +31,14 @@ pub fn generate_random_hash() -> ArrayString<64> {
     let hash = ArrayString::<64>::from(&hash).unwrap();
     hash
 }
+
+/// Hamming distance between two perceptual hashes
+pub fn phash_distance(a: &[u8], b: &[u8]) -> u32 {
+    let common: u32 = a
+        .iter()
+        .zip(b.iter())
+        .map(|(x, y)| (x ^ y).count_ones())
+        .sum();
+    // Treat missing trailing bytes as fully different
+    common + 8 * a.len().abs_diff(b.len()) as u32
+}
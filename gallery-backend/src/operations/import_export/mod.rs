@@ -0,0 +1,266 @@
+use crate::operations::open_db::open_data_table;
+use crate::public::constant::runtime::INDEX_RUNTIME;
+use crate::public::structure::abstract_data::AbstractData;
+use crate::public::structure::object::ObjectSchema;
+use crate::router::get::get_export::ExportEntry;
+use crate::tasks::actor::album::AlbumSelfUpdateTask;
+use crate::tasks::batcher::flush_tree::FlushTreeTask;
+use crate::tasks::batcher::update_tree::UpdateTreeTask;
+use crate::tasks::{BATCH_COORDINATOR, INDEX_COORDINATOR};
+use anyhow::{Context, Result, bail};
+use arrayvec::ArrayString;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// A field whose local and imported values disagree; the local value is always kept
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportConflict {
+    pub hash: String,
+    pub field: String,
+    pub local: Value,
+    pub imported: Value,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub entry_count: usize,
+    pub updated_count: usize,
+    pub created_album_count: usize,
+    /// Photos and videos in the export that do not exist on this instance
+    pub missing: Vec<String>,
+    pub conflicts: Vec<ImportConflict>,
+}
+
+struct ImportPlan {
+    report: ImportReport,
+    write_list: Vec<AbstractData>,
+    album_id_set: HashSet<ArrayString<64>>,
+}
+
+/// Merge a file produced by `/get/get-export` into the database.
+/// Records are matched by hash; tags and album memberships are unioned, favorites are kept
+/// if either side has them, and descriptions, titles and shares only fill in what is missing.
+/// With `dry_run` nothing is written and the report shows what would happen.
+pub async fn merge_export(path: PathBuf, dry_run: bool) -> Result<ImportReport> {
+    let plan = tokio::task::spawn_blocking(move || -> Result<ImportPlan> {
+        let file = File::open(&path).with_context(|| format!("Failed to open {:?}", path))?;
+        plan_import(BufReader::new(file))
+    })
+    .await??;
+
+    let mut report = plan.report;
+    report.dry_run = dry_run;
+    if dry_run || plan.write_list.is_empty() {
+        return Ok(report);
+    }
+
+    INDEX_COORDINATOR
+        .execute_batch_waiting(FlushTreeTask::insert(plan.write_list))
+        .await
+        .context("Failed to execute FlushTreeTask")?;
+    BATCH_COORDINATOR
+        .execute_batch_waiting(UpdateTreeTask)
+        .await
+        .context("Failed to execute UpdateTreeTask")?;
+    for album_id in plan.album_id_set {
+        BATCH_COORDINATOR
+            .execute_waiting(AlbumSelfUpdateTask::new(album_id))
+            .await??;
+    }
+    Ok(report)
+}
+
+fn plan_import<R: Read>(reader: R) -> Result<ImportPlan> {
+    let entry_list: Vec<ExportEntry> =
+        serde_json::from_reader(reader).context("File is not a valid export")?;
+
+    let mut seen_keys = HashSet::new();
+    for entry in &entry_list {
+        if entry.key != entry.value.hash().as_str() {
+            bail!("Key {} does not match its data", entry.key);
+        }
+        if !seen_keys.insert(entry.key.clone()) {
+            bail!("Duplicate key {}", entry.key);
+        }
+    }
+
+    let data_table = open_data_table();
+    let mut report = ImportReport {
+        entry_count: entry_list.len(),
+        ..Default::default()
+    };
+    let mut local_map = HashMap::new();
+    for entry in &entry_list {
+        if let Some(guard) = data_table.get(entry.key.as_str())? {
+            local_map.insert(entry.key.clone(), guard.value());
+        }
+    }
+
+    // Albums are imported first so memberships can point at the ones created here
+    let mut known_album_set = HashSet::new();
+    let mut write_list = Vec::new();
+    let mut album_id_set = HashSet::new();
+    for entry in &entry_list {
+        let AbstractData::Album(imported) = &entry.value else {
+            continue;
+        };
+        match local_map.get(&entry.key) {
+            Some(AbstractData::Album(local)) => {
+                let mut merged = local.clone();
+                merge_object(&mut merged.object, &imported.object, &mut report.conflicts);
+                match (&merged.metadata.title, &imported.metadata.title) {
+                    (None, Some(title)) => merged.metadata.title = Some(title.clone()),
+                    (Some(local_title), Some(title)) if local_title != title => push_conflict(
+                        &mut report.conflicts,
+                        &entry.key,
+                        "title",
+                        local_title,
+                        title,
+                    ),
+                    _ => {}
+                }
+                for (url, share) in &imported.metadata.share_list {
+                    match merged.metadata.share_list.get(url) {
+                        None => {
+                            merged.metadata.share_list.insert(*url, share.clone());
+                        }
+                        Some(local_share) if local_share != share => push_conflict(
+                            &mut report.conflicts,
+                            &entry.key,
+                            &format!("share {}", url),
+                            local_share,
+                            share,
+                        ),
+                        Some(_) => {}
+                    }
+                }
+                known_album_set.insert(merged.object.id);
+                if merged.object != local.object || merged.metadata != local.metadata {
+                    report.updated_count += 1;
+                    write_list.push(AbstractData::Album(merged));
+                }
+            }
+            Some(_) => push_conflict(
+                &mut report.conflicts,
+                &entry.key,
+                "type",
+                &local_map[&entry.key].object().obj_type,
+                &imported.object.obj_type,
+            ),
+            None => {
+                // Statistics and cover are recomputed from this instance's members. Accounts
+                // differ between instances, so the album belongs to the importer: the admin.
+                let mut album = imported.clone();
+                album.metadata.cover = None;
+                album.object.owner = None;
+                album.metadata.granted_users.clear();
+                known_album_set.insert(album.object.id);
+                album_id_set.insert(album.object.id);
+                report.created_album_count += 1;
+                write_list.push(AbstractData::Album(album));
+            }
+        }
+    }
+
+    for entry in &entry_list {
+        if matches!(entry.value, AbstractData::Album(_)) {
+            continue;
+        }
+        let Some(local) = local_map.get(&entry.key) else {
+            report.missing.push(entry.key.clone());
+            continue;
+        };
+        if local.object().obj_type != entry.value.object().obj_type {
+            push_conflict(
+                &mut report.conflicts,
+                &entry.key,
+                "type",
+                &local.object().obj_type,
+                &entry.value.object().obj_type,
+            );
+            continue;
+        }
+
+        let mut merged = local.clone();
+        merge_object(
+            merged.object_mut(),
+            entry.value.object(),
+            &mut report.conflicts,
+        );
+        if let (Some(albums), Some(imported_albums)) = (merged.albums_mut(), entry.value.albums()) {
+            for album_id in imported_albums {
+                let is_known = known_album_set.contains(album_id)
+                    || matches!(
+                        data_table
+                            .get(album_id.as_str())?
+                            .map(|guard| guard.value()),
+                        Some(AbstractData::Album(_))
+                    );
+                if is_known && albums.insert(*album_id) {
+                    album_id_set.insert(*album_id);
+                }
+            }
+        }
+        if merged.object() != local.object() || merged.albums() != local.albums() {
+            report.updated_count += 1;
+            write_list.push(merged);
+        }
+    }
+
+    Ok(ImportPlan {
+        report,
+        write_list,
+        album_id_set,
+    })
+}
+
+fn merge_object(
+    local: &mut ObjectSchema,
+    imported: &ObjectSchema,
+    conflicts: &mut Vec<ImportConflict>,
+) {
+    local.tags.extend(imported.tags.iter().cloned());
+    local.is_favorite |= imported.is_favorite;
+    match (&local.description, &imported.description) {
+        (None, Some(description)) => local.description = Some(description.clone()),
+        (Some(local_description), Some(description)) if local_description != description => {
+            push_conflict(
+                conflicts,
+                &local.id,
+                "description",
+                local_description,
+                description,
+            )
+        }
+        _ => {}
+    }
+}
+
+fn push_conflict<T: Serialize>(
+    conflicts: &mut Vec<ImportConflict>,
+    hash: &str,
+    field: &str,
+    local: &T,
+    imported: &T,
+) {
+    conflicts.push(ImportConflict {
+        hash: hash.to_string(),
+        field: field.to_string(),
+        local: serde_json::to_value(local).unwrap_or(Value::Null),
+        imported: serde_json::to_value(imported).unwrap_or(Value::Null),
+    });
+}
+
+/// Command line entry point: `urocissa import-export <file> [--dry-run]`
+pub fn run_import_export_cli(path: &Path, dry_run: bool) -> Result<()> {
+    let report = INDEX_RUNTIME.block_on(merge_export(path.to_path_buf(), dry_run))?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
@@ -2,7 +2,11 @@ use super::video_ffprobe::video_duration;
 use crate::{
     operations::indexation::generate_ffmpeg::create_silent_ffmpeg_command,
     process::info::process_image_info,
-    public::{structure::abstract_data::AbstractData, tui::DASHBOARD},
+    public::{
+        storage::{STORAGE, fetch_imported},
+        structure::abstract_data::AbstractData,
+        tui::DASHBOARD,
+    },
 };
 use anyhow::Context;
 use anyhow::Result;
@@ -20,13 +24,16 @@ static REGEX_OUT_TIME_US: LazyLock<Regex> =
 
 /// Compresses a video file, reporting progress by parsing ffmpeg's output.
 pub fn generate_compressed_video(abstract_data: &mut AbstractData) -> Result<()> {
-    let duration_result = video_duration(&abstract_data.imported_path_string());
+    // The original may only be in remote storage, e.g. when reprocessing after indexing
+    let imported_path = fetch_imported(abstract_data)?;
+    let imported_path_string = imported_path.to_string_lossy().into_owned();
+    let duration_result = video_duration(&imported_path_string);
     let duration = match duration_result {
         // Handle static GIFs by delegating to the image processor.
         Ok(d) if (d * 1000.0) as u32 == 100 => {
             info!(
                 "Static GIF detected. Processing as image: {:?}",
-                abstract_data.imported_path_string()
+                imported_path
             );
             abstract_data.convert_to_image();
             return process_image_info(abstract_data);
@@ -38,7 +45,7 @@ pub fn generate_compressed_video(abstract_data: &mut AbstractData) -> Result<()>
         {
             info!(
                 "Potentially corrupt or non-standard GIF. Processing as image: {:?}",
-                abstract_data.imported_path_string()
+                imported_path
             );
             abstract_data.convert_to_image();
             return process_image_info(abstract_data);
@@ -47,7 +54,7 @@ pub fn generate_compressed_video(abstract_data: &mut AbstractData) -> Result<()>
         Err(err) => {
             return Err(anyhow::anyhow!(
                 "Failed to get video duration for {:?}: {}",
-                abstract_data.imported_path_string(),
+                imported_path,
                 err
             ));
         }
@@ -57,7 +64,7 @@ pub fn generate_compressed_video(abstract_data: &mut AbstractData) -> Result<()>
     cmd.args([
         "-y", // Overwrite output file if it exists
         "-i",
-        &abstract_data.imported_path_string(),
+        &imported_path_string,
         "-vf",
         // Scale video to a max height of 720p, ensuring dimensions are even.
         &format!(
@@ -101,5 +108,9 @@ pub fn generate_compressed_video(abstract_data: &mut AbstractData) -> Result<()>
     child
         .wait()
         .context("Failed to wait for ffmpeg child process")?;
+    STORAGE.put(
+        &abstract_data.compressed_key(),
+        &abstract_data.compressed_path(),
+    )?;
     Ok(())
 }
@@ -1,16 +1,24 @@
+use crate::operations::indexation::generate_ffmpeg::create_silent_ffmpeg_command;
+use crate::public::constant::RAW_IMAGE_EXTENSIONS;
+use crate::public::storage::{STORAGE, fetch_imported};
 use crate::public::structure::abstract_data::AbstractData;
 use anyhow::{Context, Result, bail};
-use image::DynamicImage;
+use image::{DynamicImage, ImageFormat};
 use std::fs::read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+/// Upper bound on embedded JPEG candidates tried per file, since compressed RAW
+/// sensor data can contain the SOI byte pattern by chance
+const MAX_EMBEDDED_PREVIEW_CANDIDATES: usize = 16;
 
 /// Generate a `DynamicImage` either from the original image or
 /// from its thumbnail, adding *context* at every fallible step.
 pub fn generate_dynamic_image(abstract_data: &AbstractData) -> Result<DynamicImage> {
     let img_path = if abstract_data.is_image() {
-        abstract_data.imported_path()
+        fetch_imported(abstract_data)?
     } else {
-        PathBuf::from(abstract_data.thumbnail_path())
+        STORAGE.fetch(&abstract_data.thumbnail_key())?
     };
 
     let dynamic_image =
@@ -23,10 +31,30 @@ fn decode_image(file_path: &PathBuf) -> Result<DynamicImage> {
     let file_in_memory =
         read(file_path).context(format!("failed to read file into memory: {:?}", file_path))?;
 
-    let decoders: Vec<fn(&Vec<u8>) -> Result<DynamicImage>> = vec![image_crate_decoder];
+    let is_raw = file_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| RAW_IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()));
+
+    // TIFF-based RAW files also decode with the image crate, but only to the small
+    // thumbnail in their first IFD, so the full-size preview has to be tried first.
+    // ffmpeg comes last and covers HEIC/HEIF/AVIF.
+    let decoders: Vec<fn(&Path, &[u8]) -> Result<DynamicImage>> = if is_raw {
+        vec![
+            embedded_preview_decoder,
+            image_crate_decoder,
+            ffmpeg_decoder,
+        ]
+    } else {
+        vec![
+            image_crate_decoder,
+            embedded_preview_decoder,
+            ffmpeg_decoder,
+        ]
+    };
 
     for decoder in decoders {
-        match decoder(&file_in_memory) {
+        match decoder(file_path, &file_in_memory) {
             Ok(decoded_image) => return Ok(decoded_image),
             Err(_) => continue,
         }
@@ -35,8 +63,59 @@ fn decode_image(file_path: &PathBuf) -> Result<DynamicImage> {
     bail!("all decoders failed for file: {:?}", file_path);
 }
 
-fn image_crate_decoder(file_in_memory: &Vec<u8>) -> Result<DynamicImage> {
+fn image_crate_decoder(_file_path: &Path, file_in_memory: &[u8]) -> Result<DynamicImage> {
     let dynamic_image = image::load_from_memory(file_in_memory)
         .context("image crate failed to decode image from memory")?;
     Ok(dynamic_image)
 }
+
+/// RAW files (CR2, CR3, NEF, ARW, DNG, ...) carry JPEG previews next to the sensor
+/// data; decode every embedded JPEG found and keep the largest one.
+fn embedded_preview_decoder(_file_path: &Path, file_in_memory: &[u8]) -> Result<DynamicImage> {
+    let candidate_offsets = file_in_memory
+        .windows(3)
+        .enumerate()
+        .filter(|(_, window)| *window == [0xFF, 0xD8, 0xFF])
+        .map(|(offset, _)| offset)
+        .take(MAX_EMBEDDED_PREVIEW_CANDIDATES);
+
+    let largest_preview = candidate_offsets
+        .filter_map(|offset| {
+            image::load_from_memory_with_format(&file_in_memory[offset..], ImageFormat::Jpeg).ok()
+        })
+        .max_by_key(|preview| preview.width() as u64 * preview.height() as u64);
+
+    largest_preview.context("no decodable embedded JPEG preview found")
+}
+
+/// Let ffmpeg decode the first frame and hand it back as PNG
+fn ffmpeg_decoder(file_path: &Path, _file_in_memory: &[u8]) -> Result<DynamicImage> {
+    let output = create_silent_ffmpeg_command()
+        .arg("-i")
+        .arg(file_path)
+        .args([
+            "-frames:v",
+            "1",
+            "-f",
+            "image2pipe",
+            "-vcodec",
+            "png",
+            "pipe:1",
+        ])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .context(format!("failed to spawn ffmpeg for {:?}", file_path))?;
+
+    if !output.status.success() || output.stdout.is_empty() {
+        bail!(
+            "ffmpeg exited with status {:?} for {:?}",
+            output.status.code().unwrap_or(-1),
+            file_path
+        );
+    }
+
+    let dynamic_image = image::load_from_memory_with_format(&output.stdout, ImageFormat::Png)
+        .context("failed to decode ffmpeg output")?;
+    Ok(dynamic_image)
+}
@@ -3,10 +3,14 @@ use crate::{
         indexation::generate_ffmpeg::create_silent_ffmpeg_command,
         utils::resize::small_width_height,
     },
-    public::structure::abstract_data::AbstractData,
+    public::{
+        storage::{STORAGE, fetch_imported},
+        structure::abstract_data::AbstractData,
+    },
 };
 use anyhow::{Context, Result, anyhow};
 use image::{DynamicImage, ImageFormat};
+use std::path::Path;
 use std::process::Stdio;
 
 /// Generate a JPEG thumbnail for an **image** asset, propagating
@@ -42,6 +46,10 @@ pub fn generate_thumbnail_for_image(
             "failed to save JPEG thumbnail to {:?}",
             abstract_data.compressed_path()
         ))?;
+    STORAGE.put(
+        &abstract_data.compressed_key(),
+        &abstract_data.compressed_path(),
+    )?;
 
     Ok(())
 }
@@ -53,6 +61,7 @@ pub fn generate_thumbnail_for_video(abstract_data: &AbstractData) -> Result<()>
     let (width, height) = (abstract_data.width(), abstract_data.height());
     let (thumb_width, thumb_height) = small_width_height(width, height, 1280);
     let thumbnail_path = abstract_data.thumbnail_path();
+    let imported_path = fetch_imported(abstract_data)?;
 
     // Create target directory tree if missing
     std::fs::create_dir_all(abstract_data.compressed_path_parent())
@@ -63,7 +72,7 @@ pub fn generate_thumbnail_for_video(abstract_data: &AbstractData) -> Result<()>
     cmd.args([
         "-y",
         "-i",
-        &abstract_data.imported_path_string(),
+        &imported_path.to_string_lossy(),
         "-ss",
         "0",
         "-vframes",
@@ -86,6 +95,7 @@ pub fn generate_thumbnail_for_video(abstract_data: &AbstractData) -> Result<()>
             status.code().unwrap_or(-1)
         ));
     }
+    STORAGE.put(&abstract_data.thumbnail_key(), Path::new(&thumbnail_path))?;
 
     Ok(())
 }
@@ -1,4 +1,5 @@
 use super::video_ffprobe::video_width_height;
+use crate::public::storage::fetch_imported;
 use crate::public::structure::abstract_data::AbstractData;
 use anyhow::{Context, Result};
 use image::DynamicImage;
@@ -12,7 +13,9 @@ pub fn generate_image_width_height(dynamic_image: &DynamicImage) -> (u32, u32) {
 /// Probe a video file using `ffprobe` (through `video_width_height`) to
 /// obtain `(width, height)`, adding explicit context to every `?` site.
 pub fn generate_video_width_height(abstract_data: &AbstractData) -> Result<(u32, u32)> {
-    let imported = abstract_data.imported_path_string();
+    let imported = fetch_imported(abstract_data)?
+        .to_string_lossy()
+        .into_owned();
 
     let width = video_width_height("width", &imported)
         .context(format!("failed to obtain video width for {:?}", imported))?;
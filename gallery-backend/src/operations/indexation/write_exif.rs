@@ -0,0 +1,216 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::{Local, TimeZone};
+use std::{fs, path::Path};
+
+const TAG_EXIF_IFD_POINTER: u16 = 0x8769;
+const TAG_DATE_TIME_ORIGINAL: u16 = 0x9003;
+const TYPE_ASCII: u16 = 2;
+
+/// Write a copy of the JPEG at `source` to `dest` with its `DateTimeOriginal` tag set.
+///
+/// The tag is a fixed-width `YYYY:MM:DD HH:MM:SS` string, so patching it never moves
+/// any other data. `source` is never modified, since originals are content-addressed.
+/// Files without an existing tag are reported as an error and no copy is written.
+pub fn write_date_time_original(source: &Path, dest: &Path, timestamp_millis: u128) -> Result<()> {
+    let date_time = i64::try_from(timestamp_millis)
+        .ok()
+        .and_then(|millis| Local.timestamp_millis_opt(millis).single())
+        .ok_or_else(|| anyhow!("timestamp {} is out of range", timestamp_millis))?;
+    let value = date_time.format("%Y:%m:%d %H:%M:%S").to_string();
+
+    let mut bytes = fs::read(source).context(format!("failed to read file {:?}", source))?;
+    let offset = find_date_time_original(&bytes)
+        .ok_or_else(|| anyhow!("no DateTimeOriginal tag found in {:?}", source))?;
+    bytes[offset..offset + value.len()].copy_from_slice(value.as_bytes());
+
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .context(format!("failed to create directory tree for {:?}", parent))?;
+    }
+    // Write beside the copy and rename so a crash never leaves a truncated file
+    let temp_path = dest.with_extension("exif.tmp");
+    fs::write(&temp_path, &bytes).context(format!("failed to write file {:?}", temp_path))?;
+    fs::rename(&temp_path, dest)
+        .context(format!("failed to rename {:?} to {:?}", temp_path, dest))?;
+    Ok(())
+}
+
+/// Byte offset of the `DateTimeOriginal` value inside a JPEG file
+fn find_date_time_original(bytes: &[u8]) -> Option<usize> {
+    if bytes.get(0..2)? != [0xFF, 0xD8] {
+        return None;
+    }
+
+    // Walk the marker segments up to the start of the image data
+    let mut position = 2;
+    loop {
+        let marker = bytes.get(position..position + 2)?;
+        if marker[0] != 0xFF || marker[1] == 0xDA || marker[1] == 0xD9 {
+            return None;
+        }
+        let length = u16::from_be_bytes(bytes.get(position + 2..position + 4)?.try_into().ok()?);
+        let data_start = position + 4;
+        if marker[1] == 0xE1 && bytes.get(data_start..data_start + 6)? == b"Exif\0\0" {
+            return find_in_tiff(bytes, data_start + 6);
+        }
+        position += 2 + length as usize;
+    }
+}
+
+fn find_in_tiff(bytes: &[u8], tiff_start: usize) -> Option<usize> {
+    let little_endian = match bytes.get(tiff_start..tiff_start + 2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let read_u16 = |offset: usize| -> Option<u16> {
+        let raw = bytes.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if little_endian {
+            u16::from_le_bytes(raw)
+        } else {
+            u16::from_be_bytes(raw)
+        })
+    };
+    let read_u32 = |offset: usize| -> Option<u32> {
+        let raw = bytes.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if little_endian {
+            u32::from_le_bytes(raw)
+        } else {
+            u32::from_be_bytes(raw)
+        })
+    };
+    // Entry of `tag` in the IFD at `ifd_offset`, as (type, count, value or offset)
+    let find_entry = |ifd_offset: usize, tag: u16| -> Option<(u16, u32, u32)> {
+        let entry_count = read_u16(tiff_start + ifd_offset)? as usize;
+        for index in 0..entry_count {
+            let entry = tiff_start + ifd_offset + 2 + index * 12;
+            if read_u16(entry)? == tag {
+                return Some((
+                    read_u16(entry + 2)?,
+                    read_u32(entry + 4)?,
+                    read_u32(entry + 8)?,
+                ));
+            }
+        }
+        None
+    };
+
+    if read_u16(tiff_start + 2)? != 42 {
+        return None;
+    }
+    let ifd0_offset = read_u32(tiff_start + 4)? as usize;
+    let (_, _, exif_ifd_offset) = find_entry(ifd0_offset, TAG_EXIF_IFD_POINTER)?;
+    let (field_type, count, value_offset) =
+        find_entry(exif_ifd_offset as usize, TAG_DATE_TIME_ORIGINAL)?;
+    if field_type != TYPE_ASCII || count < 20 {
+        return None;
+    }
+
+    let offset = tiff_start + value_offset as usize;
+    (offset + 19 <= bytes.len()).then_some(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Smallest JPEG carrying an Exif IFD with a DateTimeOriginal tag
+    fn jpeg_with_date(little_endian: bool) -> Vec<u8> {
+        let u16_bytes = |value: u16| {
+            if little_endian {
+                value.to_le_bytes()
+            } else {
+                value.to_be_bytes()
+            }
+        };
+        let u32_bytes = |value: u32| {
+            if little_endian {
+                value.to_le_bytes()
+            } else {
+                value.to_be_bytes()
+            }
+        };
+        let entry = |tag: u16, field_type: u16, count: u32, value: u32| {
+            [
+                &u16_bytes(tag)[..],
+                &u16_bytes(field_type),
+                &u32_bytes(count),
+                &u32_bytes(value),
+            ]
+            .concat()
+        };
+
+        let mut tiff = if little_endian {
+            b"II".to_vec()
+        } else {
+            b"MM".to_vec()
+        };
+        tiff.extend(u16_bytes(42));
+        tiff.extend(u32_bytes(8));
+        // IFD0 at 8 points to the Exif IFD at 26
+        tiff.extend(u16_bytes(1));
+        tiff.extend(entry(TAG_EXIF_IFD_POINTER, 4, 1, 26));
+        tiff.extend(u32_bytes(0));
+        // Exif IFD at 26 points to the value at 44
+        tiff.extend(u16_bytes(1));
+        tiff.extend(entry(TAG_DATE_TIME_ORIGINAL, TYPE_ASCII, 20, 44));
+        tiff.extend(u32_bytes(0));
+        tiff.extend(b"2000:01:01 00:00:00\0");
+
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        jpeg.extend((2 + 6 + tiff.len() as u16).to_be_bytes());
+        jpeg.extend(b"Exif\0\0");
+        jpeg.extend(tiff);
+        jpeg.extend([0xFF, 0xD9]);
+        jpeg
+    }
+
+    fn temp_dir() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("urocissa-exif-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn finds_the_tag_in_either_byte_order() {
+        for little_endian in [true, false] {
+            let jpeg = jpeg_with_date(little_endian);
+            let offset = find_date_time_original(&jpeg).unwrap();
+            assert_eq!(&jpeg[offset..offset + 19], b"2000:01:01 00:00:00");
+        }
+    }
+
+    #[test]
+    fn writes_the_date_to_a_copy_only() {
+        let dir = temp_dir();
+        let source = dir.join("original.jpg");
+        let dest = dir.join("copy/original.jpg");
+        let original = jpeg_with_date(true);
+        fs::write(&source, &original).unwrap();
+
+        let millis = Local
+            .with_ymd_and_hms(2024, 1, 31, 12, 34, 56)
+            .unwrap()
+            .timestamp_millis() as u128;
+        write_date_time_original(&source, &dest, millis).unwrap();
+
+        assert_eq!(fs::read(&source).unwrap(), original);
+        let copy = fs::read(&dest).unwrap();
+        let offset = find_date_time_original(&copy).unwrap();
+        assert_eq!(&copy[offset..offset + 19], b"2024:01:31 12:34:56");
+        assert_eq!(copy.len(), original.len());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_files_without_the_tag() {
+        let dir = temp_dir();
+        let source = dir.join("plain.jpg");
+        let dest = dir.join("copy.jpg");
+        fs::write(&source, [0xFF, 0xD8, 0xFF, 0xD9]).unwrap();
+
+        assert!(write_date_time_original(&source, &dest, 0).is_err());
+        assert!(!dest.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
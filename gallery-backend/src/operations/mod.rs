@@ -1,10 +1,14 @@
 use crate::public::structure::album::ResolvedShare;
 
+pub mod archive;
+pub mod backup;
 pub mod hash;
+pub mod import_export;
 pub mod indexation;
 pub mod initialization;
 pub mod open_db;
 pub mod open_file;
+pub mod password;
 pub mod transitor;
 pub mod utils;
 
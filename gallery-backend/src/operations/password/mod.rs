@@ -0,0 +1,109 @@
+use crate::public::config::PRIVATE_CONFIG;
+use crate::public::db::tree::TREE;
+use crate::public::db::tree::setting::ADMIN_PASSWORD_HASH;
+use anyhow::{Context, Result, anyhow, bail};
+use argon2::Argon2;
+use argon2::password_hash::{
+    PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng,
+};
+use console::Term;
+use rand::Rng;
+use rand::distr::Alphanumeric;
+use sha2::{Digest, Sha256};
+use std::io::{self, BufRead, IsTerminal, Write};
+use subtle::ConstantTimeEq;
+
+/// Hash `password` with Argon2id and a random salt, in PHC string format
+pub fn hash_password(password: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let password_hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|err| anyhow!("Failed to hash password: {}", err))?;
+    Ok(password_hash.to_string())
+}
+
+/// Check `password` against a PHC string produced by [`hash_password`]
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    match PasswordHash::new(password_hash) {
+        Ok(parsed_hash) => Argon2::default()
+            .verify_password(password.as_bytes(), &parsed_hash)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Only the hash is stored; an empty password means the share is not protected
+pub fn hash_share_password(password: Option<String>) -> Result<Option<String>> {
+    password
+        .filter(|password| !password.is_empty())
+        .map(|password| hash_password(&password))
+        .transpose()
+}
+
+/// Shares created before hashing, or restored from an older backup, store the password in plaintext
+pub fn is_legacy_share_password(stored: &str) -> bool {
+    PasswordHash::new(stored).is_err()
+}
+
+/// Check a share password, accepting the legacy plaintext form until it is re-hashed
+pub fn verify_share_password(password: &str, stored: &str) -> bool {
+    if is_legacy_share_password(stored) {
+        constant_time_eq(password, stored)
+    } else {
+        verify_password(password, stored)
+    }
+}
+
+/// Random alphanumeric secret for refresh and API tokens
+pub fn random_token(length: usize) -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
+
+/// Random tokens carry enough entropy that a fast hash is enough to store them
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Compare without leaking where, or whether the lengths, differ
+pub fn constant_time_eq(left: &str, right: &str) -> bool {
+    Sha256::digest(left.as_bytes())
+        .ct_eq(&Sha256::digest(right.as_bytes()))
+        .into()
+}
+
+/// The stored hash takes precedence; the plaintext `PASSWORD` is only used until one is set
+pub fn verify_admin_password(password: &str) -> Result<bool> {
+    match TREE.read_setting(ADMIN_PASSWORD_HASH)? {
+        Some(password_hash) => Ok(verify_password(password, &password_hash)),
+        None => Ok(constant_time_eq(password, &PRIVATE_CONFIG.password)),
+    }
+}
+
+/// Command line entry point: `urocissa set-password`, reading the new password from the
+/// terminal without echo, or from stdin when it is piped in
+pub fn run_set_password_cli() -> Result<()> {
+    eprint!("New admin password: ");
+    io::stderr().flush()?;
+    let term = Term::stderr();
+    let password = if term.is_term() && io::stdin().is_terminal() {
+        term.read_secure_line()
+    } else {
+        let mut password = String::new();
+        io::stdin()
+            .lock()
+            .read_line(&mut password)
+            .map(|_| password)
+    }
+    .context("Failed to read password")?;
+    let password = password.trim_end_matches(['\r', '\n']);
+    if password.is_empty() {
+        bail!("Password must not be empty");
+    }
+    TREE.write_setting(ADMIN_PASSWORD_HASH, &hash_password(password)?)?;
+    println!("Admin password updated; PASSWORD in .env is no longer used");
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use crate::public::{
+    config::LIBRARY_CONFIG,
     db::tree_snapshot::read_tree_snapshot::MyCow,
     structure::{
         abstract_data::AbstractData,
@@ -75,9 +76,10 @@ pub fn abstract_data_to_database_timestamp_return(
     show_metadata: bool,
 ) -> DataBaseTimestampReturn {
     // Create the return object first (which computes timestamp from abstract_data)
+    let priority_list = LIBRARY_CONFIG.priority_list(&abstract_data);
     let result = DataBaseTimestampReturn::new(
         abstract_data.clone(),
-        &crate::public::constant::DEFAULT_PRIORITY_LIST,
+        &priority_list,
         timestamp,
         show_download,
     );
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use crate::operations::hash::phash_distance;
+
+/// A BK-tree keyed by perceptual hash, answering "every value within N bits" queries
+/// without comparing against every stored hash.
+#[derive(Debug, Clone, Default)]
+pub struct BkTree<T> {
+    nodes: Vec<BkNode<T>>,
+}
+
+#[derive(Debug, Clone)]
+struct BkNode<T> {
+    phash: Vec<u8>,
+    value: T,
+    children: HashMap<u32, usize>,
+}
+
+impl<T: Copy> BkTree<T> {
+    pub fn new() -> Self {
+        Self { nodes: Vec::new() }
+    }
+
+    pub fn insert(&mut self, phash: Vec<u8>, value: T) {
+        let new_index = self.nodes.len();
+        if new_index == 0 {
+            self.nodes.push(BkNode {
+                phash,
+                value,
+                children: HashMap::new(),
+            });
+            return;
+        }
+
+        let mut current = 0;
+        loop {
+            let distance = phash_distance(&self.nodes[current].phash, &phash);
+            match self.nodes[current].children.get(&distance) {
+                Some(&child) => current = child,
+                None => {
+                    self.nodes[current].children.insert(distance, new_index);
+                    break;
+                }
+            }
+        }
+        self.nodes.push(BkNode {
+            phash,
+            value,
+            children: HashMap::new(),
+        });
+    }
+
+    /// Return every `(value, distance)` whose hash is within `max_distance` bits of `phash`
+    pub fn find(&self, phash: &[u8], max_distance: u32) -> Vec<(T, u32)> {
+        let mut result = Vec::new();
+        if self.nodes.is_empty() {
+            return result;
+        }
+
+        let mut stack = vec![0];
+        while let Some(current) = stack.pop() {
+            let node = &self.nodes[current];
+            let distance = phash_distance(&node.phash, phash);
+            if distance <= max_distance {
+                result.push((node.value, distance));
+            }
+            // Triangle inequality: only children in [d - max, d + max] can match
+            let lower = distance.saturating_sub(max_distance);
+            let upper = distance.saturating_add(max_distance);
+            for (&edge, &child) in &node.children {
+                if lower <= edge && edge <= upper {
+                    stack.push(child);
+                }
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,55 @@
+use crate::operations::hash::generate_random_hash;
+use crate::operations::utils::sync_filter::{find_sync_path, sync_path_config};
+use crate::public::db::tree::TREE;
+use crate::public::structure::album::Album;
+use crate::tasks::BATCH_COORDINATOR;
+use crate::tasks::batcher::flush_tree::FlushTreeTask;
+use anyhow::Result;
+use arrayvec::ArrayString;
+use log::info;
+use std::path::Path;
+use tokio::sync::Mutex;
+use tokio::task::spawn_blocking;
+
+/// Files of one new folder are indexed concurrently; only the first may create its album
+static FOLDER_ALBUM_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Album for the folder containing `path` when its sync path has `folderAlbums` enabled.
+/// The album is titled with the folder path relative to the sync path and created on first sight;
+/// files directly in the sync path belong to no album.
+pub async fn folder_album_id(path: &Path) -> Result<Option<ArrayString<64>>> {
+    if !sync_path_config(path).is_some_and(|config| config.folder_albums) {
+        return Ok(None);
+    }
+    let (Some(sync_path), Some(folder)) = (find_sync_path(path), path.parent()) else {
+        return Ok(None);
+    };
+    let relative_folder = match folder.strip_prefix(sync_path) {
+        Ok(relative) if !relative.as_os_str().is_empty() => relative,
+        _ => return Ok(None),
+    };
+
+    let title = relative_folder
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let folder = folder.to_string_lossy().into_owned();
+
+    let _lock = FOLDER_ALBUM_LOCK.lock().await;
+
+    let folder_key = folder.clone();
+    if let Some(album_id) = spawn_blocking(move || TREE.read_folder_album(&folder_key)).await?? {
+        return Ok(Some(album_id));
+    }
+
+    let album_id = generate_random_hash();
+    let album = Album::new(album_id, Some(title.clone()));
+    BATCH_COORDINATOR
+        .execute_batch_waiting(FlushTreeTask::insert(vec![album.into_abstract_data()]))
+        .await?;
+    info!("Created album '{}' for folder {:?}", title, folder);
+    spawn_blocking(move || TREE.write_folder_album(&folder, &album_id)).await??;
+
+    Ok(Some(album_id))
+}
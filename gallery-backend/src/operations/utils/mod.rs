@@ -1,2 +1,7 @@
+pub mod bk_tree;
+pub mod folder_album;
+pub mod path;
 pub mod resize;
+pub mod sync_filter;
 pub mod timestamp;
+pub mod uri;
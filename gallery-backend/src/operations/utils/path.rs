@@ -0,0 +1,51 @@
+use path_clean::PathClean;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Canonical form of `path`, used for sync paths and stored aliases so they can be compared.
+/// Paths that no longer exist, such as a deleted file or an offline mount, are resolved through
+/// their nearest existing ancestor, or else only made absolute.
+pub fn canonical_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = fs::canonicalize(path) {
+        return canonical;
+    }
+    let absolute = std::path::absolute(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .clean();
+    for ancestor in absolute.ancestors().skip(1) {
+        if let Ok(canonical_ancestor) = fs::canonicalize(ancestor) {
+            let relative = absolute
+                .strip_prefix(ancestor)
+                .expect("ancestor is a prefix of the path");
+            return canonical_ancestor.join(relative);
+        }
+    }
+    absolute
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_missing_files_through_their_parent() {
+        let dir = std::env::temp_dir();
+        let missing = dir.join("urocissa-missing-dir").join("photo.jpg");
+        assert_eq!(
+            canonical_path(&missing),
+            fs::canonicalize(&dir)
+                .unwrap()
+                .join("urocissa-missing-dir")
+                .join("photo.jpg")
+        );
+    }
+
+    #[test]
+    fn makes_relative_paths_absolute() {
+        let relative = Path::new("./some/../missing.jpg");
+        assert_eq!(
+            canonical_path(relative),
+            fs::canonicalize(".").unwrap().join("missing.jpg")
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use crate::operations::utils::path::canonical_path;
+use crate::public::config::{LIBRARY_CONFIG, PRIVATE_CONFIG, SyncPathConfig};
+use crate::public::constant::{IGNORE_FILE_NAME, VALID_IMAGE_EXTENSIONS, VALID_VIDEO_EXTENSIONS};
+use crate::public::error_data::handle_error;
+use anyhow::anyhow;
+use dashmap::DashMap;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock},
+    time::SystemTime,
+};
+
+/// Compiled ignore rules and thresholds of one sync path
+struct SyncFilter {
+    exclude: Gitignore,
+    include: Option<Gitignore>,
+    min_file_size: Option<u64>,
+    min_width: Option<u32>,
+    min_height: Option<u32>,
+}
+
+static SYNC_FILTERS: LazyLock<HashMap<PathBuf, SyncFilter>> = LazyLock::new(|| {
+    PRIVATE_CONFIG
+        .sync_path
+        .iter()
+        .map(|sync_path| {
+            let sync_path_config = sync_path_config(sync_path).cloned().unwrap_or_default();
+            let filter = SyncFilter {
+                exclude: build_gitignore(sync_path, &sync_path_config.exclude),
+                include: (!sync_path_config.include.is_empty())
+                    .then(|| build_gitignore(sync_path, &sync_path_config.include)),
+                min_file_size: sync_path_config.min_file_size,
+                min_width: sync_path_config.min_width,
+                min_height: sync_path_config.min_height,
+            };
+            (sync_path.clone(), filter)
+        })
+        .collect()
+});
+
+/// Parsed `.urocissaignore` files by directory, reloaded when the file changes
+static IGNORE_FILE_CACHE: LazyLock<DashMap<PathBuf, (SystemTime, Arc<Gitignore>)>> =
+    LazyLock::new(DashMap::new);
+
+fn build_gitignore(root: &Path, pattern_list: &[String]) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+    for pattern in pattern_list {
+        if let Err(e) = builder.add_line(None, pattern) {
+            panic!("Invalid pattern '{}' in config.json: {}", pattern, e);
+        }
+    }
+    builder
+        .build()
+        .unwrap_or_else(|e| panic!("Invalid patterns for {:?} in config.json: {}", root, e))
+}
+
+/// Settings of the sync path containing `path`, which may be given in non-canonical form
+/// or no longer exist
+pub fn sync_path_config(path: &Path) -> Option<&'static SyncPathConfig> {
+    LIBRARY_CONFIG.sync_path_config(&canonical_path(path))
+}
+
+/// The most specific sync path containing the canonical `path`
+pub fn find_sync_path(path: &Path) -> Option<&'static PathBuf> {
+    PRIVATE_CONFIG
+        .sync_path
+        .iter()
+        .filter(|sync_path| path.starts_with(sync_path))
+        .max_by_key(|sync_path| sync_path.components().count())
+}
+
+pub fn is_valid_media_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .map(|ext| {
+            VALID_IMAGE_EXTENSIONS.contains(&ext.as_str())
+                || VALID_VIDEO_EXTENSIONS.contains(&ext.as_str())
+        })
+        .unwrap_or(false)
+}
+
+/// Whether `path` is excluded by its sync path's patterns or a `.urocissaignore` file.
+/// Anything inside an excluded directory is excluded as well.
+pub fn is_excluded(path: &Path, is_dir: bool) -> bool {
+    let Some(sync_path) = find_sync_path(path) else {
+        return false;
+    };
+    if let Some(parent) = path.parent()
+        && parent != sync_path.as_path()
+        && parent.starts_with(sync_path)
+        && is_excluded(parent, true)
+    {
+        return true;
+    }
+
+    // Like .gitignore, a deeper ignore file takes precedence over the ones above it
+    let mut directory = path.parent();
+    while let Some(current) = directory
+        && current.starts_with(sync_path)
+    {
+        if let Some(gitignore) = load_ignore_file(current) {
+            let matched = gitignore.matched(path, is_dir);
+            if matched.is_ignore() {
+                return true;
+            }
+            if matched.is_whitelist() {
+                return false;
+            }
+        }
+        directory = current.parent();
+    }
+
+    SYNC_FILTERS
+        .get(sync_path)
+        .is_some_and(|filter| filter.exclude.matched(path, is_dir).is_ignore())
+}
+
+/// Media file that passes the ignore and include rules; cheap enough for every event
+pub fn is_included(path: &Path) -> bool {
+    if !is_valid_media_file(path) || is_excluded(path, false) {
+        return false;
+    }
+    let filter = find_sync_path(path).and_then(|sync_path| SYNC_FILTERS.get(sync_path));
+    match filter.and_then(|filter| filter.include.as_ref()) {
+        Some(include) => include.matched(path, false).is_ignore(),
+        None => true,
+    }
+}
+
+/// `is_included` plus the size and dimension thresholds, which need the finished file
+pub fn should_index(path: &Path) -> bool {
+    if !is_included(path) {
+        return false;
+    }
+    let Some(filter) = find_sync_path(path).and_then(|sync_path| SYNC_FILTERS.get(sync_path))
+    else {
+        return true;
+    };
+
+    if let Some(min_file_size) = filter.min_file_size
+        && fs::metadata(path).is_ok_and(|metadata| metadata.len() < min_file_size)
+    {
+        return false;
+    }
+
+    // Only formats whose header the image crate can read are checked; videos,
+    // RAW and HEIC files always pass
+    if (filter.min_width.is_some() || filter.min_height.is_some())
+        && let Ok((width, height)) = image::image_dimensions(path)
+        && (filter.min_width.is_some_and(|min_width| width < min_width)
+            || filter
+                .min_height
+                .is_some_and(|min_height| height < min_height))
+    {
+        return false;
+    }
+
+    true
+}
+
+fn load_ignore_file(directory: &Path) -> Option<Arc<Gitignore>> {
+    let ignore_file_path = directory.join(IGNORE_FILE_NAME);
+    let Ok(modified) = fs::metadata(&ignore_file_path).and_then(|metadata| metadata.modified())
+    else {
+        IGNORE_FILE_CACHE.remove(directory);
+        return None;
+    };
+
+    if let Some(cached) = IGNORE_FILE_CACHE.get(directory)
+        && cached.0 == modified
+    {
+        return Some(cached.1.clone());
+    }
+
+    let (gitignore, error_option) = Gitignore::new(&ignore_file_path);
+    if let Some(e) = error_option {
+        handle_error(anyhow!("Failed to parse {:?}: {}", ignore_file_path, e));
+    }
+    let gitignore = Arc::new(gitignore);
+    IGNORE_FILE_CACHE.insert(directory.to_path_buf(), (modified, gitignore.clone()));
+    Some(gitignore)
+}
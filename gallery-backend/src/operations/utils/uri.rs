@@ -0,0 +1,25 @@
+/// Percent-encode everything except the unreserved characters of RFC 3986, as both SigV4
+/// and the `filename*` parameter of `Content-Disposition` require
+pub fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_unreserved_characters_only() {
+        assert_eq!(uri_encode("a-Z_0.~"), "a-Z_0.~");
+        assert_eq!(uri_encode("a b/c+"), "a%20b%2Fc%2B");
+        assert_eq!(uri_encode("旅"), "%E6%97%85");
+    }
+}
@@ -14,6 +14,7 @@ use crate::operations::indexation::generate_thumbnail::{
 use crate::operations::indexation::generate_width_height::{
     generate_image_width_height, generate_video_width_height,
 };
+use crate::public::storage::fetch_imported;
 use crate::public::structure::abstract_data::AbstractData;
 use anyhow::{Context, Result};
 
@@ -52,8 +53,8 @@ pub fn process_image_info(abstract_data: &mut AbstractData) -> Result<()> {
 
 /// Re‑build all metadata for an existing **image** (e.g. after replace / fix).
 pub fn regenerate_metadata_for_image(abstract_data: &mut AbstractData) -> Result<()> {
-    // Refresh size from filesystem
-    let size = metadata(abstract_data.imported_path())
+    // Refresh size from filesystem, downloading the original first if it is stored remotely
+    let size = metadata(fetch_imported(abstract_data)?)
         .context("failed to read metadata for imported image file")?
         .len();
     abstract_data.set_size(size);
@@ -72,6 +73,15 @@ pub fn process_video_info(abstract_data: &mut AbstractData) -> Result<()> {
         *exif_vec = exif;
     }
 
+    // Record duration so that it can be queried without probing again
+    if let Some(duration) = abstract_data
+        .exif_vec()
+        .and_then(|exif_vec| exif_vec.get("duration"))
+        .and_then(|duration| duration.trim().parse::<f64>().ok())
+    {
+        abstract_data.set_duration(duration);
+    }
+
     // Get logical dimensions and fix if rotated
     let (width, height) =
         generate_video_width_height(abstract_data).context("failed to obtain video width/height")?;
@@ -96,8 +106,8 @@ pub fn process_video_info(abstract_data: &mut AbstractData) -> Result<()> {
 
 /// Re‑build all metadata for an existing **video** file.
 pub fn regenerate_metadata_for_video(abstract_data: &mut AbstractData) -> Result<()> {
-    // Refresh size from filesystem metadata
-    let size = metadata(abstract_data.imported_path())
+    // Refresh size from filesystem metadata, downloading the original first if it is stored remotely
+    let size = metadata(fetch_imported(abstract_data)?)
         .context("failed to read metadata for imported video file")?
         .len();
     abstract_data.set_size(size);
@@ -1,10 +1,13 @@
+use crate::operations::utils::path::canonical_path;
+use crate::public::constant::{DEFAULT_PRIORITY_LIST, TIMESTAMP_FIELDS};
+use crate::public::structure::abstract_data::AbstractData;
 use dotenv::dotenv;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fs::{self, File},
     io,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::LazyLock,
 };
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Hash, Default)]
@@ -35,13 +38,165 @@ pub static PUBLIC_CONFIG: LazyLock<PublicConfig> = LazyLock::new(|| {
     }
 });
 
+/// Library settings read from config.json; unlike PublicConfig these are never sent to clients
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LibraryConfig {
+    /// Fields tried in order when computing an item's timestamp
+    pub priority_list: Option<Vec<String>>,
+    /// Settings for individual sync paths, keyed by the same paths as SYNC_PATH
+    pub sync_paths: HashMap<PathBuf, SyncPathConfig>,
+    /// Where originals and derived files are kept
+    pub storage: StorageConfig,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SyncPathConfig {
+    /// Overrides the global priority list for files under this path
+    pub priority_list: Option<Vec<String>>,
+    /// What happens to an item once every file it was imported from is gone
+    pub on_remove: RemovePolicy,
+    /// Poll for changes every N seconds instead of relying on filesystem events,
+    /// for NFS/SMB mounts where those never arrive
+    pub poll_interval_secs: Option<u64>,
+    /// Warn when no event has arrived from this path for this many hours
+    pub max_silence_hours: Option<u64>,
+    /// Gitignore-style patterns, relative to the sync path, of files to skip
+    pub exclude: Vec<String>,
+    /// Gitignore-style patterns; when given, only matching files are imported
+    pub include: Vec<String>,
+    /// Skip files smaller than this many bytes
+    pub min_file_size: Option<u64>,
+    /// Skip images narrower than this many pixels
+    pub min_width: Option<u32>,
+    /// Skip images shorter than this many pixels
+    pub min_height: Option<u32>,
+    /// Add each file to an album named after its folder, relative to the sync path
+    pub folder_albums: bool,
+    /// Serve originals from this path instead of copying them into ./object/imported
+    pub reference_only: bool,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum RemovePolicy {
+    /// Keep the imported copy and only mark the alias as missing
+    #[default]
+    Keep,
+    /// Move the item to the trash
+    Trash,
+    /// Delete the item from the database
+    Delete,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum StorageConfig {
+    /// Files stay under ./object
+    #[default]
+    Local,
+    /// Files are uploaded to an S3-compatible bucket; ./object only keeps working copies
+    S3(S3Config),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct S3Config {
+    /// Base URL of the service, e.g. `http://localhost:9000`; buckets are addressed path-style
+    pub endpoint: String,
+    pub bucket: String,
+    #[serde(default = "default_s3_region")]
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+impl LibraryConfig {
+    /// Settings of the most specific configured sync path containing `path`
+    pub fn sync_path_config(&self, path: &Path) -> Option<&SyncPathConfig> {
+        self.sync_paths
+            .iter()
+            .filter(|(sync_path, _)| path.starts_with(sync_path))
+            .max_by_key(|(sync_path, _)| sync_path.components().count())
+            .map(|(_, sync_path_config)| sync_path_config)
+    }
+
+    /// Priority list for an item: its sync path's list, else the global list, else the default
+    pub fn priority_list(&self, abstract_data: &AbstractData) -> Vec<&str> {
+        let sync_path_list = abstract_data.alias().iter().find_map(|file_modify| {
+            self.sync_path_config(Path::new(&file_modify.file))
+                .and_then(|sync_path_config| sync_path_config.priority_list.as_ref())
+        });
+        match sync_path_list.or(self.priority_list.as_ref()) {
+            Some(priority_list) => priority_list.iter().map(String::as_str).collect(),
+            None => DEFAULT_PRIORITY_LIST.to_vec(),
+        }
+    }
+}
+
+pub static LIBRARY_CONFIG: LazyLock<LibraryConfig> = LazyLock::new(|| {
+    let mut library_config: LibraryConfig = match File::open("config.json") {
+        Ok(file) => serde_json::from_reader(file).expect("Failed to parse config.json"),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => LibraryConfig::default(),
+        Err(err) => panic!("Failed to open config.json: {}", err),
+    };
+
+    // Reject unknown fields now rather than panicking later in compute_timestamp
+    let priority_lists = library_config.priority_list.iter().chain(
+        library_config
+            .sync_paths
+            .values()
+            .filter_map(|sync_path_config| sync_path_config.priority_list.as_ref()),
+    );
+    for priority_list in priority_lists {
+        for field in priority_list {
+            if !TIMESTAMP_FIELDS.contains(&field.as_str()) {
+                panic!(
+                    "Unknown priority list field '{}' in config.json, expected one of {:?}",
+                    field, TIMESTAMP_FIELDS
+                );
+            }
+        }
+    }
+
+    // Stored file paths are canonical, so the keys must be too
+    library_config.sync_paths = library_config
+        .sync_paths
+        .into_iter()
+        .map(|(path, sync_path_config)| (canonical_path(&path), sync_path_config))
+        .collect();
+
+    library_config
+});
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 pub struct PrivateConfig {
     pub password: String,
     pub sync_path: HashSet<PathBuf>,
     pub auth_key: Option<String>,
     pub discord_hook_url: Option<String>,
+    /// Directory for scheduled backups; unset disables them
+    pub backup_path: Option<PathBuf>,
+    #[serde(default = "default_backup_interval_hours")]
+    pub backup_interval_hours: u64,
+    /// Number of scheduled backups kept before the oldest are deleted
+    #[serde(default = "default_backup_keep")]
+    pub backup_keep: usize,
 }
+
+fn default_backup_interval_hours() -> u64 {
+    24
+}
+
+fn default_backup_keep() -> usize {
+    7
+}
+
 pub static PRIVATE_CONFIG: LazyLock<PrivateConfig> = LazyLock::new(|| {
     dotenv().ok();
 
@@ -54,13 +209,22 @@ pub static PRIVATE_CONFIG: LazyLock<PrivateConfig> = LazyLock::new(|| {
         }
     }
 
+    if let Some(ref backup_path) = result.backup_path
+        && backup_path.as_os_str().is_empty()
+    {
+        result.backup_path = None;
+    }
+
     let upload_path =
         fs::canonicalize(PathBuf::from("./upload")).expect("canonicalize(\"./upload\") failed");
 
-    result.sync_path.retain(|p| match fs::canonicalize(p) {
-        Ok(c) => c != upload_path,
-        Err(_) => p != &upload_path,
-    });
+    // Watch events and stored aliases are compared against these, so resolve them the same way
+    result.sync_path = result
+        .sync_path
+        .iter()
+        .map(|path| canonical_path(path))
+        .filter(|path| *path != upload_path)
+        .collect();
 
     result
 });
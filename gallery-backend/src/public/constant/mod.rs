@@ -12,11 +12,22 @@ pub const MAX_COPY_RETRIES: u32 = 3;
 
 pub const MAX_DELETE_ATTEMPTS: u64 = 5;
 
+pub const WATCHER_HEALTH_CHECK_INTERVAL_SECS: u64 = 60;
+
+pub const REFERENCE_VERIFY_INTERVAL_SECS: u64 = 24 * 60 * 60; // 24 hours
+
+pub const IGNORE_FILE_NAME: &str = ".urocissaignore";
+
 pub const SHOULD_SWAP_WIDTH_HEIGHT_ROTATION: &'static [&'static str] =
     &["90", "-90", "270", "-270"];
 
 pub const VALID_IMAGE_EXTENSIONS: &'static [&'static str] = &[
-    "jpg", "jpeg", "jfif", "jpe", "png", "tif", "tiff", "webp", "bmp",
+    "jpg", "jpeg", "jfif", "jpe", "png", "tif", "tiff", "webp", "bmp", "heic", "heif", "avif",
+    "cr2", "cr3", "nef", "nrw", "arw", "dng", "raf", "orf", "rw2", "pef", "srw",
+];
+
+pub const RAW_IMAGE_EXTENSIONS: &[&str] = &[
+    "cr2", "cr3", "nef", "nrw", "arw", "dng", "raf", "orf", "rw2", "pef", "srw",
 ];
 
 pub const VALID_VIDEO_EXTENSIONS: &'static [&'static str] = &[
@@ -25,3 +36,14 @@ pub const VALID_VIDEO_EXTENSIONS: &'static [&'static str] = &[
 
 pub const DEFAULT_PRIORITY_LIST: &'static [&'static str] =
     &["DateTimeOriginal", "filename", "modified", "scan_time"];
+
+/// Every field understood by `AbstractData::compute_timestamp`
+pub const TIMESTAMP_FIELDS: &[&str] = &[
+    "DateTimeOriginal",
+    "filename",
+    "modified",
+    "scan_time",
+    "random",
+];
+
+pub const DEFAULT_SIMILAR_THRESHOLD: u32 = 6;
@@ -3,3 +3,17 @@ use redb::TableDefinition;
 use crate::public::structure::abstract_data::AbstractData;
 
 pub const DATA_TABLE: TableDefinition<&str, AbstractData> = TableDefinition::new("database");
+
+pub const SMART_ALBUM_TABLE: TableDefinition<&str, &str> = TableDefinition::new("smart_album"); // album id -> expression as JSON
+
+pub const EXIF_COPY_TABLE: TableDefinition<&str, &str> = TableDefinition::new("exif_copy"); // hash -> storage key of the copy with the edited date
+
+pub const FOLDER_ALBUM_TABLE: TableDefinition<&str, &str> = TableDefinition::new("folder_album"); // folder path -> album id
+
+pub const USER_TABLE: TableDefinition<&str, &str> = TableDefinition::new("user"); // username -> user as JSON
+
+pub const SETTING_TABLE: TableDefinition<&str, &str> = TableDefinition::new("setting"); // setting name -> value
+
+pub const SESSION_TABLE: TableDefinition<&str, &str> = TableDefinition::new("session"); // jti -> session as JSON
+
+pub const API_TOKEN_TABLE: TableDefinition<&str, &str> = TableDefinition::new("api_token"); // token id -> API token as JSON
@@ -1,4 +1,5 @@
 pub mod expire;
 pub mod query_snapshot;
+pub mod similar;
 pub mod tree;
 pub mod tree_snapshot;
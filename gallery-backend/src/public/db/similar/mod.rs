@@ -0,0 +1,19 @@
+pub mod new;
+
+use dashmap::DashMap;
+use std::sync::LazyLock;
+
+use crate::public::structure::response::reduced_data::ReducedData;
+
+#[derive(Debug, Clone)]
+pub struct SimilarSnapshot {
+    pub version: u64, // VERSION_COUNT_TIMESTAMP the clusters were computed against
+    pub clusters: Vec<Vec<ReducedData>>,
+}
+
+#[derive(Debug)]
+pub struct Similar {
+    pub in_memory: &'static DashMap<u32, SimilarSnapshot>, // threshold -> clusters
+}
+
+pub static SIMILAR: LazyLock<Similar> = LazyLock::new(Similar::new);
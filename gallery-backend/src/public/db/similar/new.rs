@@ -0,0 +1,14 @@
+use dashmap::DashMap;
+use std::sync::LazyLock;
+
+use super::{Similar, SimilarSnapshot};
+
+static SIMILAR_IN_MEMORY: LazyLock<DashMap<u32, SimilarSnapshot>> = LazyLock::new(DashMap::new);
+
+impl Similar {
+    pub fn new() -> Self {
+        Self {
+            in_memory: &SIMILAR_IN_MEMORY,
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::public::constant::redb::API_TOKEN_TABLE;
+use crate::public::structure::api_token::ApiToken;
+use anyhow::{Context, Result};
+use redb::{ReadableDatabase, ReadableTable, TableError};
+
+use super::Tree;
+
+impl Tree {
+    pub fn read_api_token(&self, id: &str) -> Result<Option<ApiToken>> {
+        let read_txn = self
+            .in_disk
+            .begin_read()
+            .context("Failed to begin read transaction")?;
+        let table = match read_txn.open_table(API_TOKEN_TABLE) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return Ok(None),
+            Err(err) => return Err(err).context("Failed to open API_TOKEN_TABLE"),
+        };
+
+        match table.get(id).context("Failed to read API token entry")? {
+            Some(guard) => {
+                let api_token = serde_json::from_str(guard.value())
+                    .with_context(|| format!("Failed to parse API token {}", id))?;
+                Ok(Some(api_token))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Every API token, oldest first
+    pub fn read_api_tokens(&self) -> Result<Vec<ApiToken>> {
+        let read_txn = self
+            .in_disk
+            .begin_read()
+            .context("Failed to begin read transaction")?;
+        let table = match read_txn.open_table(API_TOKEN_TABLE) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return Ok(Vec::new()),
+            Err(err) => return Err(err).context("Failed to open API_TOKEN_TABLE"),
+        };
+
+        let mut api_tokens = Vec::new();
+        for entry in table
+            .iter()
+            .context("Failed to create iterator over API_TOKEN_TABLE")?
+        {
+            let (key, value) = entry.context("Failed to read API token entry")?;
+            let api_token: ApiToken = serde_json::from_str(value.value())
+                .with_context(|| format!("Failed to parse API token {}", key.value()))?;
+            api_tokens.push(api_token);
+        }
+        api_tokens.sort_by_key(|api_token| api_token.created_time);
+        Ok(api_tokens)
+    }
+
+    pub fn write_api_token(&self, api_token: &ApiToken) -> Result<()> {
+        let value = serde_json::to_string(api_token).context("Failed to serialize API token")?;
+        let txn = self
+            .in_disk
+            .begin_write()
+            .context("Failed to begin write transaction")?;
+        {
+            let mut table = txn
+                .open_table(API_TOKEN_TABLE)
+                .context("Failed to open API_TOKEN_TABLE")?;
+            table.insert(api_token.id.as_str(), value.as_str())?;
+        }
+        txn.commit().context("Failed to commit API token")?;
+        Ok(())
+    }
+
+    pub fn remove_api_token(&self, id: &str) -> Result<bool> {
+        let txn = self
+            .in_disk
+            .begin_write()
+            .context("Failed to begin write transaction")?;
+        let removed = {
+            let mut table = txn
+                .open_table(API_TOKEN_TABLE)
+                .context("Failed to open API_TOKEN_TABLE")?;
+            table.remove(id)?.is_some()
+        };
+        txn.commit().context("Failed to commit API token removal")?;
+        Ok(removed)
+    }
+
+    /// Remove every API token for which `keep` is false, returning how many were removed
+    pub fn retain_api_tokens(&self, keep: impl Fn(&ApiToken) -> bool) -> Result<usize> {
+        let txn = self
+            .in_disk
+            .begin_write()
+            .context("Failed to begin write transaction")?;
+        let removed = {
+            let mut table = txn
+                .open_table(API_TOKEN_TABLE)
+                .context("Failed to open API_TOKEN_TABLE")?;
+            let mut removed = 0;
+            table
+                .retain(|_, value| {
+                    let keep_api_token = serde_json::from_str::<ApiToken>(value)
+                        .map(|api_token| keep(&api_token))
+                        .unwrap_or(false);
+                    if !keep_api_token {
+                        removed += 1;
+                    }
+                    keep_api_token
+                })
+                .context("Failed to prune API_TOKEN_TABLE")?;
+            removed
+        };
+        txn.commit().context("Failed to commit API token removal")?;
+        Ok(removed)
+    }
+}
@@ -0,0 +1,48 @@
+use crate::public::constant::redb::EXIF_COPY_TABLE;
+use anyhow::{Context, Result};
+use redb::{ReadableDatabase, TableError};
+
+use super::Tree;
+
+impl Tree {
+    /// Storage key of the copy of `hash` whose EXIF carries the edited capture date, if any
+    pub fn read_exif_copy(&self, hash: &str) -> Result<Option<String>> {
+        let read_txn = self
+            .in_disk
+            .begin_read()
+            .context("Failed to begin read transaction")?;
+        let table = match read_txn.open_table(EXIF_COPY_TABLE) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return Ok(None),
+            Err(err) => return Err(err).context("Failed to open EXIF_COPY_TABLE"),
+        };
+
+        Ok(table
+            .get(hash)
+            .context("Failed to read EXIF copy entry")?
+            .map(|guard| guard.value().to_string()))
+    }
+
+    /// Record `key` as the EXIF copy of `hash`; `None` forgets the copy
+    pub fn write_exif_copy(&self, hash: &str, key_opt: Option<&str>) -> Result<()> {
+        let txn = self
+            .in_disk
+            .begin_write()
+            .context("Failed to begin write transaction")?;
+        {
+            let mut table = txn
+                .open_table(EXIF_COPY_TABLE)
+                .context("Failed to open EXIF_COPY_TABLE")?;
+            match key_opt {
+                Some(key) => {
+                    table.insert(hash, key)?;
+                }
+                None => {
+                    table.remove(hash)?;
+                }
+            }
+        }
+        txn.commit().context("Failed to commit EXIF copy")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use crate::public::constant::redb::FOLDER_ALBUM_TABLE;
+use anyhow::{Context, Result};
+use arrayvec::ArrayString;
+use redb::{ReadableDatabase, TableError};
+
+use super::Tree;
+
+impl Tree {
+    /// Album created for `folder` by a folder-album import, if any
+    pub fn read_folder_album(&self, folder: &str) -> Result<Option<ArrayString<64>>> {
+        let read_txn = self
+            .in_disk
+            .begin_read()
+            .context("Failed to begin read transaction")?;
+        let table = match read_txn.open_table(FOLDER_ALBUM_TABLE) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return Ok(None),
+            Err(err) => return Err(err).context("Failed to open FOLDER_ALBUM_TABLE"),
+        };
+
+        match table
+            .get(folder)
+            .context("Failed to read folder album entry")?
+        {
+            Some(guard) => {
+                let album_id = ArrayString::from(guard.value())
+                    .map_err(|_| anyhow::anyhow!("Invalid folder album id '{}'", guard.value()))?;
+                Ok(Some(album_id))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Remember `album_id` as the album of `folder`
+    pub fn write_folder_album(&self, folder: &str, album_id: &ArrayString<64>) -> Result<()> {
+        let txn = self
+            .in_disk
+            .begin_write()
+            .context("Failed to begin write transaction")?;
+        {
+            let mut table = txn
+                .open_table(FOLDER_ALBUM_TABLE)
+                .context("Failed to open FOLDER_ALBUM_TABLE")?;
+            table.insert(folder, &**album_id)?;
+        }
+        txn.commit().context("Failed to commit folder album")?;
+        Ok(())
+    }
+}
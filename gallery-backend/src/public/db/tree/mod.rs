@@ -1,12 +1,27 @@
+pub mod api_token;
+pub mod exif_copy;
+pub mod folder_album;
 pub mod new;
+pub mod phash_index;
 pub mod read_tags;
+pub mod session;
+pub mod setting;
+pub mod smart_album;
+pub mod text_index;
+pub mod user;
 
 use crate::public::structure::response::database_timestamp::DatabaseTimestamp;
+use phash_index::PhashIndex;
+use smart_album::SmartAlbumIndex;
 use std::sync::{Arc, LazyLock, RwLock, atomic::AtomicU64};
+use text_index::TextIndex;
 
 pub struct Tree {
     pub in_disk: &'static redb::Database,
     pub in_memory: &'static Arc<RwLock<Vec<DatabaseTimestamp>>>,
+    pub phash_index: &'static RwLock<PhashIndex>,
+    pub text_index: &'static RwLock<TextIndex>,
+    pub smart_album_index: &'static RwLock<SmartAlbumIndex>,
 }
 
 pub static TREE: LazyLock<Tree> = LazyLock::new(|| Tree::new());
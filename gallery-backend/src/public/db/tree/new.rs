@@ -1,4 +1,7 @@
 use super::Tree;
+use super::phash_index::PhashIndex;
+use super::smart_album::SmartAlbumIndex;
+use super::text_index::TextIndex;
 use crate::public::structure::response::database_timestamp::DatabaseTimestamp;
 use std::sync::{Arc, LazyLock, RwLock};
 
@@ -8,11 +11,23 @@ static TREE_SNAPSHOT_IN_MEMORY: LazyLock<Arc<RwLock<Vec<DatabaseTimestamp>>>> =
 static TREE_SNAPSHOT_IN_DISK: LazyLock<redb::Database> =
     LazyLock::new(|| redb::Database::create("./db/index.redb").unwrap());
 
+static TREE_PHASH_INDEX: LazyLock<RwLock<PhashIndex>> =
+    LazyLock::new(|| RwLock::new(PhashIndex::default()));
+
+static TREE_TEXT_INDEX: LazyLock<RwLock<TextIndex>> =
+    LazyLock::new(|| RwLock::new(TextIndex::default()));
+
+static TREE_SMART_ALBUM_INDEX: LazyLock<RwLock<SmartAlbumIndex>> =
+    LazyLock::new(|| RwLock::new(SmartAlbumIndex::default()));
+
 impl Tree {
     pub fn new() -> Self {
         Self {
             in_disk: &TREE_SNAPSHOT_IN_DISK,
             in_memory: &TREE_SNAPSHOT_IN_MEMORY,
+            phash_index: &TREE_PHASH_INDEX,
+            text_index: &TREE_TEXT_INDEX,
+            smart_album_index: &TREE_SMART_ALBUM_INDEX,
         }
     }
 }
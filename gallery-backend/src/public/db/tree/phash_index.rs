@@ -0,0 +1,48 @@
+use crate::operations::utils::bk_tree::BkTree;
+use crate::public::structure::abstract_data::AbstractData;
+use crate::public::structure::response::database_timestamp::DatabaseTimestamp;
+use arrayvec::ArrayString;
+use std::collections::HashMap;
+
+use super::Tree;
+
+/// Perceptual hash index over every image in the in-memory tree
+#[derive(Debug, Default)]
+pub struct PhashIndex {
+    bk_tree: BkTree<ArrayString<64>>,
+    phash_map: HashMap<ArrayString<64>, Vec<u8>>,
+}
+
+impl PhashIndex {
+    pub fn build(database_timestamp_vec: &[DatabaseTimestamp]) -> Self {
+        let mut index = Self::default();
+        for database_timestamp in database_timestamp_vec {
+            if let AbstractData::Image(img) = &database_timestamp.abstract_data
+                && let Some(phash) = &img.metadata.phash
+            {
+                index.bk_tree.insert(phash.clone(), img.object.id);
+                index.phash_map.insert(img.object.id, phash.clone());
+            }
+        }
+        index
+    }
+}
+
+impl Tree {
+    /// Hash -> phash distance of every image within `max_distance` of the image `hash`
+    pub fn find_similar(
+        &'static self,
+        hash: &ArrayString<64>,
+        max_distance: u32,
+    ) -> HashMap<ArrayString<64>, u32> {
+        let index = self.phash_index.read().unwrap();
+        match index.phash_map.get(hash) {
+            Some(phash) => index
+                .bk_tree
+                .find(phash, max_distance)
+                .into_iter()
+                .collect(),
+            None => HashMap::new(),
+        }
+    }
+}
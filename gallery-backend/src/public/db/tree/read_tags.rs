@@ -2,7 +2,7 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::{
     public::constant::redb::DATA_TABLE,
-    public::structure::{abstract_data::AbstractData, album::AlbumCombined},
+    public::structure::{abstract_data::AbstractData, album::AlbumCombined, user::UserScope},
 };
 use anyhow::{Context, Result};
 use dashmap::DashMap;
@@ -19,7 +19,8 @@ pub struct TagInfo {
 }
 
 impl Tree {
-    pub fn read_tags(&'static self) -> Vec<TagInfo> {
+    /// Tag counts over the whole library, or over what `scope_opt` can read
+    pub fn read_tags(&'static self, scope_opt: Option<&UserScope>) -> Vec<TagInfo> {
         let tag_counts: DashMap<String, AtomicUsize> = DashMap::new();
 
         self.in_memory
@@ -27,6 +28,9 @@ impl Tree {
             .unwrap()
             .iter()
             .par_bridge()
+            .filter(|database_timestamp| {
+                scope_opt.is_none_or(|scope| scope.can_read(&database_timestamp.abstract_data))
+            })
             .for_each(|database_timestamp| {
                 let abstract_data = &database_timestamp.abstract_data;
 
@@ -0,0 +1,114 @@
+use crate::public::constant::redb::SESSION_TABLE;
+use crate::public::structure::session::Session;
+use anyhow::{Context, Result};
+use redb::{ReadableDatabase, ReadableTable, TableError};
+
+use super::Tree;
+
+impl Tree {
+    pub fn read_session(&self, jti: &str) -> Result<Option<Session>> {
+        let read_txn = self
+            .in_disk
+            .begin_read()
+            .context("Failed to begin read transaction")?;
+        let table = match read_txn.open_table(SESSION_TABLE) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return Ok(None),
+            Err(err) => return Err(err).context("Failed to open SESSION_TABLE"),
+        };
+
+        match table.get(jti).context("Failed to read session entry")? {
+            Some(guard) => {
+                let session = serde_json::from_str(guard.value())
+                    .with_context(|| format!("Failed to parse session {}", jti))?;
+                Ok(Some(session))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Every session, oldest first
+    pub fn read_sessions(&self) -> Result<Vec<Session>> {
+        let read_txn = self
+            .in_disk
+            .begin_read()
+            .context("Failed to begin read transaction")?;
+        let table = match read_txn.open_table(SESSION_TABLE) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return Ok(Vec::new()),
+            Err(err) => return Err(err).context("Failed to open SESSION_TABLE"),
+        };
+
+        let mut sessions = Vec::new();
+        for entry in table
+            .iter()
+            .context("Failed to create iterator over SESSION_TABLE")?
+        {
+            let (key, value) = entry.context("Failed to read session entry")?;
+            let session: Session = serde_json::from_str(value.value())
+                .with_context(|| format!("Failed to parse session {}", key.value()))?;
+            sessions.push(session);
+        }
+        sessions.sort_by_key(|session| session.created_time);
+        Ok(sessions)
+    }
+
+    pub fn write_session(&self, session: &Session) -> Result<()> {
+        let value = serde_json::to_string(session).context("Failed to serialize session")?;
+        let txn = self
+            .in_disk
+            .begin_write()
+            .context("Failed to begin write transaction")?;
+        {
+            let mut table = txn
+                .open_table(SESSION_TABLE)
+                .context("Failed to open SESSION_TABLE")?;
+            table.insert(session.jti.as_str(), value.as_str())?;
+        }
+        txn.commit().context("Failed to commit session")?;
+        Ok(())
+    }
+
+    pub fn remove_session(&self, jti: &str) -> Result<bool> {
+        let txn = self
+            .in_disk
+            .begin_write()
+            .context("Failed to begin write transaction")?;
+        let removed = {
+            let mut table = txn
+                .open_table(SESSION_TABLE)
+                .context("Failed to open SESSION_TABLE")?;
+            table.remove(jti)?.is_some()
+        };
+        txn.commit().context("Failed to commit session removal")?;
+        Ok(removed)
+    }
+
+    /// Remove every session for which `keep` is false, returning how many were removed
+    pub fn retain_sessions(&self, keep: impl Fn(&Session) -> bool) -> Result<usize> {
+        let txn = self
+            .in_disk
+            .begin_write()
+            .context("Failed to begin write transaction")?;
+        let removed = {
+            let mut table = txn
+                .open_table(SESSION_TABLE)
+                .context("Failed to open SESSION_TABLE")?;
+            let mut removed = 0;
+            table
+                .retain(|_, value| {
+                    let keep_session = serde_json::from_str::<Session>(value)
+                        .map(|session| keep(&session))
+                        .unwrap_or(false);
+                    if !keep_session {
+                        removed += 1;
+                    }
+                    keep_session
+                })
+                .context("Failed to prune SESSION_TABLE")?;
+            removed
+        };
+        txn.commit().context("Failed to commit session removal")?;
+        Ok(removed)
+    }
+}
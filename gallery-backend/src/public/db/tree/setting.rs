@@ -0,0 +1,45 @@
+use crate::public::constant::redb::SETTING_TABLE;
+use anyhow::{Context, Result};
+use redb::{ReadableDatabase, TableError};
+
+use super::Tree;
+
+/// Argon2 hash of the admin password, set with `urocissa set-password`
+pub const ADMIN_PASSWORD_HASH: &str = "admin_password_hash";
+
+/// Layout version of the records in DATA_TABLE, see `migration::schema`
+pub const SCHEMA_VERSION: &str = "schema_version";
+
+impl Tree {
+    pub fn read_setting(&self, name: &str) -> Result<Option<String>> {
+        let read_txn = self
+            .in_disk
+            .begin_read()
+            .context("Failed to begin read transaction")?;
+        let table = match read_txn.open_table(SETTING_TABLE) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return Ok(None),
+            Err(err) => return Err(err).context("Failed to open SETTING_TABLE"),
+        };
+
+        Ok(table
+            .get(name)
+            .with_context(|| format!("Failed to read setting {}", name))?
+            .map(|guard| guard.value().to_string()))
+    }
+
+    pub fn write_setting(&self, name: &str, value: &str) -> Result<()> {
+        let txn = self
+            .in_disk
+            .begin_write()
+            .context("Failed to begin write transaction")?;
+        {
+            let mut table = txn
+                .open_table(SETTING_TABLE)
+                .context("Failed to open SETTING_TABLE")?;
+            table.insert(name, value)?;
+        }
+        txn.commit().context("Failed to commit setting")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use crate::public::constant::redb::SMART_ALBUM_TABLE;
+use crate::public::structure::abstract_data::AbstractData;
+use crate::public::structure::expression::Expression;
+use crate::public::structure::response::database_timestamp::DatabaseTimestamp;
+use crate::public::structure::user::UserScope;
+use anyhow::{Context, Result};
+use arrayvec::ArrayString;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use redb::{ReadableDatabase, ReadableTable, TableError};
+use std::collections::{HashMap, HashSet};
+
+use super::Tree;
+
+/// Members of every smart album, recomputed whenever the in-memory tree is rebuilt
+#[derive(Debug, Default)]
+pub struct SmartAlbumIndex {
+    membership: HashMap<ArrayString<64>, HashSet<ArrayString<64>>>,
+}
+
+impl SmartAlbumIndex {
+    /// Evaluate every smart album; albums owned by a user only ever match what that user can read
+    pub fn build(
+        smart_albums: HashMap<ArrayString<64>, Expression>,
+        database_timestamp_vec: &[DatabaseTimestamp],
+    ) -> Self {
+        let owner_map: HashMap<ArrayString<64>, &str> = database_timestamp_vec
+            .iter()
+            .filter_map(
+                |database_timestamp| match &database_timestamp.abstract_data {
+                    AbstractData::Album(album) if smart_albums.contains_key(&album.object.id) => {
+                        Some((album.object.id, album.object.owner.as_deref()?))
+                    }
+                    _ => None,
+                },
+            )
+            .collect();
+        let mut scope_map: HashMap<&str, UserScope> = HashMap::new();
+
+        let membership = smart_albums
+            .into_iter()
+            .map(|(album_id, expression)| {
+                let filter = expression.generate_filter();
+                let scope_opt = owner_map.get(&album_id).map(|owner| {
+                    &*scope_map
+                        .entry(owner)
+                        .or_insert_with(|| UserScope::build(owner, database_timestamp_vec))
+                });
+                let members = database_timestamp_vec
+                    .par_iter()
+                    .filter(|database_timestamp| {
+                        !matches!(database_timestamp.abstract_data, AbstractData::Album(_))
+                            && scope_opt.is_none_or(|scope| {
+                                scope.can_read(&database_timestamp.abstract_data)
+                            })
+                            && filter(database_timestamp)
+                    })
+                    .map(|database_timestamp| database_timestamp.abstract_data.hash())
+                    .collect();
+                (album_id, members)
+            })
+            .collect();
+        Self { membership }
+    }
+
+    /// Smart albums whose members differ from `previous`
+    pub fn changed_since(&self, previous: &SmartAlbumIndex) -> Vec<ArrayString<64>> {
+        self.membership
+            .iter()
+            .filter(|(album_id, members)| previous.membership.get(*album_id) != Some(*members))
+            .map(|(album_id, _)| *album_id)
+            .collect()
+    }
+}
+
+impl Tree {
+    /// Album id -> expression of every smart album stored on disk
+    pub fn read_smart_albums(&self) -> Result<HashMap<ArrayString<64>, Expression>> {
+        let read_txn = self
+            .in_disk
+            .begin_read()
+            .context("Failed to begin read transaction")?;
+        let table = match read_txn.open_table(SMART_ALBUM_TABLE) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return Ok(HashMap::new()),
+            Err(err) => return Err(err).context("Failed to open SMART_ALBUM_TABLE"),
+        };
+
+        let mut smart_albums = HashMap::new();
+        for entry in table
+            .iter()
+            .context("Failed to create iterator over SMART_ALBUM_TABLE")?
+        {
+            let (key, value) = entry.context("Failed to read smart album entry")?;
+            let album_id = ArrayString::from(key.value())
+                .map_err(|_| anyhow::anyhow!("Invalid smart album id '{}'", key.value()))?;
+            let expression: Expression =
+                serde_json::from_str(value.value()).with_context(|| {
+                    format!("Failed to parse expression of smart album {}", album_id)
+                })?;
+            smart_albums.insert(album_id, expression);
+        }
+        Ok(smart_albums)
+    }
+
+    /// Store `expression` as the definition of `album_id`; `None` turns it back into a regular album
+    pub fn write_smart_album(
+        &self,
+        album_id: &ArrayString<64>,
+        expression_opt: Option<&Expression>,
+    ) -> Result<()> {
+        let txn = self
+            .in_disk
+            .begin_write()
+            .context("Failed to begin write transaction")?;
+        {
+            let mut table = txn
+                .open_table(SMART_ALBUM_TABLE)
+                .context("Failed to open SMART_ALBUM_TABLE")?;
+            match expression_opt {
+                Some(expression) => {
+                    let json = serde_json::to_string(expression)
+                        .context("Failed to serialize smart album expression")?;
+                    table.insert(&**album_id, &*json)?;
+                }
+                None => {
+                    table.remove(&**album_id)?;
+                }
+            }
+        }
+        txn.commit().context("Failed to commit smart album")?;
+        Ok(())
+    }
+
+    /// Current members of the smart album `album_id`; empty for regular albums
+    pub fn smart_album_members(
+        &'static self,
+        album_id: &ArrayString<64>,
+    ) -> HashSet<ArrayString<64>> {
+        self.smart_album_index
+            .read()
+            .unwrap()
+            .membership
+            .get(album_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::hash::generate_random_hash;
+    use crate::public::structure::album::Album;
+
+    fn owned(mut abstract_data: AbstractData, owner: Option<&str>) -> DatabaseTimestamp {
+        abstract_data.object_mut().owner = owner.map(str::to_string);
+        DatabaseTimestamp {
+            abstract_data,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn user_smart_albums_only_match_their_owners_items() {
+        let alice_image = owned(AbstractData::generate_random_data(), Some("alice"));
+        let bob_image = owned(AbstractData::generate_random_data(), Some("bob"));
+        let bob_album_id = generate_random_hash();
+        let admin_album_id = generate_random_hash();
+        let database_timestamp_vec = vec![
+            owned(
+                Album::new(bob_album_id, None).into_abstract_data(),
+                Some("bob"),
+            ),
+            owned(Album::new(admin_album_id, None).into_abstract_data(), None),
+            alice_image,
+            bob_image,
+        ];
+        let every_image = Expression::ExtType("image".to_string());
+        let smart_albums = HashMap::from([
+            (bob_album_id, every_image.clone()),
+            (admin_album_id, every_image),
+        ]);
+
+        let index = SmartAlbumIndex::build(smart_albums, &database_timestamp_vec);
+        let alice_hash = database_timestamp_vec[2].abstract_data.hash();
+        let bob_hash = database_timestamp_vec[3].abstract_data.hash();
+        assert_eq!(index.membership[&bob_album_id], HashSet::from([bob_hash]));
+        assert_eq!(
+            index.membership[&admin_album_id],
+            HashSet::from([alice_hash, bob_hash])
+        );
+    }
+}
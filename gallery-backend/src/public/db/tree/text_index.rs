@@ -0,0 +1,116 @@
+use crate::public::structure::abstract_data::AbstractData;
+use crate::public::structure::response::database_timestamp::DatabaseTimestamp;
+use arrayvec::ArrayString;
+use std::collections::{BTreeMap, HashSet};
+
+use super::Tree;
+
+/// Inverted index from token to the ids whose text contains that token
+type PostingMap = BTreeMap<String, HashSet<ArrayString<64>>>;
+
+/// Tokenized descriptions and album titles of the in-memory tree
+#[derive(Debug, Default)]
+pub struct TextIndex {
+    description: PostingMap,
+    title: PostingMap,
+}
+
+/// Split text into lowercase alphanumeric words; CJK characters are single tokens
+/// because those scripts do not separate words with spaces.
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    for character in text.chars() {
+        if is_cjk(character) {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            tokens.push(character.to_string());
+        } else if character.is_alphanumeric() {
+            current.extend(character.to_lowercase());
+        } else if !current.is_empty() {
+            tokens.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn is_cjk(character: char) -> bool {
+    matches!(
+        character,
+        '\u{3040}'..='\u{30FF}' // Hiragana, Katakana
+            | '\u{3400}'..='\u{4DBF}' // CJK Extension A
+            | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+            | '\u{AC00}'..='\u{D7AF}' // Hangul syllables
+            | '\u{F900}'..='\u{FAFF}' // CJK Compatibility Ideographs
+    )
+}
+
+fn insert_text(posting_map: &mut PostingMap, id: ArrayString<64>, text: &str) {
+    for token in tokenize(text) {
+        posting_map.entry(token).or_default().insert(id);
+    }
+}
+
+/// Ids containing every query token, where the last token may be a prefix of a word
+fn search(posting_map: &PostingMap, query: &str) -> HashSet<ArrayString<64>> {
+    let tokens = tokenize(query);
+    let Some((last, rest)) = tokens.split_last() else {
+        return HashSet::new();
+    };
+
+    let mut result: Option<HashSet<ArrayString<64>>> = None;
+    for token in rest {
+        let ids = posting_map.get(token).cloned().unwrap_or_default();
+        result = Some(match result {
+            Some(previous) => previous.intersection(&ids).copied().collect(),
+            None => ids,
+        });
+    }
+
+    let prefix_ids: HashSet<ArrayString<64>> = posting_map
+        .range(last.clone()..)
+        .take_while(|(token, _)| token.starts_with(last.as_str()))
+        .flat_map(|(_, ids)| ids.iter().copied())
+        .collect();
+
+    match result {
+        Some(previous) => previous.intersection(&prefix_ids).copied().collect(),
+        None => prefix_ids,
+    }
+}
+
+impl TextIndex {
+    pub fn build(database_timestamp_vec: &[DatabaseTimestamp]) -> Self {
+        let mut index = Self::default();
+        for database_timestamp in database_timestamp_vec {
+            let (object, title) = match &database_timestamp.abstract_data {
+                AbstractData::Image(img) => (&img.object, None),
+                AbstractData::Video(vid) => (&vid.object, None),
+                AbstractData::Album(alb) => (&alb.object, alb.metadata.title.as_ref()),
+            };
+            if let Some(description) = &object.description {
+                insert_text(&mut index.description, object.id, description);
+            }
+            if let Some(title) = title {
+                insert_text(&mut index.title, object.id, title);
+            }
+        }
+        index
+    }
+}
+
+impl Tree {
+    /// Ids whose description matches every word of `query`
+    pub fn search_description(&'static self, query: &str) -> HashSet<ArrayString<64>> {
+        search(&self.text_index.read().unwrap().description, query)
+    }
+
+    /// Ids of albums whose title matches every word of `query`
+    pub fn search_title(&'static self, query: &str) -> HashSet<ArrayString<64>> {
+        search(&self.text_index.read().unwrap().title, query)
+    }
+}
@@ -0,0 +1,134 @@
+use crate::public::constant::redb::{DATA_TABLE, USER_TABLE};
+use crate::public::structure::abstract_data::AbstractData;
+use crate::public::structure::user::{User, UserScope};
+use anyhow::{Context, Result};
+use arrayvec::ArrayString;
+use redb::{ReadableDatabase, ReadableTable, TableError};
+use std::collections::HashSet;
+
+use super::Tree;
+
+impl Tree {
+    pub fn read_user(&self, username: &str) -> Result<Option<User>> {
+        let read_txn = self
+            .in_disk
+            .begin_read()
+            .context("Failed to begin read transaction")?;
+        let table = match read_txn.open_table(USER_TABLE) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return Ok(None),
+            Err(err) => return Err(err).context("Failed to open USER_TABLE"),
+        };
+
+        match table.get(username).context("Failed to read user entry")? {
+            Some(guard) => {
+                let user = serde_json::from_str(guard.value())
+                    .with_context(|| format!("Failed to parse user {}", username))?;
+                Ok(Some(user))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Every account, sorted by username
+    pub fn read_users(&self) -> Result<Vec<User>> {
+        let read_txn = self
+            .in_disk
+            .begin_read()
+            .context("Failed to begin read transaction")?;
+        let table = match read_txn.open_table(USER_TABLE) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return Ok(Vec::new()),
+            Err(err) => return Err(err).context("Failed to open USER_TABLE"),
+        };
+
+        let mut users = Vec::new();
+        for entry in table
+            .iter()
+            .context("Failed to create iterator over USER_TABLE")?
+        {
+            let (key, value) = entry.context("Failed to read user entry")?;
+            let user: User = serde_json::from_str(value.value())
+                .with_context(|| format!("Failed to parse user {}", key.value()))?;
+            users.push(user);
+        }
+        Ok(users)
+    }
+
+    pub fn write_user(&self, user: &User) -> Result<()> {
+        let value = serde_json::to_string(user).context("Failed to serialize user")?;
+        let txn = self
+            .in_disk
+            .begin_write()
+            .context("Failed to begin write transaction")?;
+        {
+            let mut table = txn
+                .open_table(USER_TABLE)
+                .context("Failed to open USER_TABLE")?;
+            table.insert(user.username.as_str(), value.as_str())?;
+        }
+        txn.commit().context("Failed to commit user")?;
+        Ok(())
+    }
+
+    /// Remove the account; whatever it owns passes to the admin and its album grants are dropped,
+    /// so an account created later under the same name starts empty
+    pub fn remove_user(&self, username: &str) -> Result<bool> {
+        let txn = self
+            .in_disk
+            .begin_write()
+            .context("Failed to begin write transaction")?;
+        let removed = {
+            let mut table = txn
+                .open_table(USER_TABLE)
+                .context("Failed to open USER_TABLE")?;
+            table.remove(username)?.is_some()
+        };
+        if removed {
+            let mut data_table = txn
+                .open_table(DATA_TABLE)
+                .context("Failed to open DATA_TABLE")?;
+            let mut changed_vec = Vec::new();
+            for entry in data_table
+                .iter()
+                .context("Failed to create iterator over DATA_TABLE")?
+            {
+                let (_, value) = entry.context("Failed to read data entry")?;
+                let mut abstract_data = value.value();
+                let mut changed = false;
+                if abstract_data.object().owner.as_deref() == Some(username) {
+                    abstract_data.object_mut().owner = None;
+                    changed = true;
+                }
+                if let AbstractData::Album(album) = &mut abstract_data {
+                    changed |= album.metadata.granted_users.remove(username);
+                }
+                if changed {
+                    changed_vec.push(abstract_data);
+                }
+            }
+            for abstract_data in changed_vec {
+                data_table.insert(&*abstract_data.hash(), abstract_data)?;
+            }
+        }
+        txn.commit().context("Failed to commit user removal")?;
+        Ok(removed)
+    }
+
+    /// Scope of `username` over the current in-memory tree
+    pub fn user_scope(&self, username: &str) -> UserScope {
+        UserScope::build(username, &self.in_memory.read().unwrap())
+    }
+
+    /// Hashes of everything `username` can read
+    pub fn readable_hash_set(&self, username: &str) -> HashSet<ArrayString<64>> {
+        let tree_guard = self.in_memory.read().unwrap();
+        let scope = UserScope::build(username, &tree_guard);
+        tree_guard
+            .iter()
+            .map(|database_timestamp| &database_timestamp.abstract_data)
+            .filter(|abstract_data| scope.can_read(abstract_data))
+            .map(|abstract_data| abstract_data.hash())
+            .collect()
+    }
+}
@@ -6,7 +6,7 @@ pub mod read_tree_snapshot;
 
 use std::sync::LazyLock;
 
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
 
 use crate::public::structure::response::reduced_data::ReducedData;
 
@@ -14,6 +14,8 @@ use crate::public::structure::response::reduced_data::ReducedData;
 pub struct TreeSnapshot {
     pub in_disk: &'static redb::Database,
     pub in_memory: &'static DashMap<u128, Vec<ReducedData>>,
+    /// Snapshots not ordered by date, which have no meaningful scrollbar
+    pub undated: &'static DashSet<u128>,
 }
 
 pub static TREE_SNAPSHOT: LazyLock<TreeSnapshot> = LazyLock::new(|| TreeSnapshot::new());
@@ -1,4 +1,4 @@
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
 use std::sync::LazyLock;
 
 use crate::public::structure::response::reduced_data::ReducedData;
@@ -11,11 +11,14 @@ static TREE_SNAPSHOT_IN_DISK: LazyLock<redb::Database> =
 static TREE_SNAPSHOT_IN_MEMORY: LazyLock<DashMap<u128, Vec<ReducedData>>> =
     LazyLock::new(|| DashMap::new());
 
+static TREE_SNAPSHOT_UNDATED: LazyLock<DashSet<u128>> = LazyLock::new(DashSet::new);
+
 impl TreeSnapshot {
     pub fn new() -> Self {
         Self {
             in_disk: &TREE_SNAPSHOT_IN_DISK,
             in_memory: &TREE_SNAPSHOT_IN_MEMORY,
+            undated: &TREE_SNAPSHOT_UNDATED,
         }
     }
 }
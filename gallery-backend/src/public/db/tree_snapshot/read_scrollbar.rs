@@ -11,6 +11,10 @@ use redb::ReadableTable;
 impl TreeSnapshot {
     pub fn read_scrollbar(&'static self, timestamp: u128) -> Vec<ScrollBarData> {
         let start_time = Instant::now();
+        // Month labels and positions only make sense when the items are sorted by date
+        if self.undated.contains(&timestamp) {
+            return Vec::new();
+        }
         let tree_snapshot = self.read_tree_snapshot(&timestamp).unwrap();
         let mut scroll_bar_data_vec = Vec::new();
         let mut last_year = None;
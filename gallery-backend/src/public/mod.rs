@@ -2,5 +2,6 @@ pub mod config;
 pub mod constant;
 pub mod db;
 pub mod error_data;
+pub mod storage;
 pub mod structure;
 pub mod tui;
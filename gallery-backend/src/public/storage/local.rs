@@ -0,0 +1,51 @@
+use super::Storage;
+use crate::process::io::copy_with_retry;
+use anyhow::{Context, Result};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Keeps every object as a plain file below `root`
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+}
+
+impl Storage for LocalStorage {
+    fn put(&self, key: &str, source: &Path) -> Result<()> {
+        let dest_path = self.root.join(key);
+        // Files produced in the working tree are already in place
+        if source == dest_path {
+            return Ok(());
+        }
+        if let Some(parent) = dest_path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create directory tree for {:?}", parent))?;
+        }
+        copy_with_retry(source, &dest_path)
+            .with_context(|| format!("failed to copy file from {:?} to {:?}", source, dest_path))?;
+        Ok(())
+    }
+
+    fn fetch(&self, key: &str) -> Result<PathBuf> {
+        Ok(self.root.join(key))
+    }
+
+    fn evict(&self, _key: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        match fs::remove_file(self.root.join(key)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove {}", key))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+pub mod local;
+pub mod s3;
+
+use crate::public::config::{LIBRARY_CONFIG, StorageConfig};
+use crate::public::db::tree::TREE;
+use crate::public::structure::abstract_data::AbstractData;
+use anyhow::Result;
+use local::LocalStorage;
+use s3::S3Storage;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Local directory mirroring the storage keys; for remote backends it only holds
+/// working copies and downloaded objects, any of which may be deleted at any time
+pub const OBJECT_ROOT: &str = "./object";
+
+/// Persistent home of originals and derived files, addressed by keys such as
+/// `imported/ab/<hash>.jpg` or `compressed/ab/<hash>.mp4`.
+/// Processing always works on local files under OBJECT_ROOT, so callers `put` what they
+/// produced and `fetch` what they need to read.
+pub trait Storage: Send + Sync {
+    /// Store the local file at `source` under `key`
+    fn put(&self, key: &str, source: &Path) -> Result<()>;
+
+    /// Local path holding the object `key`, downloading it first if it is not there
+    fn fetch(&self, key: &str) -> Result<PathBuf>;
+
+    /// Drop the local copy of `key` if the backend keeps the object elsewhere
+    fn evict(&self, key: &str) -> Result<()>;
+
+    /// Remove the object `key` together with any local copy; missing objects are not an error
+    fn delete(&self, key: &str) -> Result<()>;
+}
+
+pub static STORAGE: LazyLock<Box<dyn Storage>> = LazyLock::new(|| match &LIBRARY_CONFIG.storage {
+    StorageConfig::Local => Box::new(LocalStorage::new(PathBuf::from(OBJECT_ROOT))),
+    StorageConfig::S3(s3_config) => Box::new(S3Storage::new(
+        s3_config.clone(),
+        PathBuf::from(OBJECT_ROOT),
+    )),
+});
+
+/// Local path of the original, which for referenced files is the source itself
+pub fn fetch_imported(abstract_data: &AbstractData) -> Result<PathBuf> {
+    match abstract_data.reference_alias() {
+        Some(file_modify) => Ok(PathBuf::from(&file_modify.file)),
+        None => STORAGE.fetch(&abstract_data.imported_key()),
+    }
+}
+
+/// Local path of the file handed out for downloads: the copy carrying the edited capture date
+/// if one was written, otherwise the original
+pub fn fetch_download(abstract_data: &AbstractData) -> Result<PathBuf> {
+    match TREE.read_exif_copy(&abstract_data.hash())? {
+        Some(key) => STORAGE.fetch(&key),
+        None => fetch_imported(abstract_data),
+    }
+}
@@ -0,0 +1,357 @@
+use super::Storage;
+use crate::operations::utils::uri::uri_encode;
+use crate::public::config::S3Config;
+use anyhow::{Context, Result, anyhow};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::blocking::{Body, Client, RequestBuilder};
+use reqwest::{Method, Url};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Objects are streamed, so their bodies are not part of the signature
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// Stores objects in an S3-compatible bucket, signing requests with AWS Signature Version 4.
+/// `cache_root` mirrors the keys for working copies and downloads.
+pub struct S3Storage {
+    config: S3Config,
+    cache_root: PathBuf,
+    client: Client,
+}
+
+impl S3Storage {
+    pub fn new(config: S3Config, cache_root: PathBuf) -> Self {
+        Self {
+            config,
+            cache_root,
+            client: Client::new(),
+        }
+    }
+
+    fn request(&self, method: Method, key: &str) -> Result<RequestBuilder> {
+        let endpoint = self.config.endpoint.trim_end_matches('/');
+        let canonical_uri = format!(
+            "/{}/{}",
+            uri_encode(&self.config.bucket),
+            key.split('/').map(uri_encode).collect::<Vec<_>>().join("/")
+        );
+        let url = Url::parse(&format!("{}{}", endpoint, canonical_uri))
+            .with_context(|| format!("Invalid S3 endpoint {:?}", self.config.endpoint))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(anyhow!("S3 endpoint {:?} has no host", endpoint)),
+        };
+
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let scope = format!("{}/{}/s3/aws4_request", date, self.config.region);
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method.as_str(),
+            canonical_uri,
+            host,
+            UNSIGNED_PAYLOAD,
+            amz_date,
+            signed_headers,
+            UNSIGNED_PAYLOAD
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let signing_key = signing_key(&self.config.secret_access_key, &date, &self.config.region);
+        let signature = hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.config.access_key_id, scope, signed_headers, signature
+        );
+
+        Ok(self
+            .client
+            .request(method, url)
+            .header("x-amz-content-sha256", UNSIGNED_PAYLOAD)
+            .header("x-amz-date", amz_date)
+            .header("authorization", authorization))
+    }
+}
+
+impl Storage for S3Storage {
+    fn put(&self, key: &str, source: &Path) -> Result<()> {
+        let file = File::open(source).with_context(|| format!("Failed to open {:?}", source))?;
+        // S3 needs the length up front; it does not accept chunked uploads
+        let len = file
+            .metadata()
+            .with_context(|| format!("Failed to read metadata of {:?}", source))?
+            .len();
+        self.request(Method::PUT, key)?
+            .body(Body::sized(file, len))
+            .send()
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Failed to upload {:?} to S3 as {}", source, key))?;
+        Ok(())
+    }
+
+    fn fetch(&self, key: &str) -> Result<PathBuf> {
+        let local_path = self.cache_root.join(key);
+        if local_path.exists() {
+            return Ok(local_path);
+        }
+        if let Some(parent) = local_path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create directory tree for {:?}", parent))?;
+        }
+
+        let mut response = self
+            .request(Method::GET, key)?
+            .send()
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Failed to download {} from S3", key))?;
+
+        // Download next to the target so a partial file is never served; the name is unique
+        // so concurrent fetches of the same key never write into each other's file
+        let mut temp_path = local_path.clone().into_os_string();
+        temp_path.push(format!(".{}.part", Uuid::new_v4()));
+        let temp_path = PathBuf::from(temp_path);
+        let result = File::create(&temp_path)
+            .with_context(|| format!("Failed to create {:?}", temp_path))
+            .and_then(|mut temp_file| {
+                response
+                    .copy_to(&mut temp_file)
+                    .with_context(|| format!("Failed to download {} from S3", key))
+            })
+            .and_then(|_| match fs::rename(&temp_path, &local_path) {
+                // Windows refuses to replace a file another fetch has just moved in
+                Err(_) if local_path.exists() => Ok(()),
+                result => {
+                    result.with_context(|| format!("Failed to move download into {:?}", local_path))
+                }
+            });
+        // Already gone unless the download failed or lost the race
+        let _ = fs::remove_file(&temp_path);
+        result.map(|_| local_path)
+    }
+
+    fn evict(&self, key: &str) -> Result<()> {
+        match fs::remove_file(self.cache_root.join(key)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove local copy of {}", key))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        // S3 answers 204 whether or not the object existed
+        self.request(Method::DELETE, key)?
+            .send()
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Failed to delete {} from S3", key))?;
+        self.evict(key)
+    }
+}
+
+fn signing_key(secret_access_key: &str, date: &str, region: &str) -> Vec<u8> {
+    let date_key = hmac_sha256(
+        format!("AWS4{}", secret_access_key).as_bytes(),
+        date.as_bytes(),
+    );
+    let region_key = hmac_sha256(&date_key, region.as_bytes());
+    let service_key = hmac_sha256(&region_key, b"s3");
+    hmac_sha256(&service_key, b"aws4_request")
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    /// Request path such as `/library/imported/ab/photo.jpg` to object body
+    type ObjectMap = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+    /// Minimal stand-in for an S3-compatible server such as MinIO: path-style GET, PUT and DELETE
+    /// against an in-memory bucket, rejecting unsigned or chunked requests
+    fn start_stand_in() -> (String, ObjectMap) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let object_map = Arc::new(Mutex::new(HashMap::new()));
+        let server_object_map = object_map.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let object_map = server_object_map.clone();
+                thread::spawn(move || serve(stream.unwrap(), &object_map));
+            }
+        });
+        (endpoint, object_map)
+    }
+
+    fn serve(stream: TcpStream, object_map: &Mutex<HashMap<String, Vec<u8>>>) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut parts = request_line.split_whitespace();
+        let (method, path) = (
+            parts.next().unwrap().to_string(),
+            parts.next().unwrap().to_string(),
+        );
+
+        let mut headers = HashMap::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            let (name, value) = line.split_once(':').unwrap();
+            headers.insert(name.to_ascii_lowercase(), value.trim().to_string());
+        }
+
+        let signed = headers.contains_key("x-amz-date")
+            && headers
+                .get("authorization")
+                .is_some_and(|value| value.starts_with("AWS4-HMAC-SHA256 Credential=minio/"));
+        let (status, body) = if !signed {
+            ("403 Forbidden", Vec::new())
+        } else if method == "PUT" {
+            match headers.get("content-length") {
+                Some(len) => {
+                    let mut body = vec![0; len.parse().unwrap()];
+                    reader.read_exact(&mut body).unwrap();
+                    object_map.lock().unwrap().insert(path, body);
+                    ("200 OK", Vec::new())
+                }
+                None => ("411 Length Required", Vec::new()),
+            }
+        } else if method == "DELETE" {
+            object_map.lock().unwrap().remove(&path);
+            ("204 No Content", Vec::new())
+        } else {
+            match object_map.lock().unwrap().get(&path) {
+                Some(body) => ("200 OK", body.clone()),
+                None => ("404 Not Found", Vec::new()),
+            }
+        };
+
+        let mut stream = stream;
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            status,
+            body.len()
+        )
+        .unwrap();
+        stream.write_all(&body).unwrap();
+    }
+
+    fn stand_in_storage() -> (S3Storage, PathBuf, ObjectMap) {
+        let (endpoint, object_map) = start_stand_in();
+        let cache_root = std::env::temp_dir().join(format!("urocissa-s3-{}", Uuid::new_v4()));
+        let storage = S3Storage::new(
+            S3Config {
+                endpoint,
+                bucket: "library".to_string(),
+                region: "us-east-1".to_string(),
+                access_key_id: "minio".to_string(),
+                secret_access_key: "minio-secret".to_string(),
+            },
+            cache_root.clone(),
+        );
+        (storage, cache_root, object_map)
+    }
+
+    #[test]
+    fn round_trips_through_the_bucket() {
+        let (storage, cache_root, object_map) = stand_in_storage();
+        let key = "imported/ab/photo 1.jpg";
+        let source = cache_root.join(key);
+        fs::create_dir_all(source.parent().unwrap()).unwrap();
+        fs::write(&source, b"original bytes").unwrap();
+
+        storage.put(key, &source).unwrap();
+        assert_eq!(
+            object_map.lock().unwrap()["/library/imported/ab/photo%201.jpg"],
+            b"original bytes"
+        );
+
+        storage.evict(key).unwrap();
+        assert!(!source.exists());
+        let fetched = storage.fetch(key).unwrap();
+        assert_eq!(fetched, source);
+        assert_eq!(fs::read(&fetched).unwrap(), b"original bytes");
+
+        assert!(storage.fetch("imported/ab/missing.jpg").is_err());
+        fs::remove_dir_all(cache_root).unwrap();
+    }
+
+    #[test]
+    fn delete_removes_the_object_and_its_local_copy() {
+        let (storage, cache_root, object_map) = stand_in_storage();
+        let key = "compressed/ef/photo.jpg";
+        let source = cache_root.join(key);
+        fs::create_dir_all(source.parent().unwrap()).unwrap();
+        fs::write(&source, b"compressed bytes").unwrap();
+        storage.put(key, &source).unwrap();
+
+        storage.delete(key).unwrap();
+        assert!(object_map.lock().unwrap().is_empty());
+        assert!(!source.exists());
+        assert!(storage.fetch(key).is_err());
+
+        // Deleting twice is harmless
+        storage.delete(key).unwrap();
+        fs::remove_dir_all(cache_root).unwrap();
+    }
+
+    #[test]
+    fn concurrent_fetches_do_not_share_a_download() {
+        let (storage, cache_root, object_map) = stand_in_storage();
+        let body: Vec<u8> = (0..512 * 1024).map(|index| (index % 251) as u8).collect();
+        object_map
+            .lock()
+            .unwrap()
+            .insert("/library/compressed/cd/video.mp4".to_string(), body.clone());
+
+        let storage = Arc::new(storage);
+        let handle_list: Vec<_> = (0..8)
+            .map(|_| {
+                let storage = storage.clone();
+                thread::spawn(move || storage.fetch("compressed/cd/video.mp4"))
+            })
+            .collect();
+        for handle in handle_list {
+            let fetched = handle.join().unwrap().unwrap();
+            assert_eq!(fs::read(fetched).unwrap(), body);
+        }
+
+        let leftover_list: Vec<_> = fs::read_dir(cache_root.join("compressed/cd"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .filter(|name| name != "video.mp4")
+            .collect();
+        assert!(leftover_list.is_empty(), "left behind {:?}", leftover_list);
+        fs::remove_dir_all(cache_root).unwrap();
+    }
+}
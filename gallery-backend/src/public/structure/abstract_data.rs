@@ -13,6 +13,7 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use crate::public::constant::VALID_IMAGE_EXTENSIONS;
+use crate::public::storage::OBJECT_ROOT;
 
 /// Regex for parsing timestamps from filenames (e.g., 20231225_143052)
 static FILE_NAME_TIME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
@@ -46,6 +47,24 @@ impl AbstractData {
         }
     }
 
+    /// Get the common object schema
+    pub fn object(&self) -> &ObjectSchema {
+        match self {
+            AbstractData::Image(img) => &img.object,
+            AbstractData::Video(vid) => &vid.object,
+            AbstractData::Album(alb) => &alb.object,
+        }
+    }
+
+    /// Get the common object schema (mutable)
+    pub fn object_mut(&mut self) -> &mut ObjectSchema {
+        match self {
+            AbstractData::Image(img) => &mut img.object,
+            AbstractData::Video(vid) => &mut vid.object,
+            AbstractData::Album(alb) => &mut alb.object,
+        }
+    }
+
     /// Get the width
     pub fn width(&self) -> u32 {
         match self {
@@ -83,8 +102,12 @@ impl AbstractData {
     }
 
     /// Compute timestamp for sorting based on priority list
-    /// Checks fields in order: DateTimeOriginal, filename, scan_time, modified, random
+    /// A manual date override wins; otherwise checks fields in the order of the priority list
     pub fn compute_timestamp(&self, priority_list: &[&str]) -> u128 {
+        if let Some(date_override) = self.object().date_override {
+            return date_override;
+        }
+
         if let AbstractData::Album(alb) = self {
             return alb.metadata.created_time as u128;
         }
@@ -317,6 +340,8 @@ impl AbstractData {
             is_favorite: false,
             is_archived: false,
             is_trashed: false,
+            date_override: None,
+            owner: None,
         };
 
         let metadata = ImageMetadata {
@@ -332,6 +357,9 @@ impl AbstractData {
                 file: String::from("/"),
                 modified: 0,
                 scan_time: 0,
+                missing: false,
+                reference: false,
+                changed: false,
             }],
         };
 
@@ -357,29 +385,59 @@ impl AbstractData {
         PathBuf::from(self.source_path_string())
     }
 
-    /// Get the imported path string
-    pub fn imported_path_string(&self) -> String {
+    /// The alias served as the original when the file was referenced instead of copied,
+    /// preferring one that is still present
+    pub fn reference_alias(&self) -> Option<&FileModify> {
+        let mut reference_iter = self.alias().iter().filter(|file_modify| file_modify.reference);
+        reference_iter
+            .clone()
+            .find(|file_modify| !file_modify.missing)
+            .or_else(|| reference_iter.next())
+    }
+
+    /// Get the storage key of the original
+    pub fn imported_key(&self) -> String {
         let hash = self.hash();
         let ext = self.ext();
-        format!(
-            "./object/imported/{}/{}.{}",
-            &hash.as_str()[0..2],
-            hash,
-            ext
-        )
+        format!("imported/{}/{}.{}", &hash.as_str()[0..2], hash, ext)
+    }
+
+    /// Get the storage key of the copy whose EXIF carries the edited capture date
+    pub fn exif_copy_key(&self) -> String {
+        let hash = self.hash();
+        let ext = self.ext();
+        format!("exif/{}/{}.{}", &hash.as_str()[0..2], hash, ext)
+    }
+
+    /// Get the storage key of the compressed file
+    pub fn compressed_key(&self) -> String {
+        let hash = self.hash();
+        match self {
+            AbstractData::Image(_) => format!("compressed/{}/{}.jpg", &hash.as_str()[0..2], hash),
+            AbstractData::Video(_) => format!("compressed/{}/{}.mp4", &hash.as_str()[0..2], hash),
+            AbstractData::Album(_) => String::new(),
+        }
+    }
+
+    /// Get the storage key of the thumbnail
+    pub fn thumbnail_key(&self) -> String {
+        let hash = self.hash();
+        format!("compressed/{}/{}.jpg", &hash.as_str()[0..2], hash)
+    }
+
+    /// Get the imported path string; the source file itself for referenced originals
+    pub fn imported_path_string(&self) -> String {
+        if let Some(file_modify) = self.reference_alias() {
+            return file_modify.file.clone();
+        }
+        format!("{}/{}", OBJECT_ROOT, self.imported_key())
     }
 
     /// Get the compressed path string
     pub fn compressed_path_string(&self) -> String {
-        let hash = self.hash();
         match self {
-            AbstractData::Image(_) => {
-                format!("./object/compressed/{}/{}.jpg", &hash.as_str()[0..2], hash)
-            }
-            AbstractData::Video(_) => {
-                format!("./object/compressed/{}/{}.mp4", &hash.as_str()[0..2], hash)
-            }
             AbstractData::Album(_) => String::new(),
+            _ => format!("{}/{}", OBJECT_ROOT, self.compressed_key()),
         }
     }
 
@@ -395,8 +453,7 @@ impl AbstractData {
 
     /// Get the thumbnail path
     pub fn thumbnail_path(&self) -> String {
-        let hash = self.hash();
-        format!("./object/compressed/{}/{}.jpg", &hash.as_str()[0..2], hash)
+        format!("{}/{}", OBJECT_ROOT, self.thumbnail_key())
     }
 
     /// Get the parent directory of the compressed path
@@ -508,6 +565,13 @@ impl AbstractData {
         }
     }
 
+    /// Set duration in seconds (only for videos)
+    pub fn set_duration(&mut self, duration: f64) {
+        if let AbstractData::Video(vid) = self {
+            vid.metadata.duration = duration;
+        }
+    }
+
     /// Convert to Image type (if currently video)
     pub fn convert_to_image(&mut self) {
         if let AbstractData::Video(vid) = self {
@@ -521,6 +585,8 @@ impl AbstractData {
                 is_favorite: vid.object.is_favorite,
                 is_archived: vid.object.is_archived,
                 is_trashed: vid.object.is_trashed,
+                date_override: vid.object.date_override,
+                owner: vid.object.owner.clone(),
             };
             let metadata = ImageMetadata {
                 id: vid.metadata.id,
@@ -65,6 +65,8 @@ impl Album {
             is_favorite: false,
             is_archived: false,
             is_trashed: false,
+            date_override: None,
+            owner: None,
         };
 
         // Create AlbumMetadata
@@ -79,6 +81,7 @@ impl Album {
             item_count: self.item_count,
             item_size: self.item_size,
             share_list: self.share_list,
+            granted_users: HashSet::new(),
         };
 
         AbstractData::Album(AlbumCombined { object, metadata })
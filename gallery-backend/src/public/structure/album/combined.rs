@@ -2,12 +2,14 @@ use arrayvec::ArrayString;
 use bitcode::{Decode, Encode};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use super::metadata::AlbumMetadata;
 use crate::public::db::tree::TREE;
 use crate::public::structure::abstract_data::AbstractData;
 use crate::public::structure::object::ObjectSchema;
+use crate::public::structure::response::database_timestamp::DatabaseTimestamp;
 
 /// Combined Album data with Object and Metadata
 #[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode)]
@@ -28,6 +30,12 @@ struct MediaItemInfo {
 }
 
 impl AlbumCombined {
+    /// Owned by `username` or granted to them
+    pub fn is_accessible_by(&self, username: &str) -> bool {
+        self.object.owner.as_deref() == Some(username)
+            || self.metadata.granted_users.contains(username)
+    }
+
     pub fn set_cover(&mut self, cover_data: &AbstractData) {
         self.metadata.cover = Some(cover_data.hash());
         self.object.thumbhash = cover_data.thumbhash().cloned();
@@ -39,8 +47,23 @@ impl AlbumCombined {
     }
 
     pub fn self_update(&mut self) {
+        let smart_members = TREE.smart_album_members(&self.object.id);
+
         // Acquire a read lock on the in-memory tree
         let ref_data = TREE.in_memory.read().unwrap();
+        self.update_from(&ref_data, &smart_members);
+    }
+
+    /// Recompute count, size, time range and cover from `ref_data`.
+    /// Items listed in `smart_members` count as members even if not added by hand.
+    pub fn update_from(
+        &mut self,
+        ref_data: &[DatabaseTimestamp],
+        smart_members: &HashSet<ArrayString<64>>,
+    ) {
+        let is_member = |albums: &HashSet<ArrayString<64>>, hash: &ArrayString<64>| {
+            albums.contains(&self.object.id) || smart_members.contains(hash)
+        };
 
         // Collect relevant media items (Image/Video) along with their info
         let mut data_in_album: Vec<MediaItemInfo> = ref_data
@@ -49,7 +72,8 @@ impl AlbumCombined {
                 |database_timestamp| match &database_timestamp.abstract_data {
                     AbstractData::Image(img) => {
                         // Check if in this album and not trashed
-                        if img.metadata.albums.contains(&self.object.id) && !img.object.is_trashed {
+                        if is_member(&img.metadata.albums, &img.object.id) && !img.object.is_trashed
+                        {
                             Some(MediaItemInfo {
                                 hash: img.object.id,
                                 size: img.metadata.size,
@@ -62,7 +86,8 @@ impl AlbumCombined {
                     }
                     AbstractData::Video(vid) => {
                         // Check if in this album and not trashed
-                        if vid.metadata.albums.contains(&self.object.id) && !vid.object.is_trashed {
+                        if is_member(&vid.metadata.albums, &vid.object.id) && !vid.object.is_trashed
+                        {
                             Some(MediaItemInfo {
                                 hash: vid.object.id,
                                 size: vid.metadata.size,
@@ -1,7 +1,7 @@
 use arrayvec::ArrayString;
 use bitcode::{Decode, Encode};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use super::share::Share;
 
@@ -19,4 +19,7 @@ pub struct AlbumMetadata {
     pub item_count: usize,
     pub item_size: u64,
     pub share_list: HashMap<ArrayString<64>, Share>,
+    /// Accounts other than the owner that can see the album and its members
+    #[serde(default)]
+    pub granted_users: HashSet<String>,
 }
@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 pub struct Share {
     pub url: ArrayString<64>,
     pub description: String,
+    /// Argon2 hash of the share password
     pub password: Option<String>,
     pub show_metadata: bool,
     pub show_download: bool,
@@ -23,7 +24,9 @@ pub struct ResolvedShare {
 }
 
 impl ResolvedShare {
-    pub fn new(album_id: ArrayString<64>, album_title: Option<String>, share: Share) -> Self {
+    /// Visitors are only told whether a password is set, never its hash
+    pub fn new(album_id: ArrayString<64>, album_title: Option<String>, mut share: Share) -> Self {
+        share.password = share.password.map(|_| String::new());
         Self {
             share,
             album_id,
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::operations::password::{constant_time_eq, hash_token, random_token};
+
+/// Every API token starts with this, which tells it apart from the JWTs sent as Bearer tokens
+pub const API_TOKEN_PREFIX: &str = "uro_";
+
+/// Last-used times are only written back once this much time has passed, in milliseconds
+const LAST_USED_RESOLUTION_MILLIS: u128 = 60_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ApiScope {
+    /// Read routes, including downloads and prefetch
+    ReadOnly,
+    /// Uploading files
+    Upload,
+    /// Every other change made on behalf of the owner: flags, tags, albums, shares, deletion
+    EditMetadata,
+    /// Admin routes; implies every other scope and is only available to the admin
+    Admin,
+}
+
+/// Long-lived token for scripts, stored in API_TOKEN_TABLE under its id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    /// Account the token acts as; `None` for the admin
+    pub username: Option<String>,
+    pub scopes: HashSet<ApiScope>,
+    /// SHA-256 of the secret part of the token, which is only shown once
+    pub token_hash: String,
+    pub created_time: u128,
+    pub last_used_time: Option<u128>,
+}
+
+impl ApiToken {
+    /// New token together with the string handed to the client, `uro_<id>_<secret>`
+    pub fn new(
+        name: String,
+        username: Option<String>,
+        scopes: HashSet<ApiScope>,
+    ) -> (Self, String) {
+        let id = random_token(16);
+        let secret = random_token(48);
+        let api_token = Self {
+            token_hash: hash_token(&secret),
+            id,
+            name,
+            username,
+            scopes,
+            created_time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_millis(),
+            last_used_time: None,
+        };
+        let token = format!("{}{}_{}", API_TOKEN_PREFIX, api_token.id, secret);
+        (api_token, token)
+    }
+
+    /// Split a token into its id and its secret
+    pub fn parse(token: &str) -> Option<(&str, &str)> {
+        token.strip_prefix(API_TOKEN_PREFIX)?.split_once('_')
+    }
+
+    pub fn verify_secret(&self, secret: &str) -> bool {
+        constant_time_eq(&hash_token(secret), &self.token_hash)
+    }
+
+    pub fn allows(&self, scope: ApiScope) -> bool {
+        self.scopes.contains(&ApiScope::Admin) || self.scopes.contains(&scope)
+    }
+
+    /// Record a use; returns whether the change is worth writing back
+    pub fn touch(&mut self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis();
+        let is_stale = self.last_used_time.is_none_or(|last_used_time| {
+            now.saturating_sub(last_used_time) >= LAST_USED_RESOLUTION_MILLIS
+        });
+        if is_stale {
+            self.last_used_time = Some(now);
+        }
+        is_stale
+    }
+}
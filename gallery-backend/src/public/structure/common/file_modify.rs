@@ -10,6 +10,15 @@ pub struct FileModify {
     pub file: String,
     pub modified: u128,
     pub scan_time: u128,
+    /// The file was removed or moved out of the sync path after it was indexed
+    #[serde(default)]
+    pub missing: bool,
+    /// Served in place as the original instead of a copy in ./object/imported
+    #[serde(default)]
+    pub reference: bool,
+    /// The referenced file no longer matches the hash it was indexed with
+    #[serde(default)]
+    pub changed: bool,
 }
 
 impl FileModify {
@@ -18,6 +27,9 @@ impl FileModify {
             file: file.to_string_lossy().into_owned(),
             modified,
             scan_time: Utc::now().timestamp_millis() as u128,
+            missing: false,
+            reference: false,
+            changed: false,
         }
     }
 }
@@ -1,61 +1,60 @@
 use super::Expression;
+use crate::public::db::tree::TREE;
 use crate::public::structure::abstract_data::AbstractData;
+use crate::public::structure::response::database_timestamp::DatabaseTimestamp;
 
 impl Expression {
-    pub fn generate_filter(self) -> Box<dyn Fn(&AbstractData) -> bool + Sync + Send> {
+    pub fn generate_filter(self) -> Box<dyn Fn(&DatabaseTimestamp) -> bool + Sync + Send> {
         match self {
             Expression::Or(expressions) => {
-                let filters: Vec<Expression> = expressions;
-                Box::new(move |abstract_data: &AbstractData| {
-                    filters.iter().any(|expr| {
-                        let filter = expr.clone().generate_filter();
-                        filter(abstract_data)
-                    })
-                })
+                // Build the sub-filters once, since some of them resolve against the index
+                let filters: Vec<_> = expressions
+                    .into_iter()
+                    .map(|expr| expr.generate_filter())
+                    .collect();
+                Box::new(move |data: &DatabaseTimestamp| filters.iter().any(|filter| filter(data)))
             }
             Expression::And(expressions) => {
-                let filters: Vec<Expression> = expressions;
-                Box::new(move |abstract_data: &AbstractData| {
-                    filters.iter().all(|expr| {
-                        let filter = expr.clone().generate_filter();
-                        filter(abstract_data)
-                    })
-                })
+                let filters: Vec<_> = expressions
+                    .into_iter()
+                    .map(|expr| expr.generate_filter())
+                    .collect();
+                Box::new(move |data: &DatabaseTimestamp| filters.iter().all(|filter| filter(data)))
             }
             Expression::Not(expression) => {
                 let inner_filter = expression.clone().generate_filter();
-                Box::new(move |abstract_data: &AbstractData| !inner_filter(abstract_data))
+                Box::new(move |data: &DatabaseTimestamp| !inner_filter(data))
             }
             Expression::Tag(tag) => {
-                Box::new(move |abstract_data: &AbstractData| match abstract_data {
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
                     AbstractData::Image(img) => img.object.tags.contains(&tag),
                     AbstractData::Video(vid) => vid.object.tags.contains(&tag),
                     AbstractData::Album(alb) => alb.object.tags.contains(&tag),
                 })
             }
             Expression::Favorite(value) => {
-                Box::new(move |abstract_data: &AbstractData| match abstract_data {
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
                     AbstractData::Image(img) => img.object.is_favorite == value,
                     AbstractData::Video(vid) => vid.object.is_favorite == value,
                     AbstractData::Album(alb) => alb.object.is_favorite == value,
                 })
             }
             Expression::Archived(value) => {
-                Box::new(move |abstract_data: &AbstractData| match abstract_data {
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
                     AbstractData::Image(img) => img.object.is_archived == value,
                     AbstractData::Video(vid) => vid.object.is_archived == value,
                     AbstractData::Album(alb) => alb.object.is_archived == value,
                 })
             }
             Expression::Trashed(value) => {
-                Box::new(move |abstract_data: &AbstractData| match abstract_data {
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
                     AbstractData::Image(img) => img.object.is_trashed == value,
                     AbstractData::Video(vid) => vid.object.is_trashed == value,
                     AbstractData::Album(alb) => alb.object.is_trashed == value,
                 })
             }
             Expression::ExtType(ext_type) => {
-                Box::new(move |abstract_data: &AbstractData| match abstract_data {
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
                     AbstractData::Image(_) => ext_type.contains("image"),
                     AbstractData::Video(_) => ext_type.contains("video"),
                     AbstractData::Album(_) => ext_type.contains("album"),
@@ -63,7 +62,7 @@ impl Expression {
             }
             Expression::Ext(ext) => {
                 let ext_lower = ext.to_ascii_lowercase();
-                Box::new(move |abstract_data: &AbstractData| match abstract_data {
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
                     AbstractData::Image(img) => {
                         img.metadata.ext.to_ascii_lowercase().contains(&ext_lower)
                     }
@@ -75,7 +74,7 @@ impl Expression {
             }
             Expression::Model(model) => {
                 let model_lower = model.to_ascii_lowercase();
-                Box::new(move |abstract_data: &AbstractData| match abstract_data {
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
                     AbstractData::Image(img) => {
                         img.metadata.exif_vec.get("Model").map_or(false, |model_of_exif| {
                             model_of_exif.to_ascii_lowercase().contains(&model_lower)
@@ -91,7 +90,7 @@ impl Expression {
             }
             Expression::Make(make) => {
                 let make_lower = make.to_ascii_lowercase();
-                Box::new(move |abstract_data: &AbstractData| match abstract_data {
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
                     AbstractData::Image(img) => {
                         img.metadata.exif_vec.get("Make").map_or(false, |make_of_exif| {
                             make_of_exif.to_ascii_lowercase().contains(&make_lower)
@@ -107,7 +106,7 @@ impl Expression {
             }
             Expression::Path(path) => {
                 let path_lower = path.to_ascii_lowercase();
-                Box::new(move |abstract_data: &AbstractData| match abstract_data {
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
                     AbstractData::Image(img) => img.metadata.alias.iter().any(|file_modify| {
                         file_modify.file.to_ascii_lowercase().contains(&path_lower)
                     }),
@@ -118,15 +117,36 @@ impl Expression {
                 })
             }
             Expression::Album(album_id) => {
-                Box::new(move |abstract_data: &AbstractData| match abstract_data {
-                    AbstractData::Image(img) => img.metadata.albums.contains(&album_id),
-                    AbstractData::Video(vid) => vid.metadata.albums.contains(&album_id),
+                let smart_members = TREE.smart_album_members(&album_id);
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
+                    AbstractData::Image(img) => {
+                        img.metadata.albums.contains(&album_id)
+                            || smart_members.contains(&img.object.id)
+                    }
+                    AbstractData::Video(vid) => {
+                        vid.metadata.albums.contains(&album_id)
+                            || smart_members.contains(&vid.object.id)
+                    }
                     AbstractData::Album(_) => false,
                 })
             }
+            Expression::Description(description) => {
+                let id_set = TREE.search_description(&description);
+                Box::new(move |data: &DatabaseTimestamp| {
+                    id_set.contains(&data.abstract_data.hash())
+                })
+            }
+            Expression::Title(title) => {
+                let id_set = TREE.search_title(&title);
+                Box::new(move |data: &DatabaseTimestamp| {
+                    id_set.contains(&data.abstract_data.hash())
+                })
+            }
             Expression::Any(any_identifier) => {
                 let any_lower = any_identifier.to_ascii_lowercase();
-                Box::new(move |abstract_data: &AbstractData| match abstract_data {
+                let description_set = TREE.search_description(&any_identifier);
+                let title_set = TREE.search_title(&any_identifier);
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
                     AbstractData::Image(img) => {
                         img.object.tags.contains(&any_identifier)
                             || "image".contains(&any_identifier)
@@ -140,6 +160,7 @@ impl Expression {
                             || img.metadata.alias.iter().any(|file_modify| {
                                 file_modify.file.to_ascii_lowercase().contains(&any_lower)
                             })
+                            || description_set.contains(&img.object.id)
                     }
                     AbstractData::Video(vid) => {
                         vid.object.tags.contains(&any_identifier)
@@ -154,13 +175,61 @@ impl Expression {
                             || vid.metadata.alias.iter().any(|file_modify| {
                                 file_modify.file.to_ascii_lowercase().contains(&any_lower)
                             })
+                            || description_set.contains(&vid.object.id)
                     }
                     AbstractData::Album(alb) => {
                         alb.object.tags.contains(&any_identifier)
                             || "album".to_ascii_lowercase().contains(&any_lower)
+                            || description_set.contains(&alb.object.id)
+                            || title_set.contains(&alb.object.id)
                     }
                 })
             }
+            Expression::Before(timestamp) => {
+                Box::new(move |data: &DatabaseTimestamp| data.timestamp < timestamp)
+            }
+            Expression::After(timestamp) => {
+                Box::new(move |data: &DatabaseTimestamp| data.timestamp > timestamp)
+            }
+            Expression::Between(start, end) => Box::new(move |data: &DatabaseTimestamp| {
+                start <= data.timestamp && data.timestamp <= end
+            }),
+            Expression::Size(comparison, size) => {
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
+                    AbstractData::Image(img) => comparison.compare(img.metadata.size, size),
+                    AbstractData::Video(vid) => comparison.compare(vid.metadata.size, size),
+                    AbstractData::Album(_) => false,
+                })
+            }
+            Expression::Width(comparison, width) => {
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
+                    AbstractData::Image(img) => comparison.compare(img.metadata.width, width),
+                    AbstractData::Video(vid) => comparison.compare(vid.metadata.width, width),
+                    AbstractData::Album(_) => false,
+                })
+            }
+            Expression::Height(comparison, height) => {
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
+                    AbstractData::Image(img) => comparison.compare(img.metadata.height, height),
+                    AbstractData::Video(vid) => comparison.compare(vid.metadata.height, height),
+                    AbstractData::Album(_) => false,
+                })
+            }
+            Expression::Duration(comparison, seconds) => {
+                let seconds = seconds as f64;
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
+                    AbstractData::Video(vid) => comparison.compare(vid.metadata.duration, seconds),
+                    AbstractData::Image(_) | AbstractData::Album(_) => false,
+                })
+            }
+            Expression::SimilarTo { hash, max_distance } => {
+                // Resolve once against the index instead of comparing per record
+                let similar_map = TREE.find_similar(&hash, max_distance);
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
+                    AbstractData::Image(img) => similar_map.contains_key(&img.object.id),
+                    AbstractData::Video(_) | AbstractData::Album(_) => false,
+                })
+            }
         }
     }
 }
@@ -1,32 +1,28 @@
 use super::Expression;
+use crate::public::db::tree::TREE;
 use crate::public::structure::abstract_data::AbstractData;
+use crate::public::structure::response::database_timestamp::DatabaseTimestamp;
 use arrayvec::ArrayString;
 
 impl Expression {
     pub fn generate_filter_hide_metadata(
         self,
         shared_album_id: ArrayString<64>,
-    ) -> Box<dyn Fn(&AbstractData) -> bool + Send + Sync> {
+    ) -> Box<dyn Fn(&DatabaseTimestamp) -> bool + Send + Sync> {
         match self {
             Expression::Or(exprs) => {
-                let id = shared_album_id.clone();
-                let filters = exprs;
-                Box::new(move |data| {
-                    filters.iter().any(|expr| {
-                        let filter = expr.clone().generate_filter_hide_metadata(id.clone());
-                        filter(data)
-                    })
-                })
+                let filters: Vec<_> = exprs
+                    .into_iter()
+                    .map(|expr| expr.generate_filter_hide_metadata(shared_album_id))
+                    .collect();
+                Box::new(move |data| filters.iter().any(|filter| filter(data)))
             }
             Expression::And(exprs) => {
-                let id = shared_album_id.clone();
-                let filters = exprs;
-                Box::new(move |data| {
-                    filters.iter().all(|expr| {
-                        let filter = expr.clone().generate_filter_hide_metadata(id.clone());
-                        filter(data)
-                    })
-                })
+                let filters: Vec<_> = exprs
+                    .into_iter()
+                    .map(|expr| expr.generate_filter_hide_metadata(shared_album_id))
+                    .collect();
+                Box::new(move |data| filters.iter().all(|filter| filter(data)))
             }
             Expression::Not(expr) => {
                 let inner = expr.generate_filter_hide_metadata(shared_album_id);
@@ -36,9 +32,16 @@ impl Expression {
             /* ---------- Allowed album condition ---------- */
             Expression::Album(album_id) => {
                 if album_id == shared_album_id {
-                    Box::new(move |data| match data {
-                        AbstractData::Image(img) => img.metadata.albums.contains(&album_id),
-                        AbstractData::Video(vid) => vid.metadata.albums.contains(&album_id),
+                    let smart_members = TREE.smart_album_members(&album_id);
+                    Box::new(move |data| match &data.abstract_data {
+                        AbstractData::Image(img) => {
+                            img.metadata.albums.contains(&album_id)
+                                || smart_members.contains(&img.object.id)
+                        }
+                        AbstractData::Video(vid) => {
+                            vid.metadata.albums.contains(&album_id)
+                                || smart_members.contains(&vid.object.id)
+                        }
                         AbstractData::Album(_) => false,
                     })
                 } else {
@@ -48,25 +51,29 @@ impl Expression {
             }
 
             /* ---------- Supplementary conditions that must be invalid ---------- */
-            Expression::Tag(_) | Expression::Path(_) => Box::new(|_| false),
-            
+            Expression::Tag(_)
+            | Expression::Path(_)
+            | Expression::Description(_)
+            | Expression::Title(_)
+            | Expression::SimilarTo { .. } => Box::new(|_| false),
+
             /* ---------- Boolean field filters ---------- */
             Expression::Favorite(value) => {
-                Box::new(move |data: &AbstractData| match data {
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
                     AbstractData::Image(img) => img.object.is_favorite == value,
                     AbstractData::Video(vid) => vid.object.is_favorite == value,
                     AbstractData::Album(alb) => alb.object.is_favorite == value,
                 })
             }
             Expression::Archived(value) => {
-                Box::new(move |data: &AbstractData| match data {
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
                     AbstractData::Image(img) => img.object.is_archived == value,
                     AbstractData::Video(vid) => vid.object.is_archived == value,
                     AbstractData::Album(alb) => alb.object.is_archived == value,
                 })
             }
             Expression::Trashed(value) => {
-                Box::new(move |data: &AbstractData| match data {
+                Box::new(move |data: &DatabaseTimestamp| match &data.abstract_data {
                     AbstractData::Image(img) => img.object.is_trashed == value,
                     AbstractData::Video(vid) => vid.object.is_trashed == value,
                     AbstractData::Album(alb) => alb.object.is_trashed == value,
@@ -74,14 +81,14 @@ impl Expression {
             }
 
             /* ---------- Still allowed embedded / file-related conditions ---------- */
-            Expression::ExtType(ext_type) => Box::new(move |data| match data {
+            Expression::ExtType(ext_type) => Box::new(move |data| match &data.abstract_data {
                 AbstractData::Image(_) => ext_type.contains("image"),
                 AbstractData::Video(_) => ext_type.contains("video"),
                 AbstractData::Album(_) => false,
             }),
             Expression::Ext(ext) => {
                 let ext_lower = ext.to_ascii_lowercase();
-                Box::new(move |data| match data {
+                Box::new(move |data| match &data.abstract_data {
                     AbstractData::Image(img) => img.metadata.ext.to_ascii_lowercase().contains(&ext_lower),
                     AbstractData::Video(vid) => vid.metadata.ext.to_ascii_lowercase().contains(&ext_lower),
                     AbstractData::Album(_) => false,
@@ -89,7 +96,7 @@ impl Expression {
             }
             Expression::Model(model) => {
                 let model_lower = model.to_ascii_lowercase();
-                Box::new(move |data| match data {
+                Box::new(move |data| match &data.abstract_data {
                     AbstractData::Image(img) => img
                         .metadata.exif_vec
                         .get("Model")
@@ -103,7 +110,7 @@ impl Expression {
             }
             Expression::Make(make) => {
                 let make_lower = make.to_ascii_lowercase();
-                Box::new(move |data| match data {
+                Box::new(move |data| match &data.abstract_data {
                     AbstractData::Image(img) => img
                         .metadata.exif_vec
                         .get("Make")
@@ -116,10 +123,41 @@ impl Expression {
                 })
             }
 
+            /* ---------- Time and numeric filters ---------- */
+            Expression::Before(timestamp) => Box::new(move |data| data.timestamp < timestamp),
+            Expression::After(timestamp) => Box::new(move |data| data.timestamp > timestamp),
+            Expression::Between(start, end) => {
+                Box::new(move |data| start <= data.timestamp && data.timestamp <= end)
+            }
+            Expression::Size(comparison, size) => Box::new(move |data| match &data.abstract_data {
+                AbstractData::Image(img) => comparison.compare(img.metadata.size, size),
+                AbstractData::Video(vid) => comparison.compare(vid.metadata.size, size),
+                AbstractData::Album(_) => false,
+            }),
+            Expression::Width(comparison, width) => Box::new(move |data| match &data.abstract_data {
+                AbstractData::Image(img) => comparison.compare(img.metadata.width, width),
+                AbstractData::Video(vid) => comparison.compare(vid.metadata.width, width),
+                AbstractData::Album(_) => false,
+            }),
+            Expression::Height(comparison, height) => {
+                Box::new(move |data| match &data.abstract_data {
+                    AbstractData::Image(img) => comparison.compare(img.metadata.height, height),
+                    AbstractData::Video(vid) => comparison.compare(vid.metadata.height, height),
+                    AbstractData::Album(_) => false,
+                })
+            }
+            Expression::Duration(comparison, seconds) => {
+                let seconds = seconds as f64;
+                Box::new(move |data| match &data.abstract_data {
+                    AbstractData::Video(vid) => comparison.compare(vid.metadata.duration, seconds),
+                    AbstractData::Image(_) | AbstractData::Album(_) => false,
+                })
+            }
+
             /* ---------- Any: removes tag / alias / album / path matching ---------- */
             Expression::Any(identifier) => {
                 let any_lower = identifier.to_ascii_lowercase();
-                Box::new(move |data| match data {
+                Box::new(move |data| match &data.abstract_data {
                     AbstractData::Image(img) => {
                         "image".contains(&identifier)
                             || img.metadata.ext.to_ascii_lowercase().contains(&any_lower)
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 pub mod generate_filter;
 pub mod generate_filter_hide_metadata;
+pub mod parse;
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Hash)]
 pub enum Expression {
     Or(Vec<Expression>),
@@ -16,8 +17,63 @@ pub enum Expression {
     Path(String),
     Album(ArrayString<64>),
     Any(String),
+    /// Words of the user-defined description
+    Description(String),
+    /// Words of the album title
+    Title(String),
     // Boolean field filters
     Favorite(bool),
     Archived(bool),
     Trashed(bool),
+    // Time filters on the sorting timestamp (milliseconds since UNIX epoch)
+    Before(u128),
+    After(u128),
+    Between(u128, u128),
+    // Numeric field filters
+    Size(Comparison, u64),
+    Width(Comparison, u32),
+    Height(Comparison, u32),
+    /// Video duration in seconds
+    Duration(Comparison, u64),
+    /// Images whose phash is within `max_distance` bits of the image `hash`
+    SimilarTo {
+        hash: ArrayString<64>,
+        max_distance: u32,
+    },
+}
+
+impl Expression {
+    /// The `SimilarTo` that decides the result order, if the expression requires one.
+    /// Only top-level terms and terms joined by `And` qualify.
+    pub fn similar_to(&self) -> Option<(ArrayString<64>, u32)> {
+        match self {
+            Expression::SimilarTo { hash, max_distance } => Some((*hash, *max_distance)),
+            Expression::And(expressions) => expressions.iter().find_map(|expr| expr.similar_to()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    /// Evaluate `lhs <op> rhs`
+    pub fn compare<T: PartialOrd>(self, lhs: T, rhs: T) -> bool {
+        match self {
+            Comparison::Eq => lhs == rhs,
+            Comparison::Ne => lhs != rhs,
+            Comparison::Lt => lhs < rhs,
+            Comparison::Le => lhs <= rhs,
+            Comparison::Gt => lhs > rhs,
+            Comparison::Ge => lhs >= rhs,
+        }
+    }
 }
@@ -0,0 +1,449 @@
+use super::{Comparison, Expression};
+use crate::public::constant::DEFAULT_SIMILAR_THRESHOLD;
+use anyhow::{Error, Result, anyhow};
+use arrayvec::ArrayString;
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+use std::str::FromStr;
+
+/// Parse the textual query syntax documented in SEARCH.md, e.g.
+/// `and(type: "image", not(tag: "private"))`.
+impl FromStr for Expression {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut parser = Parser { input, position: 0 };
+        let expression = parser.parse_expression()?;
+        parser.skip_whitespace();
+        if parser.position < input.len() {
+            return Err(parser.error("Unexpected trailing input"));
+        }
+        Ok(expression)
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize, // byte offset into input
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.position = self.input.len() - trimmed.len();
+    }
+
+    /// Error pointing at the current position, counted in characters from 1
+    fn error(&self, message: &str) -> Error {
+        self.error_at(self.position, message)
+    }
+
+    fn error_at(&self, position: usize, message: &str) -> Error {
+        let column = self.input[..position].chars().count() + 1;
+        match self.input[position..].chars().next() {
+            Some(found) => anyhow!("{} at position {} (found '{}')", message, column, found),
+            None => anyhow!("{} at position {} (found end of query)", message, column),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.position += expected.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", expected)))
+        }
+    }
+
+    /// Consume `expected` if it is the next character
+    fn eat(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.position += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_word(&mut self) -> Result<(usize, &'a str)> {
+        self.skip_whitespace();
+        let start = self.position;
+        let length = self
+            .rest()
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(self.rest().len());
+        if length == 0 {
+            return Err(self.error("Expected a keyword"));
+        }
+        self.position += length;
+        Ok((start, &self.input[start..self.position]))
+    }
+
+    fn parse_expression(&mut self) -> Result<Expression> {
+        let (start, keyword) = self.parse_word()?;
+        match keyword {
+            "or" => Ok(Expression::Or(self.parse_expression_list()?)),
+            "and" => Ok(Expression::And(self.parse_expression_list()?)),
+            "not" => {
+                self.expect('(')?;
+                let expression = self.parse_expression()?;
+                self.expect(')')?;
+                Ok(Expression::Not(Box::new(expression)))
+            }
+            "similar" => {
+                self.expect('(')?;
+                let hash = self.parse_array_string()?;
+                let max_distance = if self.eat(',') {
+                    self.parse_number()?
+                } else {
+                    DEFAULT_SIMILAR_THRESHOLD
+                };
+                self.expect(')')?;
+                Ok(Expression::SimilarTo { hash, max_distance })
+            }
+            "between" => {
+                self.expect('(')?;
+                let start_time = self.parse_date(false)?;
+                self.expect(',')?;
+                let end_time = self.parse_date(true)?;
+                self.expect(')')?;
+                Ok(Expression::Between(start_time, end_time))
+            }
+            _ => {
+                self.expect(':')?;
+                self.parse_field(start, keyword)
+            }
+        }
+    }
+
+    fn parse_expression_list(&mut self) -> Result<Vec<Expression>> {
+        self.expect('(')?;
+        let mut expressions = vec![self.parse_expression()?];
+        while self.eat(',') {
+            expressions.push(self.parse_expression()?);
+        }
+        self.expect(')')?;
+        Ok(expressions)
+    }
+
+    fn parse_field(&mut self, start: usize, keyword: &str) -> Result<Expression> {
+        let expression = match keyword {
+            "tag" => Expression::Tag(self.parse_string()?),
+            "type" => Expression::ExtType(self.parse_string()?),
+            "ext" => Expression::Ext(self.parse_string()?),
+            "make" => Expression::Make(self.parse_string()?),
+            "model" => Expression::Model(self.parse_string()?),
+            "path" => Expression::Path(self.parse_string()?),
+            "any" => Expression::Any(self.parse_string()?),
+            "description" => Expression::Description(self.parse_string()?),
+            "title" => Expression::Title(self.parse_string()?),
+            "album" => Expression::Album(self.parse_array_string()?),
+            "favorite" => Expression::Favorite(self.parse_bool()?),
+            "archived" => Expression::Archived(self.parse_bool()?),
+            "trashed" => Expression::Trashed(self.parse_bool()?),
+            "before" => Expression::Before(self.parse_date(false)?),
+            "after" => Expression::After(self.parse_date(false)?),
+            "size" => Expression::Size(self.parse_comparison(), self.parse_number()?),
+            "width" => Expression::Width(self.parse_comparison(), self.parse_number()?),
+            "height" => Expression::Height(self.parse_comparison(), self.parse_number()?),
+            "duration" => Expression::Duration(self.parse_comparison(), self.parse_number()?),
+            _ => {
+                return Err(self.error_at(start, &format!("Unknown keyword '{}'", keyword)));
+            }
+        };
+        Ok(expression)
+    }
+
+    /// Double-quoted string; a backslash escapes the following character
+    fn parse_string(&mut self) -> Result<String> {
+        self.skip_whitespace();
+        if self.peek() != Some('"') {
+            return Err(self.error("Expected a quoted string"));
+        }
+        let start = self.position;
+        let mut value = String::new();
+        let mut chars = self.rest().char_indices().skip(1);
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.position += offset + 1;
+                    return Ok(value);
+                }
+                '\\' => match chars.next() {
+                    Some((_, escaped)) => value.push(escaped),
+                    None => break,
+                },
+                _ => value.push(c),
+            }
+        }
+        Err(self.error_at(start, "Unterminated string"))
+    }
+
+    fn parse_array_string(&mut self) -> Result<ArrayString<64>> {
+        self.skip_whitespace();
+        let start = self.position;
+        let value = self.parse_string()?;
+        ArrayString::from(&value)
+            .map_err(|_| self.error_at(start, "Identifier is longer than 64 bytes"))
+    }
+
+    fn parse_bool(&mut self) -> Result<bool> {
+        let (start, word) = self.parse_word()?;
+        match word {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(self.error_at(start, "Expected 'true' or 'false'")),
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Comparison {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let (comparison, length) = if rest.starts_with("<=") {
+            (Comparison::Le, 2)
+        } else if rest.starts_with(">=") {
+            (Comparison::Ge, 2)
+        } else if rest.starts_with("!=") {
+            (Comparison::Ne, 2)
+        } else if rest.starts_with('<') {
+            (Comparison::Lt, 1)
+        } else if rest.starts_with('>') {
+            (Comparison::Gt, 1)
+        } else if rest.starts_with('=') {
+            (Comparison::Eq, 1)
+        } else {
+            (Comparison::Eq, 0)
+        };
+        self.position += length;
+        comparison
+    }
+
+    fn parse_number<T: FromStr>(&mut self) -> Result<T> {
+        self.skip_whitespace();
+        let start = self.position;
+        let length = self
+            .rest()
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest().len());
+        if length == 0 {
+            return Err(self.error("Expected a number"));
+        }
+        self.position += length;
+        self.input[start..self.position]
+            .parse()
+            .map_err(|_| self.error_at(start, "Number is out of range"))
+    }
+
+    /// Quoted date as milliseconds since UNIX epoch in local time. Accepts raw
+    /// milliseconds, `YYYY-MM-DD` and `YYYY-MM-DD HH:MM:SS`; a bare date means the
+    /// start of that day, or its end when `end_of_day` is set.
+    fn parse_date(&mut self, end_of_day: bool) -> Result<u128> {
+        self.skip_whitespace();
+        let start = self.position;
+        let value = self.parse_string()?;
+        let value = value.trim();
+
+        if !value.is_empty() && value.chars().all(|c| c.is_ascii_digit()) {
+            return value
+                .parse()
+                .map_err(|_| self.error_at(start, "Timestamp is out of range"));
+        }
+
+        let naive_date_time = if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            if end_of_day {
+                date.and_hms_milli_opt(23, 59, 59, 999)
+            } else {
+                date.and_hms_opt(0, 0, 0)
+            }
+        } else {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+                .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S"))
+                .ok()
+        };
+
+        naive_date_time
+            .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+            .and_then(|date_time| u128::try_from(date_time.timestamp_millis()).ok())
+            .ok_or_else(|| {
+                self.error_at(
+                    start,
+                    "Expected a date like \"2024-01-31\" or \"2024-01-31 12:00:00\"",
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Expression {
+        input.parse().unwrap()
+    }
+
+    fn parse_error(input: &str) -> String {
+        input.parse::<Expression>().unwrap_err().to_string()
+    }
+
+    fn hash(value: &str) -> ArrayString<64> {
+        ArrayString::from(value).unwrap()
+    }
+
+    #[test]
+    fn parses_nested_operators() {
+        assert_eq!(
+            parse(r#"and(type: "image", or(tag: "a", tag: "b"), not(favorite: true))"#),
+            Expression::And(vec![
+                Expression::ExtType("image".to_string()),
+                Expression::Or(vec![
+                    Expression::Tag("a".to_string()),
+                    Expression::Tag("b".to_string()),
+                ]),
+                Expression::Not(Box::new(Expression::Favorite(true))),
+            ])
+        );
+    }
+
+    #[test]
+    fn ignores_surrounding_whitespace() {
+        assert_eq!(
+            parse("  or (  archived :false ,trashed:  true )  "),
+            Expression::Or(vec![Expression::Archived(false), Expression::Trashed(true)])
+        );
+    }
+
+    #[test]
+    fn parses_comparisons() {
+        assert_eq!(parse("size: >= 100"), Expression::Size(Comparison::Ge, 100));
+        assert_eq!(parse("size: <= 100"), Expression::Size(Comparison::Le, 100));
+        assert_eq!(parse("width: != 5"), Expression::Width(Comparison::Ne, 5));
+        assert_eq!(parse("height: <7"), Expression::Height(Comparison::Lt, 7));
+        assert_eq!(
+            parse("duration: >60"),
+            Expression::Duration(Comparison::Gt, 60)
+        );
+        assert_eq!(parse("width: = 5"), Expression::Width(Comparison::Eq, 5));
+        assert_eq!(parse("width: 5"), Expression::Width(Comparison::Eq, 5));
+    }
+
+    #[test]
+    fn handles_quotes_and_escapes() {
+        assert_eq!(
+            parse(r#"description: "say \"hi\"""#),
+            Expression::Description(r#"say "hi""#.to_string())
+        );
+        assert_eq!(
+            parse(r#"path: "C:\\photos\\2024""#),
+            Expression::Path(r"C:\photos\2024".to_string())
+        );
+        assert_eq!(
+            parse(r#"any: "a, b) or(""#),
+            Expression::Any("a, b) or(".to_string())
+        );
+        assert_eq!(
+            parse(r#"title: "日本""#),
+            Expression::Title("日本".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_similar_with_default_threshold() {
+        assert_eq!(
+            parse(r#"similar("abc")"#),
+            Expression::SimilarTo {
+                hash: hash("abc"),
+                max_distance: DEFAULT_SIMILAR_THRESHOLD,
+            }
+        );
+        assert_eq!(
+            parse(r#"similar("abc", 3)"#),
+            Expression::SimilarTo {
+                hash: hash("abc"),
+                max_distance: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_dates() {
+        assert_eq!(
+            parse(r#"before: "1700000000000""#),
+            Expression::Before(1700000000000)
+        );
+
+        let start_of_day = Local
+            .with_ymd_and_hms(2024, 1, 31, 0, 0, 0)
+            .unwrap()
+            .timestamp_millis() as u128;
+        assert_eq!(
+            parse(r#"after: "2024-01-31""#),
+            Expression::After(start_of_day)
+        );
+        assert_eq!(
+            parse(r#"after: "2024-01-31 12:00:00""#),
+            parse(r#"after: "2024-01-31T12:00:00""#)
+        );
+        assert_eq!(
+            parse(r#"between("2024-01-31", "2024-01-31")"#),
+            Expression::Between(start_of_day, start_of_day + 86_399_999)
+        );
+    }
+
+    #[test]
+    fn reports_errors_with_positions() {
+        assert_eq!(
+            parse_error(r#"foo: "x""#),
+            "Unknown keyword 'foo' at position 1 (found 'f')"
+        );
+        assert_eq!(
+            parse_error(r#"tag: "x" tag"#),
+            "Unexpected trailing input at position 10 (found 't')"
+        );
+        assert_eq!(
+            parse_error(r#"tag: "open"#),
+            "Unterminated string at position 6 (found '\"')"
+        );
+        assert_eq!(
+            parse_error(r#"tag: "trailing\"#),
+            "Unterminated string at position 6 (found '\"')"
+        );
+        assert_eq!(
+            parse_error("tag: x"),
+            "Expected a quoted string at position 6 (found 'x')"
+        );
+        assert_eq!(
+            parse_error(r#"and(tag: "x""#),
+            "Expected ')' at position 13 (found end of query)"
+        );
+        assert_eq!(
+            parse_error("favorite: yes"),
+            "Expected 'true' or 'false' at position 11 (found 'y')"
+        );
+        assert_eq!(
+            parse_error("size: >= big"),
+            "Expected a number at position 10 (found 'b')"
+        );
+        assert_eq!(
+            parse_error("width: 99999999999"),
+            "Number is out of range at position 8 (found '9')"
+        );
+        assert!(parse_error(r#"before: "yesterday""#).starts_with("Expected a date like"));
+        assert!(
+            parse_error(&format!(r#"album: "{}""#, "a".repeat(65)))
+                .starts_with("Identifier is longer than 64 bytes")
+        );
+        assert_eq!(
+            parse_error(""),
+            "Expected a keyword at position 1 (found end of query)"
+        );
+    }
+}
@@ -1,9 +1,13 @@
 pub mod abstract_data;
 pub mod album;
+pub mod api_token;
 pub mod common;
 pub mod expression;
 pub mod guard;
 pub mod image;
 pub mod object;
 pub mod response;
+pub mod session;
+pub mod sort;
+pub mod user;
 pub mod video;
@@ -48,6 +48,12 @@ pub struct ObjectSchema {
     pub is_favorite: bool,
     pub is_archived: bool,
     pub is_trashed: bool,
+    /// Manually set timestamp in milliseconds, preferred over every priority list field
+    #[serde(default)]
+    pub date_override: Option<u128>,
+    /// Account the object belongs to; `None` for the admin's library
+    #[serde(default)]
+    pub owner: Option<String>,
 }
 
 impl ObjectSchema {
@@ -62,6 +68,8 @@ impl ObjectSchema {
             is_favorite: false,
             is_archived: false,
             is_trashed: false,
+            date_override: None,
+            owner: None,
         }
     }
 }
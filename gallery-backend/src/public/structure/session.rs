@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::operations::password::{constant_time_eq, hash_token, random_token};
+
+/// Refresh tokens slide: every refresh pushes the expiry this far out again
+pub const REFRESH_TOKEN_LIFETIME_SECS: u64 = 14 * 86_400; // 14 days
+
+/// How long a rotated-out refresh token is still accepted, so tabs refreshing at once
+/// do not look like a replayed token
+pub const REFRESH_TOKEN_GRACE_SECS: u64 = 30;
+
+/// Sign-in stored in SESSION_TABLE under its `jti`; access tokens carry the `jti` and
+/// stop working as soon as the session is removed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    pub jti: String,
+    /// `None` for the admin
+    pub username: Option<String>,
+    /// SHA-256 of the current refresh token, which changes on every refresh
+    pub refresh_token_hash: String,
+    /// SHA-256 of the refresh token replaced at `refreshed_time`
+    #[serde(default)]
+    pub previous_refresh_token_hash: Option<String>,
+    pub client_ip: Option<String>,
+    pub created_time: u128,
+    pub refreshed_time: u128,
+    /// Unix seconds after which the refresh token is rejected
+    pub exp: u64,
+}
+
+impl Session {
+    /// New session together with its first refresh token
+    pub fn new(username: Option<String>, client_ip: Option<String>) -> (Self, String) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards");
+        let mut session = Self {
+            jti: random_token(32),
+            username,
+            refresh_token_hash: String::new(),
+            previous_refresh_token_hash: None,
+            client_ip,
+            created_time: now.as_millis(),
+            refreshed_time: now.as_millis(),
+            exp: 0,
+        };
+        let refresh_token = session.rotate();
+        (session, refresh_token)
+    }
+
+    /// Replace the refresh token and extend the expiry; the previous token stops working
+    /// once the grace period is over
+    pub fn rotate(&mut self) -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards");
+        let secret = random_token(64);
+        let previous_hash = std::mem::replace(&mut self.refresh_token_hash, hash_token(&secret));
+        self.previous_refresh_token_hash = Some(previous_hash).filter(|hash| !hash.is_empty());
+        self.refreshed_time = now.as_millis();
+        self.exp = now.as_secs() + REFRESH_TOKEN_LIFETIME_SECS;
+        format!("{}.{}", self.jti, secret)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs();
+        now > self.exp
+    }
+
+    /// Split a refresh token into the `jti` it belongs to and its secret
+    pub fn parse_refresh_token(refresh_token: &str) -> Option<(&str, &str)> {
+        refresh_token.split_once('.')
+    }
+
+    pub fn verify_refresh_secret(&self, secret: &str) -> bool {
+        constant_time_eq(&hash_token(secret), &self.refresh_token_hash)
+    }
+
+    /// Whether `secret` is the refresh token replaced by the last rotation, still in its grace period
+    pub fn verify_previous_refresh_secret(&self, secret: &str) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis();
+        let in_grace_period =
+            now.saturating_sub(self.refreshed_time) <= u128::from(REFRESH_TOKEN_GRACE_SECS) * 1000;
+        in_grace_period
+            && self
+                .previous_refresh_token_hash
+                .as_ref()
+                .is_some_and(|previous_hash| constant_time_eq(&hash_token(secret), previous_hash))
+    }
+}
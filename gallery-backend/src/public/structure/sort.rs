@@ -0,0 +1,87 @@
+use crate::public::structure::abstract_data::AbstractData;
+use crate::public::structure::response::database_timestamp::DatabaseTimestamp;
+use rayon::slice::ParallelSliceMut;
+use rocket::form::FromFormField;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::Path;
+
+/// Order of prefetch results; the in-memory tree itself is always newest first
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize, FromFormField,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum SortKey {
+    #[default]
+    Newest,
+    Oldest,
+    /// Largest first
+    Size,
+    /// Case-insensitive file name, albums by title
+    Filename,
+    /// Most recently scanned first
+    #[field(value = "scan_time")]
+    ScanTime,
+    /// Shuffled, but stable for the same seed
+    Random,
+}
+
+impl SortKey {
+    /// Whether the order follows the item dates, which the scrollbar relies on
+    pub fn is_date_based(self) -> bool {
+        matches!(self, SortKey::Newest | SortKey::Oldest)
+    }
+
+    /// Reorder `database_timestamp_vec`, which must be newest first
+    pub fn sort(self, database_timestamp_vec: &mut Vec<&DatabaseTimestamp>, seed: u64) {
+        match self {
+            SortKey::Newest => {}
+            SortKey::Oldest => database_timestamp_vec.reverse(),
+            SortKey::Size => database_timestamp_vec.par_sort_by_key(|database_timestamp| {
+                Reverse(size(&database_timestamp.abstract_data))
+            }),
+            SortKey::Filename => {
+                database_timestamp_vec.par_sort_by_cached_key(|database_timestamp| {
+                    file_name(&database_timestamp.abstract_data).to_lowercase()
+                })
+            }
+            SortKey::ScanTime => database_timestamp_vec.par_sort_by_key(|database_timestamp| {
+                Reverse(
+                    database_timestamp
+                        .abstract_data
+                        .alias()
+                        .iter()
+                        .map(|file_modify| file_modify.scan_time)
+                        .max(),
+                )
+            }),
+            SortKey::Random => {
+                database_timestamp_vec.par_sort_by_cached_key(|database_timestamp| {
+                    let mut hasher = DefaultHasher::new();
+                    seed.hash(&mut hasher);
+                    database_timestamp.abstract_data.hash().hash(&mut hasher);
+                    hasher.finish()
+                })
+            }
+        }
+    }
+}
+
+fn size(abstract_data: &AbstractData) -> u64 {
+    match abstract_data {
+        AbstractData::Image(img) => img.metadata.size,
+        AbstractData::Video(vid) => vid.metadata.size,
+        AbstractData::Album(alb) => alb.metadata.item_size,
+    }
+}
+
+fn file_name(abstract_data: &AbstractData) -> &str {
+    match abstract_data {
+        AbstractData::Album(alb) => alb.metadata.title.as_deref().unwrap_or_default(),
+        _ => Path::new(abstract_data.source_path_string())
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .unwrap_or_default(),
+    }
+}
@@ -0,0 +1,55 @@
+use arrayvec::ArrayString;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::public::structure::abstract_data::AbstractData;
+use crate::public::structure::response::database_timestamp::DatabaseTimestamp;
+
+/// Account stored in USER_TABLE; the admin is configured separately and has no record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    pub username: String,
+    pub password_hash: String,
+    pub created_time: u128,
+}
+
+/// What a user can reach: everything they own plus the albums they own or have been granted,
+/// together with the members of those albums
+#[derive(Debug, Clone)]
+pub struct UserScope {
+    pub username: String,
+    album_id_set: HashSet<ArrayString<64>>,
+}
+
+impl UserScope {
+    pub fn build(username: &str, database_timestamp_vec: &[DatabaseTimestamp]) -> Self {
+        let album_id_set = database_timestamp_vec
+            .iter()
+            .filter_map(
+                |database_timestamp| match &database_timestamp.abstract_data {
+                    AbstractData::Album(album) if album.is_accessible_by(username) => {
+                        Some(album.object.id)
+                    }
+                    _ => None,
+                },
+            )
+            .collect();
+        Self {
+            username: username.to_string(),
+            album_id_set,
+        }
+    }
+
+    pub fn can_read(&self, abstract_data: &AbstractData) -> bool {
+        if abstract_data.object().owner.as_deref() == Some(self.username.as_str()) {
+            return true;
+        }
+        match abstract_data {
+            AbstractData::Album(album) => self.album_id_set.contains(&album.object.id),
+            _ => abstract_data
+                .albums()
+                .is_some_and(|albums| !albums.is_disjoint(&self.album_id_set)),
+        }
+    }
+}
@@ -181,6 +181,9 @@ pub struct Dashboard {
     handled: AtomicU64,
     pending: AtomicU64,
     total_duration: AtomicF64,
+    scan_done: AtomicU64,
+    scan_total: AtomicU64,
+    warnings: DashMap<String, String>,
 }
 
 pub static LOGGER_TX: OnceLock<UnboundedSender<String>> = OnceLock::new();
@@ -194,6 +197,9 @@ impl Dashboard {
             handled: AtomicU64::new(0),
             pending: AtomicU64::new(0),
             total_duration: AtomicF64::new(0.0),
+            scan_done: AtomicU64::new(0),
+            scan_total: AtomicU64::new(0),
+            warnings: DashMap::new(),
         }
     }
 
@@ -272,6 +278,30 @@ impl Dashboard {
         self.pending.fetch_sub(1, Ordering::Relaxed);
     }
 
+    /// Show reconcile scan progress in the stats line until `finish_scan`
+    pub fn start_scan(&self, total: u64) {
+        self.scan_done.store(0, Ordering::Relaxed);
+        self.scan_total.store(total, Ordering::Relaxed);
+    }
+    pub fn advance_scan(&self) {
+        self.scan_done.fetch_add(1, Ordering::Relaxed);
+    }
+    pub fn finish_scan(&self) {
+        self.scan_total.store(0, Ordering::Relaxed);
+        self.scan_done.store(0, Ordering::Relaxed);
+    }
+
+    /// Show a warning under the stats line; returns whether it is new or changed
+    pub fn set_warning(&self, key: &str, message: &str) -> bool {
+        match self.warnings.insert(key.to_string(), message.to_string()) {
+            Some(previous) => previous != message,
+            None => true,
+        }
+    }
+    pub fn clear_warning(&self, key: &str) {
+        self.warnings.remove(key);
+    }
+
     #[inline]
     fn handled(&self) -> u64 {
         self.handled.load(Ordering::Relaxed)
@@ -313,10 +343,22 @@ impl Component for Dashboard {
             self.pending(),
             avg_str
         );
+        let scan_total = self.scan_total.load(Ordering::Relaxed);
+        if scan_total > 0 {
+            let scan_done = self.scan_done.load(Ordering::Relaxed);
+            stats.push_str(&format!(" │ Scan: {}/{}", scan_done, scan_total));
+        }
         // Pad right to fill the terminal width
         stats.push_str(&" ".repeat(cols.saturating_sub(UnicodeWidthStr::width(stats.as_str()))));
         lines.push(Line::sanitized(&stats));
 
+        // Warnings, e.g. unreachable sync paths
+        let mut warnings: Vec<String> = self.warnings.iter().map(|kv| kv.value().clone()).collect();
+        warnings.sort();
+        for warning in &warnings {
+            lines.push(Line::sanitized(&format!(" ! {}", warning)));
+        }
+
         // Middle separator line
         lines.push(Line::sanitized(&sep));
 
@@ -359,11 +401,11 @@ impl Component for Dashboard {
         }
 
         // Fill remaining lines with blank spaces to maintain dashboard height
-        while lines.len() < max + 3 {
+        while lines.len() < max + 3 + warnings.len() {
             let blank = " ".repeat(cols);
             lines.push(Line::sanitized(&blank));
         }
-        lines.truncate(max + 3);
+        lines.truncate(max + 3 + warnings.len());
 
         Ok(Lines(lines))
     }
@@ -1,34 +1,47 @@
 use crate::public::structure::album::ResolvedShare;
+use crate::public::structure::object::ObjectSchema;
+use crate::public::structure::session::Session;
+use crate::router::AccessDenied;
 use crate::router::post::authenticate::JSON_WEB_TOKEN_SECRET_KEY;
+use anyhow::Result;
 use jsonwebtoken::{EncodingKey, Header, encode};
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Role {
     Admin,
     Share(ResolvedShare),
+    /// Account from USER_TABLE, identified by its username
+    User(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Claims {
     pub role: Role,
     pub exp: u64,
+    /// Session in SESSION_TABLE that admin and user tokens belong to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
 }
 
+/// Admin and user tokens are short-lived; the session's refresh token renews them
+const ACCESS_TOKEN_LIFETIME_SECS: u64 = 15 * 60; // 15 minutes
+
 impl Claims {
-    pub fn new_admin() -> Self {
+    pub fn new_admin(jti: String) -> Self {
         let exp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .expect("Time went backwards")
             .as_secs()
-            + 14 * 86_400; // 14 days
+            + ACCESS_TOKEN_LIFETIME_SECS;
 
         Self {
             role: Role::Admin,
             exp,
+            jti: Some(jti),
         }
     }
 
@@ -42,8 +55,59 @@ impl Claims {
         Self {
             role: Role::Share(resolved_share),
             exp,
+            jti: None,
+        }
+    }
+    /// Issued by /post/share/unlock, so the share password is only sent once
+    pub fn new_unlocked_share(resolved_share: ResolvedShare) -> Self {
+        let exp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            + 3_600; // 1 hour
+
+        Self {
+            role: Role::Share(resolved_share),
+            exp,
+            jti: None,
         }
     }
+
+    pub fn new_user(username: String, jti: String) -> Self {
+        let exp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            + ACCESS_TOKEN_LIFETIME_SECS;
+
+        Self {
+            role: Role::User(username),
+            exp,
+            jti: Some(jti),
+        }
+    }
+
+    /// Built per request from an API token and never encoded
+    pub fn new_api_token(username_opt: Option<String>) -> Self {
+        let role = match username_opt {
+            Some(username) => Role::User(username),
+            None => Role::Admin,
+        };
+        Self {
+            role,
+            exp: 0,
+            jti: None,
+        }
+    }
+
+    /// Access token for `session`, as the admin or as its user
+    pub fn new_session(session: &Session) -> Self {
+        match &session.username {
+            Some(username) => Self::new_user(username.clone(), session.jti.clone()),
+            None => Self::new_admin(session.jti.clone()),
+        }
+    }
+
     pub fn is_admin(&self) -> bool {
         match &self.role {
             Role::Admin => true,
@@ -57,6 +121,23 @@ impl Claims {
         }
     }
 
+    pub fn get_username(&self) -> Option<&str> {
+        match &self.role {
+            Role::User(username) => Some(username),
+            _ => None,
+        }
+    }
+
+    /// Users may only change what they own; the admin and shares are not restricted here
+    pub fn check_owner(&self, object: &ObjectSchema) -> Result<()> {
+        match &self.role {
+            Role::User(username) if object.owner.as_ref() != Some(username) => {
+                Err(AccessDenied(format!("{} is not owned by {}", object.id, username)).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
     pub fn encode(&self) -> String {
         encode(
             &Header::default(),
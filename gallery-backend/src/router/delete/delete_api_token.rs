@@ -0,0 +1,33 @@
+use crate::public::db::tree::TREE;
+use crate::router::fairing::guard_auth::GuardAuth;
+use crate::router::{AccessDenied, AppError, AppResult, GuardResult};
+use anyhow::anyhow;
+use rocket::http::Status;
+
+/// Revoke an API token; the admin may revoke any, users only their own
+#[delete("/delete/delete-api-token/<id>")]
+pub async fn delete_api_token(auth: GuardResult<GuardAuth>, id: String) -> AppResult<()> {
+    let auth = auth?;
+    auth.require_session()?;
+    tokio::task::spawn_blocking(move || -> AppResult<()> {
+        let Some(api_token) = TREE.read_api_token(&id)? else {
+            return Err(AppError {
+                status: Status::NotFound,
+                error: anyhow!("API token {} not found", id),
+            });
+        };
+        if let Some(username) = auth.claims.get_username()
+            && api_token.username.as_deref() != Some(username)
+        {
+            return Err(AccessDenied(format!(
+                "{} cannot revoke API token {} of another account",
+                username, id
+            ))
+            .into());
+        }
+        TREE.remove_api_token(&id)?;
+        info!("Revoked API token {}", id);
+        Ok(())
+    })
+    .await?
+}
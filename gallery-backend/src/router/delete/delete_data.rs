@@ -1,6 +1,7 @@
 use crate::operations::open_db::{open_data_table, open_tree_snapshot_table};
 use crate::process::transitor::index_to_abstract_data;
 use crate::public::structure::abstract_data::AbstractData;
+use crate::router::claims::claims::Claims;
 use crate::router::fairing::guard_auth::GuardAuth;
 use crate::router::fairing::guard_read_only_mode::GuardReadOnlyMode;
 use crate::router::{AppResult, GuardResult};
@@ -24,11 +25,11 @@ pub async fn delete_data(
     _read_only_mode: GuardReadOnlyMode,
     json_data: Json<DeleteList>,
 ) -> AppResult<()> {
-    let _ = auth?;
+    let auth = auth?;
     let (abstract_data_to_remove, all_affected_album_ids) = tokio::task::spawn_blocking({
         let delete_list = json_data.delete_list.clone();
         let timestamp = json_data.timestamp;
-        move || process_deletes(delete_list, timestamp)
+        move || process_deletes(delete_list, timestamp, &auth.claims)
     })
     .await??;
 
@@ -56,6 +57,7 @@ pub async fn delete_data(
 fn process_deletes(
     delete_list: Vec<usize>,
     timestamp: u128,
+    claims: &Claims,
 ) -> Result<(Vec<AbstractData>, Vec<ArrayString<64>>)> {
     let data_table = open_data_table();
     let tree_snapshot = open_tree_snapshot_table(timestamp)?;
@@ -64,8 +66,8 @@ fn process_deletes(
     let mut abstract_data_to_remove = Vec::new();
 
     for index in delete_list {
-        let abstract_data =
-            index_to_abstract_data(&tree_snapshot, &data_table, index)?;
+        let abstract_data = index_to_abstract_data(&tree_snapshot, &data_table, index)?;
+        claims.check_owner(abstract_data.object())?;
 
         let affected_albums = match &abstract_data {
             AbstractData::Image(img) => img.metadata.albums.iter().cloned().collect(),
@@ -0,0 +1,33 @@
+use crate::public::db::tree::TREE;
+use crate::router::fairing::guard_auth::GuardAuth;
+use crate::router::{AccessDenied, AppError, AppResult, GuardResult};
+use anyhow::anyhow;
+use rocket::http::Status;
+
+/// Revoke a session; the admin may revoke any, users only their own
+#[delete("/delete/delete-session/<jti>")]
+pub async fn delete_session(auth: GuardResult<GuardAuth>, jti: String) -> AppResult<()> {
+    let auth = auth?;
+    auth.require_session()?;
+    tokio::task::spawn_blocking(move || -> AppResult<()> {
+        let Some(session) = TREE.read_session(&jti)? else {
+            return Err(AppError {
+                status: Status::NotFound,
+                error: anyhow!("Session {} not found", jti),
+            });
+        };
+        if let Some(username) = auth.claims.get_username()
+            && session.username.as_deref() != Some(username)
+        {
+            return Err(AccessDenied(format!(
+                "{} cannot revoke session {} of another account",
+                username, jti
+            ))
+            .into());
+        }
+        TREE.remove_session(&jti)?;
+        info!("Revoked session {}", jti);
+        Ok(())
+    })
+    .await?
+}
@@ -0,0 +1,40 @@
+use crate::public::db::tree::TREE;
+use crate::router::fairing::guard_admin::GuardAdmin;
+use crate::router::fairing::guard_read_only_mode::GuardReadOnlyMode;
+use crate::router::{AppError, AppResult, GuardResult};
+use crate::tasks::BATCH_COORDINATOR;
+use crate::tasks::batcher::update_tree::UpdateTreeTask;
+use anyhow::{Result, anyhow};
+use rocket::http::Status;
+
+/// Remove an account, sign it out and revoke its API tokens; its photos, videos and albums pass to the admin
+#[delete("/delete/delete-user/<username>")]
+pub async fn delete_user(
+    auth: GuardResult<GuardAdmin>,
+    read_only_mode: Result<GuardReadOnlyMode>,
+    username: String,
+) -> AppResult<()> {
+    let _ = auth?;
+    let _ = read_only_mode?;
+    let removed = tokio::task::spawn_blocking({
+        let username = username.clone();
+        move || -> Result<bool> {
+            let removed = TREE.remove_user(&username)?;
+            TREE.retain_sessions(|session| session.username.as_ref() != Some(&username))?;
+            TREE.retain_api_tokens(|api_token| api_token.username.as_ref() != Some(&username))?;
+            Ok(removed)
+        }
+    })
+    .await??;
+    if !removed {
+        return Err(AppError {
+            status: Status::NotFound,
+            error: anyhow!("User {} not found", username),
+        });
+    }
+    BATCH_COORDINATOR
+        .execute_batch_waiting(UpdateTreeTask)
+        .await?;
+    info!("Deleted user {}", username);
+    Ok(())
+}
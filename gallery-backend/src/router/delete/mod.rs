@@ -1,7 +1,15 @@
 use rocket::Route;
 
+pub mod delete_api_token;
 pub mod delete_data;
+pub mod delete_session;
+pub mod delete_user;
 
 pub fn generate_delete_routes() -> Vec<Route> {
-    routes![delete_data::delete_data]
+    routes![
+        delete_api_token::delete_api_token,
+        delete_data::delete_data,
+        delete_session::delete_session,
+        delete_user::delete_user
+    ]
 }
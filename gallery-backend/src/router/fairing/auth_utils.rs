@@ -1,8 +1,10 @@
 use crate::public::constant::redb::DATA_TABLE;
 use crate::public::db::tree::TREE;
 use crate::public::structure::abstract_data::AbstractData;
-use crate::public::structure::album::{ResolvedShare, Share};
-use crate::router::claims::claims::Claims;
+use crate::public::structure::album::{AlbumCombined, ResolvedShare, Share};
+use crate::public::structure::api_token::{API_TOKEN_PREFIX, ApiToken};
+use crate::router::AccessDenied;
+use crate::router::claims::claims::{Claims, Role};
 use crate::router::post::authenticate::JSON_WEB_TOKEN_SECRET_KEY;
 use anyhow::Error;
 use anyhow::Result;
@@ -15,6 +17,9 @@ use rocket::Request;
 use serde::de::DeserializeOwned;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use super::VALIDATION;
+use super::route_scope::required_scope;
+
 // Error types for share validation
 #[derive(Debug)]
 pub enum ShareError {
@@ -23,10 +28,12 @@ pub enum ShareError {
     Internal(anyhow::Error),
 }
 
-/// Extract and validate Authorization header Bearer token
+/// Extract and validate Authorization header Bearer token. API tokens in the header only
+/// authenticate the caller, so the hash or timestamp token then comes from the query.
 pub fn extract_bearer_token<'a>(req: &'a Request<'_>) -> Result<&'a str> {
     if let Some(auth_header) = req.headers().get_one("Authorization") {
         match auth_header.strip_prefix("Bearer ") {
+            Some(token) if token.starts_with(API_TOKEN_PREFIX) => {}
             Some(token) => return Ok(token),
             None => {
                 return Err(anyhow!(
@@ -59,20 +66,74 @@ pub fn my_decode_token<T: DeserializeOwned>(token: &str, validation: &Validation
     }
 }
 
-/// Try to authenticate via JWT cookie and check if user is admin
+/// Try to authenticate via JWT cookie as the admin or as an existing user
 pub fn try_jwt_cookie_auth(req: &Request<'_>, validation: &Validation) -> Result<Claims> {
     if let Some(jwt_cookie) = req.cookies().get("jwt") {
         let token = jwt_cookie.value();
         let claims = my_decode_token::<Claims>(token, validation)?;
-        if claims.is_admin() {
-            return Ok(claims);
-        } else {
-            return Err(anyhow!("User is not an admin"));
+        if !claims.is_admin() && claims.get_username().is_none() {
+            return Err(anyhow!("Token is neither an admin nor a user token"));
+        }
+
+        // Revoked sessions stop working right away, even before the token expires
+        let Some(jti) = claims.jti.as_deref() else {
+            return Err(anyhow!("Token does not belong to a session"));
+        };
+        match TREE.read_session(jti)? {
+            Some(session) if session.username.as_deref() == claims.get_username() => {}
+            _ => return Err(anyhow!("Session {} has been revoked", jti)),
         }
+
+        // Tokens of deleted accounts stop working right away
+        if let Some(username) = claims.get_username()
+            && TREE.read_user(username)?.is_none()
+        {
+            return Err(anyhow!("User {} no longer exists", username));
+        }
+        return Ok(claims);
     }
     Err(anyhow!("JWT not found in cookies"))
 }
 
+/// Try to authenticate via an API token in the Authorization header, which must grant the scope
+/// of the route. Returns `None` when the request carries no API token, leaving Bearer JWTs to
+/// other checks.
+pub fn try_api_token_auth(req: &Request<'_>) -> Result<Option<Claims>> {
+    let Some(token) = req
+        .headers()
+        .get_one("Authorization")
+        .and_then(|auth_header| auth_header.strip_prefix("Bearer "))
+        .filter(|token| token.starts_with(API_TOKEN_PREFIX))
+    else {
+        return Ok(None);
+    };
+
+    let Some(scope) = required_scope(req) else {
+        return Err(AccessDenied("This route requires a signed-in session".to_string()).into());
+    };
+    let (id, secret) = ApiToken::parse(token).ok_or_else(|| anyhow!("Malformed API token"))?;
+    let mut api_token = match TREE.read_api_token(id)? {
+        Some(api_token) if api_token.verify_secret(secret) => api_token,
+        _ => return Err(anyhow!("API token {} is invalid or has been revoked", id)),
+    };
+    if !api_token.allows(scope) {
+        return Err(AccessDenied(format!("API token {} lacks the {:?} scope", id, scope)).into());
+    }
+
+    if api_token.touch() {
+        TREE.write_api_token(&api_token)?;
+    }
+    Ok(Some(Claims::new_api_token(api_token.username)))
+}
+
+/// API token granting the scope of the route if the request has one, otherwise the JWT cookie
+pub fn try_token_or_cookie_auth(req: &Request<'_>, validation: &Validation) -> Result<Claims> {
+    match try_api_token_auth(req)? {
+        Some(claims) => Ok(claims),
+        None => try_jwt_cookie_auth(req, validation),
+    }
+}
+
 /// Extract hash from the request URL path (last segment before extension)
 pub fn extract_hash_from_path(req: &Request<'_>) -> Result<String> {
     let hash_opt = req
@@ -89,8 +150,8 @@ pub fn extract_hash_from_path(req: &Request<'_>) -> Result<String> {
     }
 }
 
-/// Validate share access: check expiration and password
-fn validate_share_access(share: &Share, req: &Request<'_>) -> Result<(), ShareError> {
+/// Validate share access: check expiration, and that password protected shares were unlocked
+pub fn validate_share_access(share: &Share, unlocked: bool) -> Result<(), ShareError> {
     // 1. Check expiration
     if share.exp > 0 {
         let now = SystemTime::now()
@@ -106,26 +167,95 @@ fn validate_share_access(share: &Share, req: &Request<'_>) -> Result<(), ShareEr
         }
     }
 
-    // 2. Check password
-    if let Some(ref pwd) = share.password {
-        // Check Header: x-share-password
-        if let Some(header_pwd) = req.headers().get_one("x-share-password") {
-            if header_pwd == pwd {
-                return Ok(());
-            }
-        }
+    // 2. Check password: it is only accepted by /post/share/unlock, which hands out a share token
+    if share.password.is_some() && !unlocked {
+        return Err(ShareError::Unauthorized);
+    }
 
-        // Check Query Param: password
-        if let Some(Ok(query_pwd)) = req.query_value::<&str>("password") {
-            if query_pwd == pwd {
-                return Ok(());
-            }
+    Ok(())
+}
+
+/// Read the album and one of its shares
+pub fn read_shared_album(
+    album_id: &str,
+    share_id: &str,
+) -> Result<(AlbumCombined, Share), ShareError> {
+    let read_txn = TREE
+        .in_disk
+        .begin_read()
+        .map_err(|e| ShareError::Internal(anyhow!("Failed to begin read transaction: {}", e)))?;
+
+    let table = read_txn
+        .open_table(DATA_TABLE)
+        .map_err(|e| ShareError::Internal(anyhow!("Failed to open data table: {}", e)))?;
+
+    let data_guard = table
+        .get(album_id)
+        .map_err(|e| ShareError::Internal(anyhow!("Failed to get data from table: {}", e)))?
+        .ok_or_else(|| ShareError::Internal(anyhow!("Album not found for id '{}'", album_id)))?;
+
+    let mut album = match data_guard.value() {
+        AbstractData::Album(album) => album,
+        _ => {
+            return Err(ShareError::Internal(anyhow!(
+                "Data with id '{}' is not an album",
+                album_id
+            )));
         }
+    };
+
+    let share = album.metadata.share_list.remove(share_id).ok_or_else(|| {
+        ShareError::Internal(anyhow!(
+            "Share '{}' not found in album '{}'",
+            share_id,
+            album_id
+        ))
+    })?;
+
+    Ok((album, share))
+}
+
+fn resolve_share(album: AlbumCombined, share: Share) -> Result<Claims, ShareError> {
+    let resolved_share = ResolvedShare::new(
+        ArrayString::<64>::from(album.object.id.as_str())
+            .map_err(|_| ShareError::Internal(anyhow!("Failed to parse album_id")))?,
+        album.metadata.title,
+        share,
+    );
+    Ok(Claims::new_share(resolved_share))
+}
+
+/// Share token from the x-share-token header or the shareToken query parameter
+fn extract_share_token<'a>(req: &'a Request<'_>) -> Option<&'a str> {
+    req.headers()
+        .get_one("x-share-token")
+        .or_else(|| req.query_value::<&str>("shareToken").and_then(Result::ok))
+}
 
+/// Album and share named by a token from /post/share/unlock, re-read so edits and deletions apply
+fn read_unlocked_share(token: &str) -> Result<(AlbumCombined, Share), ShareError> {
+    let claims = my_decode_token::<Claims>(token, &VALIDATION).map_err(|err| {
+        info!("Rejected share token: {:#}", err);
+        ShareError::Unauthorized
+    })?;
+    let Role::Share(resolved_share) = claims.role else {
         return Err(ShareError::Unauthorized);
-    }
+    };
 
-    Ok(())
+    let (album, share) = read_shared_album(&resolved_share.album_id, &resolved_share.share.url)?;
+    validate_share_access(&share, true)?;
+    Ok((album, share))
+}
+
+/// Try to resolve album and share from a share token
+pub fn try_resolve_share_from_token(req: &Request<'_>) -> Result<Option<Claims>, ShareError> {
+    match extract_share_token(req) {
+        Some(token) => {
+            let (album, share) = read_unlocked_share(token)?;
+            resolve_share(album, share).map(Some)
+        }
+        None => Ok(None),
+    }
 }
 
 /// Try to resolve album and share from headers
@@ -141,51 +271,12 @@ pub fn try_resolve_share_from_headers(req: &Request<'_>) -> Result<Option<Claims
         ))),
 
         (Some(album_id), Some(share_id)) => {
-            let read_txn = TREE.in_disk.begin_read().map_err(|e| {
-                ShareError::Internal(anyhow!("Failed to begin read transaction: {}", e))
-            })?;
-
-            let table = read_txn
-                .open_table(DATA_TABLE)
-                .map_err(|e| ShareError::Internal(anyhow!("Failed to open data table: {}", e)))?;
-
-            let data_guard = table
-                .get(album_id)
-                .map_err(|e| ShareError::Internal(anyhow!("Failed to get data from table: {}", e)))?
-                .ok_or_else(|| {
-                    ShareError::Internal(anyhow!("Album not found for id '{}'", album_id))
-                })?;
-
-            let abstract_data = data_guard.value();
-            let mut album = match abstract_data {
-                AbstractData::Album(album) => album,
-                _ => {
-                    return Err(ShareError::Internal(anyhow!(
-                        "Data with id '{}' is not an album",
-                        album_id
-                    )));
-                }
-            };
-
-            let share = album.metadata.share_list.remove(share_id).ok_or_else(|| {
-                ShareError::Internal(anyhow!(
-                    "Share '{}' not found in album '{}'",
-                    share_id,
-                    album_id
-                ))
-            })?;
+            let (album, share) = read_shared_album(album_id, share_id)?;
 
             // Validate share access (password and expiration)
-            validate_share_access(&share, req)?;
-
-            let resolved_share = ResolvedShare::new(
-                ArrayString::<64>::from(album_id)
-                    .map_err(|_| ShareError::Internal(anyhow!("Failed to parse album_id")))?,
-                album.metadata.title,
-                share,
-            );
-            let claims = Claims::new_share(resolved_share);
-            Ok(Some(claims))
+            validate_share_access(&share, false)?;
+
+            resolve_share(album, share).map(Some)
         }
     }
 }
@@ -203,84 +294,110 @@ pub fn try_resolve_share_from_query(req: &Request<'_>) -> Result<Option<Claims>,
         ))),
 
         (Some(album_id), Some(share_id)) => {
-            let read_txn = TREE.in_disk.begin_read().map_err(|e| {
-                ShareError::Internal(anyhow!("Failed to begin read transaction: {}", e))
-            })?;
-
-            let table = read_txn
-                .open_table(DATA_TABLE)
-                .map_err(|e| ShareError::Internal(anyhow!("Failed to open data table: {}", e)))?;
-
-            let data_guard = table
-                .get(album_id)
-                .map_err(|e| ShareError::Internal(anyhow!("Failed to get data from table: {}", e)))?
-                .ok_or_else(|| {
-                    ShareError::Internal(anyhow!("Album not found for id '{}'", album_id))
-                })?;
-
-            let abstract_data = data_guard.value();
-            let mut album = match abstract_data {
-                AbstractData::Album(album) => album,
-                _ => {
-                    return Err(ShareError::Internal(anyhow!(
-                        "Data with id '{}' is not an album",
-                        album_id
-                    )));
-                }
-            };
-
-            let share = album.metadata.share_list.remove(share_id).ok_or_else(|| {
-                ShareError::Internal(anyhow!(
-                    "Share '{}' not found in album '{}'",
-                    share_id,
-                    album_id
-                ))
-            })?;
+            let (album, share) = read_shared_album(album_id, share_id)?;
 
             // Validate share access (password and expiration)
-            validate_share_access(&share, req)?;
-
-            let resolved_share = ResolvedShare::new(
-                ArrayString::<64>::from(album_id)
-                    .map_err(|_| ShareError::Internal(anyhow!("Failed to parse album_id")))?,
-                album.metadata.title,
-                share,
-            );
-            let claims = Claims::new_share(resolved_share);
-            Ok(Some(claims))
+            validate_share_access(&share, false)?;
+
+            resolve_share(album, share).map(Some)
         }
     }
 }
 
-/// Try to authorize upload via share headers with upload permission
-pub fn try_authorize_upload_via_share(req: &Request<'_>) -> bool {
-    let album_id = req.headers().get_one("x-album-id");
-    let share_id = req.headers().get_one("x-share-id");
+/// Album that the share token or share headers allow uploading into, if any
+pub fn try_authorize_upload_via_share(req: &Request<'_>) -> Option<AlbumCombined> {
+    let (album, share) = match extract_share_token(req) {
+        Some(token) => read_unlocked_share(token).ok()?,
+        None => {
+            let album_id = req.headers().get_one("x-album-id")?;
+            let share_id = req.headers().get_one("x-share-id")?;
+            let (album, share) = read_shared_album(album_id, share_id).ok()?;
+            // Ensure password and expiration are also valid for upload
+            validate_share_access(&share, false).ok()?;
+            (album, share)
+        }
+    };
 
-    if let (Some(album_id), Some(share_id)) = (album_id, share_id) {
-        if let Ok(read_txn) = TREE.in_disk.begin_read() {
-            if let Ok(table) = read_txn.open_table(DATA_TABLE) {
-                if let Ok(Some(data_guard)) = table.get(album_id) {
-                    let abstract_data = data_guard.value();
-                    if let AbstractData::Album(mut album) = abstract_data {
-                        if let Some(share) = album.metadata.share_list.remove(share_id) {
-                            if share.show_upload {
-                                // Ensure password and expiration are also valid for upload
-                                if validate_share_access(&share, req).is_err() {
-                                    return false;
-                                }
-
-                                if let Some(Ok(album_id_parsed)) =
-                                    req.query_value::<&str>("presigned_album_id_opt")
-                                {
-                                    return album.object.id.as_str() == album_id_parsed;
-                                }
-                            }
-                        }
-                    }
-                }
+    if !share.show_upload {
+        return None;
+    }
+    match req.query_value::<&str>("presigned_album_id_opt") {
+        Some(Ok(album_id_parsed)) if album.object.id.as_str() == album_id_parsed => Some(album),
+        _ => None,
+    }
+}
+
+/// Whether `username` may add to the album; missing albums are left to the upload to report
+pub fn can_upload_to_album(username: &str, album_id: &str) -> Result<bool> {
+    let read_txn = TREE.in_disk.begin_read()?;
+    let table = read_txn.open_table(DATA_TABLE)?;
+    Ok(match table.get(album_id)?.map(|guard| guard.value()) {
+        Some(AbstractData::Album(album)) => album.is_accessible_by(username),
+        Some(_) => false,
+        None => true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::Client;
+    use rocket::request::{FromRequest, Outcome};
+    use rocket::{get, routes};
+
+    /// The token a hash or timestamp guard would decode, together with the path hash
+    struct ExtractedToken(String);
+
+    #[rocket::async_trait]
+    impl<'r> FromRequest<'r> for ExtractedToken {
+        type Error = ();
+
+        async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+            match (extract_bearer_token(req), extract_hash_from_path(req)) {
+                (Ok(token), Ok(hash)) => Outcome::Success(Self(format!("{} {}", token, hash))),
+                _ => Outcome::Error((Status::Unauthorized, ())),
             }
         }
     }
-    false
+
+    #[get("/object/imported/<_>/<_>")]
+    fn imported_file(extracted_token: ExtractedToken) -> String {
+        extracted_token.0
+    }
+
+    fn client() -> Client {
+        Client::untracked(rocket::build().mount("/", routes![imported_file])).unwrap()
+    }
+
+    #[test]
+    fn api_token_leaves_the_hash_token_to_the_query() {
+        let client = client();
+        let response = client
+            .get("/object/imported/ab/abcd.jpg?token=hash.jwt.token")
+            .header(Header::new("Authorization", "Bearer uro_id_secret"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_string().unwrap(), "hash.jwt.token abcd");
+    }
+
+    #[test]
+    fn api_token_alone_is_not_a_hash_token() {
+        let client = client();
+        let response = client
+            .get("/object/imported/ab/abcd.jpg")
+            .header(Header::new("Authorization", "Bearer uro_id_secret"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+
+    #[test]
+    fn bearer_hash_token_wins_over_the_query() {
+        let client = client();
+        let response = client
+            .get("/object/imported/ab/abcd.jpg?token=other")
+            .header(Header::new("Authorization", "Bearer hash.jwt.token"))
+            .dispatch();
+        assert_eq!(response.into_string().unwrap(), "hash.jwt.token abcd");
+    }
 }
@@ -0,0 +1,33 @@
+use rocket::Request;
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome};
+
+use crate::router::GuardError;
+
+use super::VALIDATION;
+use super::auth_utils::try_token_or_cookie_auth;
+
+/// Signed in as the admin; used for routes that act on the whole instance
+pub struct GuardAdmin;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for GuardAdmin {
+    type Error = GuardError;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match try_token_or_cookie_auth(req, &VALIDATION) {
+            Ok(claims) if claims.is_admin() => Outcome::Success(GuardAdmin),
+            Ok(_) => Outcome::Error((
+                Status::Forbidden,
+                GuardError {
+                    status: Status::Forbidden,
+                    error: anyhow::anyhow!("Only the admin can do this"),
+                },
+            )),
+            Err(err) => Outcome::Error((
+                Status::InternalServerError,
+                err.context("Authentication error").into(),
+            )),
+        }
+    }
+}
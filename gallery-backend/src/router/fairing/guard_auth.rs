@@ -2,20 +2,45 @@ use rocket::Request;
 use rocket::http::Status;
 use rocket::request::{FromRequest, Outcome};
 
-use crate::router::GuardError;
+use crate::public::db::tree::TREE;
+use crate::public::structure::user::UserScope;
+use crate::router::{AccessDenied, GuardError};
+use crate::router::claims::claims::Claims;
 
 use super::VALIDATION;
-use super::auth_utils::try_jwt_cookie_auth;
+use super::auth_utils::try_token_or_cookie_auth;
 
-pub struct GuardAuth;
+/// Signed in as the admin or as a user
+pub struct GuardAuth {
+    pub claims: Claims,
+}
+
+impl GuardAuth {
+    /// `None` for the admin, who is not limited to a scope
+    pub fn user_scope(&self) -> Option<UserScope> {
+        self.claims
+            .get_username()
+            .map(|username| TREE.user_scope(username))
+    }
+
+    /// Account management needs a signed-in session, so an API token cannot take over the account
+    pub fn require_session(&self) -> Result<(), AccessDenied> {
+        match self.claims.jti {
+            Some(_) => Ok(()),
+            None => Err(AccessDenied(
+                "This requires a signed-in session, not an API token".to_string(),
+            )),
+        }
+    }
+}
 
 #[rocket::async_trait]
 impl<'r> FromRequest<'r> for GuardAuth {
     type Error = GuardError;
 
     async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        match try_jwt_cookie_auth(req, &VALIDATION) {
-            Ok(_) => Outcome::Success(GuardAuth),
+        match try_token_or_cookie_auth(req, &VALIDATION) {
+            Ok(claims) => Outcome::Success(GuardAuth { claims }),
             Err(err) => Outcome::Error((
                 Status::InternalServerError,
                 err.context("Authentication error").into(),
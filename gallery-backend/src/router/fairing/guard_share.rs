@@ -4,7 +4,8 @@ use rocket::request::{FromRequest, Outcome};
 
 use super::VALIDATION;
 use super::auth_utils::{
-    ShareError, try_jwt_cookie_auth, try_resolve_share_from_headers, try_resolve_share_from_query,
+    ShareError, try_resolve_share_from_headers, try_resolve_share_from_query,
+    try_resolve_share_from_token, try_token_or_cookie_auth,
 };
 use crate::router::GuardError;
 use crate::router::claims::claims::Claims;
@@ -18,6 +19,32 @@ impl<'r> FromRequest<'r> for GuardShare {
     type Error = GuardError;
 
     async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        // share token from /post/share/unlock
+        match try_resolve_share_from_token(req) {
+            Ok(Some(claims)) => return Outcome::Success(GuardShare { claims }),
+            Ok(None) => {}
+            Err(err) => {
+                let status = match err {
+                    ShareError::Unauthorized => Status::Unauthorized,
+                    ShareError::Expired => Status::Forbidden,
+                    ShareError::Internal(_) => Status::InternalServerError,
+                };
+
+                let err_msg = match err {
+                    ShareError::Internal(e) => e,
+                    _ => anyhow::anyhow!("Share authentication failed: {:?}", err),
+                };
+
+                return Outcome::Error((
+                    status,
+                    GuardError {
+                        status,
+                        error: err_msg,
+                    },
+                ));
+            }
+        }
+
         // headers
         match try_resolve_share_from_headers(req) {
             Ok(Some(claims)) => return Outcome::Success(GuardShare { claims }),
@@ -70,17 +97,13 @@ impl<'r> FromRequest<'r> for GuardShare {
             }
         }
 
-        // Fall back to JWT cookie authentication (Admin)
-        match try_jwt_cookie_auth(req, &VALIDATION) {
+        // Fall back to an API token or JWT cookie authentication (Admin or user)
+        match try_token_or_cookie_auth(req, &VALIDATION) {
             Ok(claims) => return Outcome::Success(GuardShare { claims }),
             Err(err) => {
-                return Outcome::Error((
-                    Status::Unauthorized,
-                    GuardError {
-                        status: Status::Unauthorized,
-                        error: err.context("Authentication error"),
-                    },
-                ));
+                // Unauthorized, or Forbidden for an API token without the scope of the route
+                let guard_error = GuardError::from(err.context("Authentication error"));
+                return Outcome::Error((guard_error.status, guard_error));
             }
         }
     }
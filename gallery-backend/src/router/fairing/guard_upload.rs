@@ -5,9 +5,14 @@ use rocket::request::{FromRequest, Outcome};
 use crate::router::GuardError;
 
 use super::VALIDATION;
-use super::auth_utils::{try_authorize_upload_via_share, try_jwt_cookie_auth};
+use super::auth_utils::{
+    can_upload_to_album, try_authorize_upload_via_share, try_token_or_cookie_auth,
+};
 
-pub struct GuardUpload;
+pub struct GuardUpload {
+    /// Owner given to new files: the uploading user, or the album owner for shares
+    pub owner: Option<String>,
+}
 
 #[rocket::async_trait]
 impl<'r> FromRequest<'r> for GuardUpload {
@@ -15,13 +20,49 @@ impl<'r> FromRequest<'r> for GuardUpload {
 
     async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
         // Try to authorize upload via share first
-        if try_authorize_upload_via_share(req) {
-            return Outcome::Success(GuardUpload);
+        if let Some(album) = try_authorize_upload_via_share(req) {
+            return Outcome::Success(GuardUpload {
+                owner: album.object.owner,
+            });
         }
 
-        // Fall back to JWT cookie authentication
-        match try_jwt_cookie_auth(req, &VALIDATION) {
-            Ok(_) => return Outcome::Success(GuardUpload),
+        // Fall back to an API token or JWT cookie authentication
+        match try_token_or_cookie_auth(req, &VALIDATION) {
+            Ok(claims) => {
+                let Some(username) = claims.get_username() else {
+                    return Outcome::Success(GuardUpload { owner: None });
+                };
+                if let Some(Ok(album_id)) = req.query_value::<&str>("presigned_album_id_opt") {
+                    match can_upload_to_album(username, album_id) {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            return Outcome::Error((
+                                Status::Forbidden,
+                                GuardError {
+                                    status: Status::Forbidden,
+                                    error: anyhow::anyhow!(
+                                        "Album {} is not accessible by {}",
+                                        album_id,
+                                        username
+                                    ),
+                                },
+                            ));
+                        }
+                        Err(err) => {
+                            return Outcome::Error((
+                                Status::InternalServerError,
+                                GuardError {
+                                    status: Status::InternalServerError,
+                                    error: err.context("Failed to check album access"),
+                                },
+                            ));
+                        }
+                    }
+                }
+                Outcome::Success(GuardUpload {
+                    owner: Some(username.to_string()),
+                })
+            }
             Err(err) => {
                 let full_err = err.context("Authentication error").into();
                 Outcome::Error((Status::Unauthorized, full_err))
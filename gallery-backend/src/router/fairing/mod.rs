@@ -5,12 +5,15 @@ use rocket::Route;
 
 pub mod auth_utils;
 pub mod cache_control_fairing;
+pub mod guard_admin;
 pub mod guard_auth;
 pub mod guard_hash;
 pub mod guard_read_only_mode;
 pub mod guard_share;
 pub mod guard_timestamp;
 pub mod guard_upload;
+pub mod route_scope;
+pub mod throttle;
 
 pub fn generate_fairing_routes() -> Vec<Route> {
     routes![
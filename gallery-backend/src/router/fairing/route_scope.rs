@@ -0,0 +1,106 @@
+use rocket::Request;
+
+use crate::public::structure::api_token::ApiScope;
+
+/// Scope an API token needs for each route, by handler name. Routes that are not listed only
+/// accept a signed-in session: account management (passwords, users, sessions, API tokens)
+/// must never be reachable with a token, or a leaked token could take over the account.
+const ROUTE_SCOPES: &[(&str, ApiScope)] = &[
+    // Reads
+    ("prefetch", ApiScope::ReadOnly),
+    ("compressed_file", ApiScope::ReadOnly),
+    ("imported_file", ApiScope::ReadOnly),
+    ("get_album_zip", ApiScope::ReadOnly),
+    ("get_config", ApiScope::ReadOnly),
+    ("get_tags", ApiScope::ReadOnly),
+    ("get_albums", ApiScope::ReadOnly),
+    ("get_similar", ApiScope::ReadOnly),
+    // Uploads
+    ("upload", ApiScope::Upload),
+    // Changes to the owner's library
+    ("create_empty_album", ApiScope::EditMetadata),
+    ("create_non_empty_album", ApiScope::EditMetadata),
+    ("create_smart_album", ApiScope::EditMetadata),
+    ("create_share", ApiScope::EditMetadata),
+    ("edit_album", ApiScope::EditMetadata),
+    ("set_album_cover", ApiScope::EditMetadata),
+    ("set_album_title", ApiScope::EditMetadata),
+    ("set_smart_album_expression", ApiScope::EditMetadata),
+    ("set_album_users", ApiScope::EditMetadata),
+    ("edit_datetime", ApiScope::EditMetadata),
+    ("edit_flags", ApiScope::EditMetadata),
+    ("edit_share", ApiScope::EditMetadata),
+    ("delete_share", ApiScope::EditMetadata),
+    ("edit_tag", ApiScope::EditMetadata),
+    ("set_user_defined_description", ApiScope::EditMetadata),
+    ("regenerate_thumbnail_with_frame", ApiScope::EditMetadata),
+    ("reindex", ApiScope::EditMetadata),
+    ("delete_data", ApiScope::EditMetadata),
+    // Instance administration
+    ("get_backup", ApiScope::Admin),
+    ("get_export", ApiScope::Admin),
+    ("get_users", ApiScope::Admin),
+    ("import_export", ApiScope::Admin),
+    ("restore", ApiScope::Admin),
+    ("rescan", ApiScope::Admin),
+    ("generate_random_data", ApiScope::Admin),
+];
+
+/// Scope an API token needs for the route handling `req`; `None` if tokens are not accepted
+pub fn required_scope(req: &Request<'_>) -> Option<ApiScope> {
+    let route_name = req.route()?.name.as_deref()?;
+    route_scope(route_name)
+}
+
+fn route_scope(route_name: &str) -> Option<ApiScope> {
+    ROUTE_SCOPES
+        .iter()
+        .find(|(name, _)| *name == route_name)
+        .map(|(_, scope)| *scope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::{
+        delete::generate_delete_routes, fairing::generate_fairing_routes,
+        get::generate_get_routes, post::generate_post_routes, put::generate_put_routes,
+    };
+    use std::collections::HashSet;
+
+    #[test]
+    fn every_listed_route_is_mounted() {
+        let mounted: HashSet<String> = [
+            generate_get_routes(),
+            generate_post_routes(),
+            generate_put_routes(),
+            generate_delete_routes(),
+            generate_fairing_routes(),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|route| route.name.map(|name| name.into_owned()))
+        .collect();
+
+        for (name, _) in ROUTE_SCOPES {
+            assert!(mounted.contains(*name), "{} is not a mounted route", name);
+        }
+    }
+
+    #[test]
+    fn account_management_needs_a_session() {
+        for name in [
+            "edit_user_password",
+            "create_user",
+            "delete_user",
+            "create_api_token",
+            "delete_api_token",
+            "get_api_tokens",
+            "delete_session",
+            "get_sessions",
+            "logout",
+        ] {
+            assert_eq!(route_scope(name), None, "{} accepts API tokens", name);
+        }
+    }
+}
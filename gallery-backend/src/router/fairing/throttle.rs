@@ -0,0 +1,125 @@
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+/// Failures allowed before an address is locked out
+const FREE_ATTEMPTS: u32 = 5;
+/// First lockout; every further failure doubles it
+const BASE_LOCKOUT: Duration = Duration::from_secs(1);
+const MAX_LOCKOUT: Duration = Duration::from_secs(15 * 60);
+/// Failures older than this are forgotten
+const FORGET_AFTER: Duration = Duration::from_secs(60 * 60);
+
+pub static LOGIN_THROTTLE: LazyLock<Throttle> = LazyLock::new(Throttle::default);
+
+pub static SHARE_PASSWORD_THROTTLE: LazyLock<Throttle> = LazyLock::new(Throttle::default);
+
+#[derive(Debug)]
+struct FailureRecord {
+    /// Failures per target (account or share), so signing in to one cannot reset another
+    failure_counts: HashMap<String, u32>,
+    last_failure: Instant,
+    locked_until: Option<Instant>,
+}
+
+impl FailureRecord {
+    fn failure_count(&self) -> u32 {
+        self.failure_counts.values().sum()
+    }
+}
+
+/// Per-IP exponential backoff for password checks
+#[derive(Debug, Default)]
+pub struct Throttle {
+    failure_map: DashMap<IpAddr, FailureRecord>,
+}
+
+impl Throttle {
+    /// Count an attempt by `ip` against `target` before its password is verified, so parallel
+    /// requests cannot all slip through while the hash is being checked. Fails with the time
+    /// left until `ip` may try again. The attempt stays counted as a failure unless
+    /// [`Throttle::record_success`] is called for the same target.
+    pub fn reserve(&self, ip: IpAddr, target: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        self.failure_map
+            .retain(|_, record| now.duration_since(record.last_failure) < FORGET_AFTER);
+
+        let mut record = self.failure_map.entry(ip).or_insert(FailureRecord {
+            failure_counts: HashMap::new(),
+            last_failure: now,
+            locked_until: None,
+        });
+        if let Some(locked_until) = record.locked_until
+            && locked_until > now
+        {
+            return Err(locked_until - now);
+        }
+
+        *record.failure_counts.entry(target.to_string()).or_insert(0) += 1;
+        record.last_failure = now;
+        let failure_count = record.failure_count();
+        if failure_count >= FREE_ATTEMPTS {
+            let exponent = (failure_count - FREE_ATTEMPTS).min(20);
+            let lockout = BASE_LOCKOUT.saturating_mul(1 << exponent).min(MAX_LOCKOUT);
+            record.locked_until = Some(now + lockout);
+            warn!(
+                "{} made {} failed or pending password checks, locked out for {:?}",
+                ip, failure_count, lockout
+            );
+        }
+        Ok(())
+    }
+
+    /// Forget the failures of `ip` against `target`; failures against other targets still count
+    pub fn record_success(&self, ip: IpAddr, target: &str) {
+        if let Some(mut record) = self.failure_map.get_mut(&ip) {
+            record.failure_counts.remove(target);
+            if record.failure_count() < FREE_ATTEMPTS {
+                record.locked_until = None;
+            }
+        }
+        self.failure_map
+            .remove_if(&ip, |_, record| record.failure_counts.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    const IP: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+    #[test]
+    fn locks_out_after_free_attempts() {
+        let throttle = Throttle::default();
+        for _ in 0..FREE_ATTEMPTS {
+            assert!(throttle.reserve(IP, "admin").is_ok());
+        }
+        assert!(throttle.reserve(IP, "admin").is_err());
+    }
+
+    #[test]
+    fn success_only_clears_its_own_target() {
+        let throttle = Throttle::default();
+        for _ in 0..FREE_ATTEMPTS - 1 {
+            throttle.reserve(IP, "admin").unwrap();
+        }
+        throttle.reserve(IP, "user:alice").unwrap();
+        throttle.record_success(IP, "user:alice");
+        assert!(throttle.reserve(IP, "admin").is_ok());
+        assert!(throttle.reserve(IP, "admin").is_err());
+    }
+
+    #[test]
+    fn success_lifts_the_lockout_of_its_own_attempt() {
+        let throttle = Throttle::default();
+        for _ in 0..FREE_ATTEMPTS {
+            throttle.reserve(IP, "admin").unwrap();
+        }
+        throttle.record_success(IP, "admin");
+        assert!(throttle.reserve(IP, "admin").is_ok());
+    }
+}
@@ -0,0 +1,85 @@
+use crate::operations::archive::{SidecarFormat, ZipDownload, stream_zip};
+use crate::operations::open_db::open_data_table;
+use crate::operations::transitor::hash_to_abstract_data;
+use crate::public::db::tree::TREE;
+use crate::public::structure::abstract_data::AbstractData;
+use crate::public::structure::user::UserScope;
+use crate::router::fairing::guard_share::GuardShare;
+use crate::router::{AccessDenied, AppError, AppResult, GuardResult};
+use anyhow::{Result, anyhow, bail};
+use arrayvec::ArrayString;
+use rocket::get;
+use rocket::http::Status;
+use rocket::response::stream::ByteStream;
+
+/// Download every photo and video of an album, smart album members included
+#[get("/get/download-album/<album_id>?<sidecar>")]
+pub async fn get_album_zip(
+    auth: GuardResult<GuardShare>,
+    album_id: &str,
+    sidecar: Option<SidecarFormat>,
+) -> AppResult<ZipDownload<ByteStream![Vec<u8>]>> {
+    let auth = auth?;
+    let album_id = ArrayString::<64>::from(album_id).map_err(|_| anyhow!("Invalid album id"))?;
+    let username_opt = auth.claims.get_username().map(str::to_string);
+    if let Some(resolved_share) = auth.claims.get_share() {
+        if resolved_share.album_id != album_id {
+            return Err(AppError {
+                status: Status::Forbidden,
+                error: anyhow!("Share does not include album {}", album_id),
+            });
+        }
+        if !resolved_share.share.show_download {
+            return Err(AppError {
+                status: Status::Forbidden,
+                error: anyhow!("Downloads are disabled for this share"),
+            });
+        }
+    }
+
+    let (title_opt, data_list) =
+        tokio::task::spawn_blocking(move || -> Result<(Option<String>, Vec<AbstractData>)> {
+            let data_table = open_data_table();
+            let AbstractData::Album(album) = hash_to_abstract_data(&data_table, album_id)? else {
+                bail!("{} is not an album", album_id);
+            };
+            if let Some(username) = &username_opt
+                && !album.is_accessible_by(username)
+            {
+                return Err(AccessDenied(format!(
+                    "Album {} is not accessible by {}",
+                    album_id, username
+                ))
+                .into());
+            }
+
+            let smart_members = TREE.smart_album_members(&album_id);
+            let tree_guard = TREE.in_memory.read().unwrap();
+            let scope_opt = username_opt
+                .as_deref()
+                .map(|username| UserScope::build(username, &tree_guard));
+            let data_list = tree_guard
+                .iter()
+                .map(|database_timestamp| &database_timestamp.abstract_data)
+                .filter(|abstract_data| {
+                    scope_opt
+                        .as_ref()
+                        .is_none_or(|scope| scope.can_read(abstract_data))
+                        && !abstract_data.object().is_trashed
+                        && (abstract_data
+                            .albums()
+                            .is_some_and(|albums| albums.contains(&album_id))
+                            || smart_members.contains(&abstract_data.hash()))
+                })
+                .cloned()
+                .collect();
+            Ok((album.metadata.title, data_list))
+        })
+        .await??;
+
+    info!("Downloading album {} as ZIP", album_id);
+    let name = title_opt
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| album_id.to_string());
+    Ok(stream_zip(&name, data_list, sidecar))
+}
@@ -0,0 +1,50 @@
+use crate::operations::backup::{backup_entry_line, backup_footer_line, backup_header_line};
+use crate::operations::open_db::open_data_table;
+use crate::router::fairing::guard_admin::GuardAdmin;
+use crate::router::{AppResult, GuardResult};
+use redb::ReadableTable;
+use rocket::get;
+use rocket::response::stream::ByteStream;
+
+/// Stream a backup of DATA_TABLE; every entry comes from the same read transaction
+#[get("/get/backup")]
+pub async fn get_backup(auth: GuardResult<GuardAdmin>) -> AppResult<ByteStream![Vec<u8>]> {
+    let _ = auth?;
+    let data_table = open_data_table();
+    let header_line = backup_header_line()?;
+    let byte_stream = ByteStream! {
+        yield header_line;
+
+        let iter = match data_table.iter() {
+            Ok(it) => it,
+            Err(err) => {
+                error!("Failed to iterate DATA_TABLE for backup: {:?}", err);
+                return;
+            }
+        };
+
+        let mut entry_count = 0;
+        for entry_res in iter {
+            let line_res = entry_res
+                .map_err(anyhow::Error::from)
+                .and_then(|(key, value)| backup_entry_line(key.value(), value.value()));
+            match line_res {
+                Ok(line) => {
+                    entry_count += 1;
+                    yield line;
+                }
+                // Stopping before the footer marks the backup as incomplete
+                Err(err) => {
+                    error!("Backup aborted: {:?}", err);
+                    return;
+                }
+            }
+        }
+
+        match backup_footer_line(entry_count) {
+            Ok(line) => yield line,
+            Err(err) => error!("Backup aborted: {:?}", err),
+        }
+    };
+    Ok(byte_stream)
+}
@@ -2,20 +2,20 @@ use crate::operations::open_db::open_data_table;
 use crate::router::{AppResult, GuardResult};
 use crate::{
     public::structure::abstract_data::AbstractData,
-    router::fairing::guard_auth::GuardAuth,
+    router::fairing::guard_admin::GuardAdmin,
 };
 use redb::ReadableTable;
 use rocket::get;
 use rocket::response::stream::ByteStream;
-use serde::Serialize;
-#[derive(Debug, Serialize)]
+use serde::{Deserialize, Serialize};
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ExportEntry {
-    key: String,
-    value: AbstractData,
+    pub key: String,
+    pub value: AbstractData,
 }
 
 #[get("/get/get-export")]
-pub async fn get_export(auth: GuardResult<GuardAuth>) -> AppResult<ByteStream![Vec<u8>]> {
+pub async fn get_export(auth: GuardResult<GuardAdmin>) -> AppResult<ByteStream![Vec<u8>]> {
     let _ = auth?;
     let data_table = open_data_table();
     let byte_stream = ByteStream! {
@@ -1,3 +1,5 @@
+use crate::operations::open_db::open_data_table;
+use crate::public::storage::{STORAGE, fetch_download};
 use crate::router::{
     AppResult, GuardResult,
     fairing::{
@@ -6,6 +8,7 @@ use crate::router::{
     },
 };
 use anyhow::Context;
+use anyhow::Result;
 use rocket::fs::NamedFile;
 use rocket::response::Responder;
 use rocket_seek_stream::SeekStream;
@@ -24,7 +27,7 @@ pub async fn compressed_file(
 ) -> AppResult<CompressedFileResponse<'static>> {
     let _ = auth_guard?;
     let _ = hash_guard?;
-    let compressed_file_path = Path::new("./object/compressed").join(&file_path);
+    let compressed_file_path = fetch_object(storage_key("compressed", &file_path)).await?;
 
     let result = match compressed_file_path
         .extension()
@@ -68,7 +71,7 @@ pub async fn imported_file(
 ) -> AppResult<CompressedFileResponse<'static>> {
     let _ = auth?;
     let _ = hash_guard?;
-    let imported_file_path = Path::new("./object/imported").join(&file_path);
+    let imported_file_path = download_path(file_path).await?;
     NamedFile::open(imported_file_path)
         .await
         .map(CompressedFileResponse::NamedFile)
@@ -77,3 +80,33 @@ pub async fn imported_file(
             anyhow::anyhow!("Error opening imported file: {:#?}", error).into()
         })
 }
+
+/// File served for `imported/<hash prefix>/<hash>.<ext>`: the copy with an edited capture
+/// date if there is one, the source of a referenced original, or else the stored original
+async fn download_path(file_path: PathBuf) -> Result<PathBuf> {
+    tokio::task::spawn_blocking(move || -> Result<PathBuf> {
+        let abstract_data_opt = match file_path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(hash) => open_data_table().get(hash)?.map(|guard| guard.value()),
+            None => None,
+        };
+        match abstract_data_opt {
+            Some(abstract_data) => fetch_download(&abstract_data),
+            None => STORAGE.fetch(&storage_key("imported", &file_path)),
+        }
+    })
+    .await?
+}
+
+/// Storage key of a file requested as `<prefix>/<file_path>`
+fn storage_key(prefix: &str, file_path: &Path) -> String {
+    file_path
+        .components()
+        .fold(prefix.to_string(), |key, component| {
+            format!("{}/{}", key, component.as_os_str().to_string_lossy())
+        })
+}
+
+/// Local copy of a stored object; remote backends may have to download it first
+async fn fetch_object(key: String) -> Result<PathBuf> {
+    tokio::task::spawn_blocking(move || STORAGE.fetch(&key)).await?
+}
@@ -2,6 +2,9 @@ use crate::public::config::{PUBLIC_CONFIG, PublicConfig};
 use crate::public::db::tree::TREE;
 use crate::public::db::tree::read_tags::TagInfo;
 use crate::public::structure::album::Share;
+use crate::public::structure::api_token::ApiScope;
+use crate::public::structure::expression::Expression;
+use crate::router::fairing::guard_admin::GuardAdmin;
 use crate::router::fairing::guard_auth::GuardAuth;
 use crate::router::fairing::guard_share::GuardShare;
 use crate::router::{AppResult, GuardResult};
@@ -9,7 +12,7 @@ use anyhow::Context;
 use arrayvec::ArrayString;
 use rocket::serde::json::Json;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[get("/get/get-config.json")]
 pub async fn get_config(auth: GuardResult<GuardShare>) -> AppResult<Json<&'static PublicConfig>> {
@@ -19,9 +22,10 @@ pub async fn get_config(auth: GuardResult<GuardShare>) -> AppResult<Json<&'stati
 
 #[get("/get/get-tags")]
 pub async fn get_tags(auth: GuardResult<GuardAuth>) -> AppResult<Json<Vec<TagInfo>>> {
-    let _ = auth?;
+    let auth = auth?;
     tokio::task::spawn_blocking(move || {
-        let vec_tags_info = TREE.read_tags();
+        let scope_opt = auth.user_scope();
+        let vec_tags_info = TREE.read_tags(scope_opt.as_ref());
         Ok(Json(vec_tags_info))
     })
     .await?
@@ -33,22 +37,146 @@ pub struct AlbumInfo {
     pub album_id: String,
     pub album_name: Option<String>,
     pub share_list: HashMap<ArrayString<64>, Share>,
+    /// Present for smart albums
+    pub expression: Option<Expression>,
+    pub owner: Option<String>,
+    pub granted_users: HashSet<String>,
 }
 
 #[get("/get/get-albums")]
 pub async fn get_albums(auth: GuardResult<GuardAuth>) -> AppResult<Json<Vec<AlbumInfo>>> {
-    let _ = auth?;
+    let auth = auth?;
     tokio::task::spawn_blocking(move || {
-        let album_list = TREE.read_albums().context("Failed to read albums")?;
+        let mut album_list = TREE.read_albums().context("Failed to read albums")?;
+        if let Some(username) = auth.claims.get_username() {
+            album_list.retain(|album| album.is_accessible_by(username));
+            // Share links are managed by the owner alone
+            for album in &mut album_list {
+                if album.object.owner.as_deref() != Some(username) {
+                    album.metadata.share_list.clear();
+                }
+            }
+        }
+        let mut smart_albums = TREE
+            .read_smart_albums()
+            .context("Failed to read smart albums")?;
         let album_info_list = album_list
             .into_iter()
             .map(|album| AlbumInfo {
                 album_id: album.object.id.to_string(),
                 album_name: album.metadata.title,
                 share_list: album.metadata.share_list,
+                expression: smart_albums.remove(&album.object.id),
+                owner: album.object.owner,
+                granted_users: album.metadata.granted_users,
             })
             .collect();
         Ok(Json(album_info_list))
     })
     .await?
 }
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UserInfo {
+    pub username: String,
+    pub created_time: u128,
+}
+
+#[get("/get/get-users")]
+pub async fn get_users(auth: GuardResult<GuardAdmin>) -> AppResult<Json<Vec<UserInfo>>> {
+    let _ = auth?;
+    tokio::task::spawn_blocking(move || {
+        let user_info_list = TREE
+            .read_users()
+            .context("Failed to read users")?
+            .into_iter()
+            .map(|user| UserInfo {
+                username: user.username,
+                created_time: user.created_time,
+            })
+            .collect();
+        Ok(Json(user_info_list))
+    })
+    .await?
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub jti: String,
+    pub username: Option<String>,
+    pub client_ip: Option<String>,
+    pub created_time: u128,
+    pub refreshed_time: u128,
+    pub exp: u64,
+    /// The session making this request
+    pub current: bool,
+}
+
+/// The admin sees every session, users only their own
+#[get("/get/get-sessions")]
+pub async fn get_sessions(auth: GuardResult<GuardAuth>) -> AppResult<Json<Vec<SessionInfo>>> {
+    let auth = auth?;
+    auth.require_session()?;
+    tokio::task::spawn_blocking(move || {
+        let username_opt = auth.claims.get_username();
+        let session_info_list = TREE
+            .read_sessions()
+            .context("Failed to read sessions")?
+            .into_iter()
+            .filter(|session| username_opt.is_none() || session.username.as_deref() == username_opt)
+            .filter(|session| !session.is_expired())
+            .map(|session| SessionInfo {
+                current: auth.claims.jti.as_deref() == Some(session.jti.as_str()),
+                jti: session.jti,
+                username: session.username,
+                client_ip: session.client_ip,
+                created_time: session.created_time,
+                refreshed_time: session.refreshed_time,
+                exp: session.exp,
+            })
+            .collect();
+        Ok(Json(session_info_list))
+    })
+    .await?
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiTokenInfo {
+    pub id: String,
+    pub name: String,
+    pub username: Option<String>,
+    pub scopes: HashSet<ApiScope>,
+    pub created_time: u128,
+    pub last_used_time: Option<u128>,
+}
+
+/// The admin sees every API token, users only their own
+#[get("/get/get-api-tokens")]
+pub async fn get_api_tokens(auth: GuardResult<GuardAuth>) -> AppResult<Json<Vec<ApiTokenInfo>>> {
+    let auth = auth?;
+    auth.require_session()?;
+    tokio::task::spawn_blocking(move || {
+        let username_opt = auth.claims.get_username();
+        let api_token_info_list = TREE
+            .read_api_tokens()
+            .context("Failed to read API tokens")?
+            .into_iter()
+            .filter(|api_token| {
+                username_opt.is_none() || api_token.username.as_deref() == username_opt
+            })
+            .map(|api_token| ApiTokenInfo {
+                id: api_token.id,
+                name: api_token.name,
+                username: api_token.username,
+                scopes: api_token.scopes,
+                created_time: api_token.created_time,
+                last_used_time: api_token.last_used_time,
+            })
+            .collect();
+        Ok(Json(api_token_info_list))
+    })
+    .await?
+}
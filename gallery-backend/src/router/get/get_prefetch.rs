@@ -6,6 +6,9 @@ use crate::public::structure::album::ResolvedShare;
 use crate::public::structure::response::database_timestamp::DatabaseTimestamp;
 use crate::public::structure::expression::Expression;
 use crate::public::structure::response::reduced_data::ReducedData;
+use crate::public::structure::sort::SortKey;
+use crate::public::structure::user::UserScope;
+use crate::router::AppError;
 use crate::router::AppResult;
 use crate::router::GuardResult;
 use crate::router::claims::claims_timestamp::ClaimsTimestamp;
@@ -19,6 +22,8 @@ use anyhow::{Result, anyhow};
 use bitcode::{Decode, Encode};
 use log::info;
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use rayon::slice::ParallelSliceMut;
+use rocket::http::Status;
 use rocket::serde::json::Json;
 use serde::{Deserialize, Serialize};
 use std::hash::Hasher;
@@ -108,38 +113,59 @@ fn check_query_cache(
 fn filter_items(
     expression_option: Option<Expression>,
     resolved_share_option: &Option<ResolvedShare>,
+    username_option: Option<&str>,
+    sort_key: SortKey,
+    seed: u64,
 ) -> Result<Vec<ReducedData>> {
     let filter_items_start_time = Instant::now();
 
-    let tree_guard = TREE.in_memory.read().map_err(|err| anyhow!("{:?}", err))?;
-    let reduced_data_vector: Vec<ReducedData> = match (expression_option, &resolved_share_option) {
+    let similar_to_option = expression_option
+        .as_ref()
+        .and_then(|expression| expression.similar_to());
+
+    let filter_fn_option = match (expression_option, &resolved_share_option) {
         // If we have a resolved share then it must have a filter expression
-        (Some(expr), Some(resolved_share)) => {
-            let filter_fn = if resolved_share.share.show_metadata {
-                expr.generate_filter()
-            } else {
-                expr.generate_filter_hide_metadata(resolved_share.album_id)
-            };
-            tree_guard
-                .par_iter()
-                .filter(|db_ts| filter_fn(&db_ts.abstract_data))
-                .map(|db_ts| db_ts.into())
-                .collect()
-        }
-        (Some(expr), None) => {
-            let filter_fn = expr.generate_filter();
-            tree_guard
-                .par_iter()
-                .filter(|database_timestamp| filter_fn(&database_timestamp.abstract_data))
-                .map(|database_timestamp| database_timestamp.into())
-                .collect()
-        }
-        (None, _) => tree_guard
-            .par_iter()
-            .map(|database_timestamp| database_timestamp.into())
-            .collect(),
+        (Some(expr), Some(resolved_share)) => Some(if resolved_share.share.show_metadata {
+            expr.generate_filter()
+        } else {
+            expr.generate_filter_hide_metadata(resolved_share.album_id)
+        }),
+        (Some(expr), None) => Some(expr.generate_filter()),
+        (None, _) => None,
     };
 
+    let tree_guard = TREE.in_memory.read().map_err(|err| anyhow!("{:?}", err))?;
+    let scope_option = username_option.map(|username| UserScope::build(username, &tree_guard));
+    let mut database_timestamp_vec: Vec<&DatabaseTimestamp> = tree_guard
+        .par_iter()
+        .filter(|database_timestamp| {
+            scope_option
+                .as_ref()
+                .is_none_or(|scope| scope.can_read(&database_timestamp.abstract_data))
+                && filter_fn_option
+                    .as_ref()
+                    .is_none_or(|filter_fn| filter_fn(database_timestamp))
+        })
+        .collect();
+
+    sort_key.sort(&mut database_timestamp_vec, seed);
+
+    // Similar images are listed closest first; the stable sort keeps ties in the requested order
+    if let Some((hash, max_distance)) = similar_to_option {
+        let distance_map = TREE.find_similar(&hash, max_distance);
+        database_timestamp_vec.par_sort_by_key(|database_timestamp| {
+            distance_map
+                .get(&database_timestamp.abstract_data.hash())
+                .copied()
+                .unwrap_or(u32::MAX)
+        });
+    }
+
+    let reduced_data_vector: Vec<ReducedData> = database_timestamp_vec
+        .par_iter()
+        .map(|database_timestamp| (*database_timestamp).into())
+        .collect();
+
     let duration = format!("{:?}", filter_items_start_time.elapsed());
     info!(duration = &*duration; "Filter items");
 
@@ -165,15 +191,24 @@ fn compute_locate(
     locate_to_index
 }
 
-fn build_cache_key(expression_option: &Option<Expression>, locate_option: &Option<String>) -> u64 {
+fn build_cache_key(
+    expression_option: &Option<Expression>,
+    locate_option: &Option<String>,
+    username_option: Option<&str>,
+    sort_key: SortKey,
+    seed: u64,
+) -> u64 {
     let cache_key_start_time = Instant::now();
 
     let mut hasher = DefaultHasher::new();
     expression_option.hash(&mut hasher);
+    sort_key.hash(&mut hasher);
+    seed.hash(&mut hasher);
     VERSION_COUNT_TIMESTAMP
         .load(Ordering::Relaxed)
         .hash(&mut hasher);
     locate_option.hash(&mut hasher);
+    username_option.hash(&mut hasher);
     let query_hash = hasher.finish();
 
     let duration = format!("{:?}", cache_key_start_time.elapsed());
@@ -182,12 +217,18 @@ fn build_cache_key(expression_option: &Option<Expression>, locate_option: &Optio
     query_hash
 }
 
-fn insert_data_into_tree_snapshot(reduced_data_vector: Vec<ReducedData>) -> Result<(u128, usize)> {
+pub fn insert_data_into_tree_snapshot(
+    reduced_data_vector: Vec<ReducedData>,
+    date_ordered: bool,
+) -> Result<(u128, usize)> {
     let db_start_time = Instant::now();
 
     // Persist to snapshot
     let timestamp_millis = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
     let reduced_data_vector_length = reduced_data_vector.len();
+    if !date_ordered {
+        TREE_SNAPSHOT.undated.insert(timestamp_millis);
+    }
     TREE_SNAPSHOT
         .in_memory
         .insert(timestamp_millis, reduced_data_vector);
@@ -240,27 +281,48 @@ fn execute_prefetch_logic(
     expression_option: Option<Expression>,
     locate_option: Option<String>,
     mut resolved_share_option: Option<ResolvedShare>,
+    username_option: Option<String>,
+    sort_key: SortKey,
+    seed: u64,
 ) -> Result<Json<PrefetchReturn>> {
     // Start timer
     let start_time = Instant::now();
 
     // Step 1: Build cache key for response creation
-    let query_hash = build_cache_key(&expression_option, &locate_option);
+    let query_hash = build_cache_key(
+        &expression_option,
+        &locate_option,
+        username_option.as_deref(),
+        sort_key,
+        seed,
+    );
 
     // Step 2: Check if query cache is available
     if let Some(cached_response) = check_query_cache(query_hash, &mut resolved_share_option) {
         return Ok(cached_response);
     }
 
+    // Similar images are listed closest first, whatever the sort key
+    let date_ordered = sort_key.is_date_based()
+        && expression_option
+            .as_ref()
+            .is_none_or(|expression| expression.similar_to().is_none());
+
     // Step 3: Filter items
-    let reduced_data_vector = filter_items(expression_option, &resolved_share_option)?;
+    let reduced_data_vector = filter_items(
+        expression_option,
+        &resolved_share_option,
+        username_option.as_deref(),
+        sort_key,
+        seed,
+    )?;
 
     // Step 4: Compute layout
     let locate_to_index = compute_locate(&reduced_data_vector, &locate_option);
 
     // Step 6: Insert data into TREE_SNAPSHOT
     let (timestamp_millis, reduced_data_vector_length) =
-        insert_data_into_tree_snapshot(reduced_data_vector)?;
+        insert_data_into_tree_snapshot(reduced_data_vector, date_ordered)?;
 
     // Step 7: Create and return JSON response
     let json = create_json_response(
@@ -278,15 +340,38 @@ fn execute_prefetch_logic(
     Ok(json)
 }
 
-#[post("/get/prefetch?<locate>", format = "json", data = "<query_data>")]
+#[post(
+    "/get/prefetch?<locate>&<q>&<sort>&<seed>",
+    format = "json",
+    data = "<query_data>"
+)]
 pub async fn prefetch(
     auth_guard: GuardResult<GuardShare>,
     query_data: Option<Json<Expression>>,
     locate: Option<String>,
+    q: Option<String>,
+    sort: Option<SortKey>,
+    seed: Option<u64>,
 ) -> AppResult<Json<PrefetchReturn>> {
     let auth_guard = auth_guard?;
-    // Combine album filter (if any) with the client‑supplied query.
     let mut combined_expression_option = query_data.map(|wrapper| wrapper.into_inner());
+
+    // Textual query in the SEARCH.md syntax, combined with the JSON body if both are given
+    if let Some(query_text) = q {
+        let text_expression = query_text.parse::<Expression>().map_err(|error| AppError {
+            status: Status::BadRequest,
+            error: error.context("Failed to parse query"),
+        })?;
+        combined_expression_option = Some(match combined_expression_option {
+            Some(json_expression) => Expression::And(vec![json_expression, text_expression]),
+            None => text_expression,
+        });
+    }
+
+    // Users only see what they own or have been granted
+    let username_option = auth_guard.claims.get_username().map(str::to_string);
+
+    // Combine album filter (if any) with the client‑supplied query.
     let resolved_share_option = auth_guard.claims.get_share();
 
     if let Some(resolved_share) = &resolved_share_option {
@@ -302,7 +387,14 @@ pub async fn prefetch(
 
     // Execute on blocking thread
     let job_handle = tokio::task::spawn_blocking(move || {
-        execute_prefetch_logic(combined_expression_option, locate, resolved_share_option)
+        execute_prefetch_logic(
+            combined_expression_option,
+            locate,
+            resolved_share_option,
+            username_option,
+            sort.unwrap_or_default(),
+            seed.unwrap_or_default(),
+        )
     })
     .await??;
 
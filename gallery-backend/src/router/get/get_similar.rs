@@ -0,0 +1,106 @@
+use crate::public::constant::DEFAULT_SIMILAR_THRESHOLD;
+use crate::public::db::similar::SIMILAR;
+use crate::public::db::tree::{TREE, VERSION_COUNT_TIMESTAMP};
+use crate::public::structure::response::reduced_data::ReducedData;
+use crate::router::claims::claims_timestamp::ClaimsTimestamp;
+use crate::router::fairing::guard_auth::GuardAuth;
+use crate::router::get::get_prefetch::insert_data_into_tree_snapshot;
+use crate::router::{AppResult, GuardResult};
+use crate::tasks::BATCH_COORDINATOR;
+use crate::tasks::batcher::update_similar::UpdateSimilarTask;
+use anyhow::{Context, anyhow};
+use arrayvec::ArrayString;
+use rocket::serde::json::Json;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+
+/// Hashes farther apart than this are never considered duplicates
+const MAX_SIMILAR_THRESHOLD: u32 = 32;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarCluster {
+    /// Index of the first item of this cluster inside the snapshot
+    pub start: usize,
+    pub hash_list: Vec<ArrayString<64>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarReturn {
+    pub timestamp: u128,
+    pub token: String,
+    pub data_length: usize,
+    pub clusters: Vec<SimilarCluster>,
+}
+
+#[get("/get/similar?<threshold>")]
+pub async fn get_similar(
+    auth: GuardResult<GuardAuth>,
+    threshold: Option<u32>,
+) -> AppResult<Json<SimilarReturn>> {
+    let auth = auth?;
+    let threshold = threshold.unwrap_or(DEFAULT_SIMILAR_THRESHOLD);
+    if threshold > MAX_SIMILAR_THRESHOLD {
+        return Err(anyhow!(
+            "Threshold {} exceeds the maximum of {}",
+            threshold,
+            MAX_SIMILAR_THRESHOLD
+        )
+        .into());
+    }
+
+    let is_stale = SIMILAR
+        .in_memory
+        .get(&threshold)
+        .is_none_or(|snapshot| snapshot.version != VERSION_COUNT_TIMESTAMP.load(Ordering::SeqCst));
+    if is_stale {
+        BATCH_COORDINATOR
+            .execute_batch_waiting(UpdateSimilarTask::new(threshold))
+            .await
+            .context("Failed to compute similar clusters")?;
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let clusters = SIMILAR
+            .in_memory
+            .get(&threshold)
+            .map(|snapshot| snapshot.clusters.clone())
+            .ok_or_else(|| anyhow!("Similar clusters for threshold {} not found", threshold))?;
+
+        // Users only see the part of each cluster they can read
+        let readable_hash_set_opt = auth
+            .claims
+            .get_username()
+            .map(|username| TREE.readable_hash_set(username));
+
+        // Flatten the clusters into one snapshot so that the frontend can address
+        // them by index, e.g. when trashing the extras through edit_flags.
+        let mut similar_clusters = Vec::with_capacity(clusters.len());
+        let mut reduced_data_vector: Vec<ReducedData> = Vec::new();
+        for mut cluster in clusters {
+            if let Some(readable_hash_set) = &readable_hash_set_opt {
+                cluster.retain(|reduced| readable_hash_set.contains(&reduced.hash));
+                if cluster.len() < 2 {
+                    continue;
+                }
+            }
+            similar_clusters.push(SimilarCluster {
+                start: reduced_data_vector.len(),
+                hash_list: cluster.iter().map(|reduced| reduced.hash).collect(),
+            });
+            reduced_data_vector.extend(cluster);
+        }
+
+        let (timestamp, data_length) = insert_data_into_tree_snapshot(reduced_data_vector, false)?;
+        let token = ClaimsTimestamp::new(None, timestamp).encode();
+
+        Ok(Json(SimilarReturn {
+            timestamp,
+            token,
+            data_length,
+            clusters: similar_clusters,
+        }))
+    })
+    .await?
+}
@@ -1,17 +1,23 @@
 use rocket::Route;
 
+pub mod get_album_zip;
+pub mod get_backup;
 pub mod get_data;
 pub mod get_export;
 pub mod get_img;
 pub mod get_list;
 pub mod get_page;
 pub mod get_prefetch;
+pub mod get_similar;
 
 pub fn generate_get_routes() -> Vec<Route> {
     routes![
         get_list::get_config,
         get_list::get_tags,
         get_list::get_albums,
+        get_list::get_users,
+        get_list::get_sessions,
+        get_list::get_api_tokens,
         get_data::get_data,
         get_data::get_rows,
         get_data::get_scroll_bar,
@@ -44,6 +50,9 @@ pub fn generate_get_routes() -> Vec<Route> {
         get_page::service_worker,
         get_page::sregister_sw,
         get_prefetch::prefetch,
-        get_export::get_export
+        get_export::get_export,
+        get_backup::get_backup,
+        get_album_zip::get_album_zip,
+        get_similar::get_similar
     ]
 }
@@ -4,12 +4,26 @@ pub mod fairing;
 pub mod get;
 pub mod post;
 pub mod put;
+pub mod spool;
 use rocket::http::{ContentType, Status};
 use rocket::request::Request;
 use rocket::response::{self, Responder, Response};
 use serde_json::json;
+use std::fmt;
 use std::io::Cursor;
 
+/// Raised when a user touches something outside their scope; answered with 403
+#[derive(Debug)]
+pub struct AccessDenied(pub String);
+
+impl fmt::Display for AccessDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Access denied: {}", self.0)
+    }
+}
+
+impl std::error::Error for AccessDenied {}
+
 #[derive(Debug)]
 pub struct AppError {
     pub status: Status,
@@ -42,10 +56,13 @@ where
     anyhow::Error: From<E>,
 {
     fn from(err: E) -> Self {
-        AppError {
-            status: Status::InternalServerError,
-            error: anyhow::Error::from(err),
-        }
+        let error = anyhow::Error::from(err);
+        let status = if error.downcast_ref::<AccessDenied>().is_some() {
+            Status::Forbidden
+        } else {
+            Status::InternalServerError
+        };
+        AppError { status, error }
     }
 }
 
@@ -73,9 +90,12 @@ where
     anyhow::Error: From<E>,
 {
     fn from(err: E) -> Self {
-        GuardError {
-            status: Status::Unauthorized,
-            error: anyhow::Error::from(err),
-        }
+        let error = anyhow::Error::from(err);
+        let status = if error.downcast_ref::<AccessDenied>().is_some() {
+            Status::Forbidden
+        } else {
+            Status::Unauthorized
+        };
+        GuardError { status, error }
     }
 }
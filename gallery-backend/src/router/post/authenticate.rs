@@ -1,33 +1,218 @@
+use anyhow::Context;
 use rand::{TryRngCore, rngs::OsRng};
+use rocket::http::{Cookie, CookieJar, SameSite, Status};
 use rocket::post;
 use rocket::serde::json::Json;
-use std::sync::LazyLock;
+use serde::Deserialize;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::sync::{LazyLock, Mutex};
 
+use crate::operations::password::{verify_admin_password, verify_password};
 use crate::public::config::PRIVATE_CONFIG;
-use crate::router::AppResult;
+use crate::public::db::tree::TREE;
+use crate::public::structure::session::{REFRESH_TOKEN_LIFETIME_SECS, Session};
 use crate::router::claims::claims::Claims;
+use crate::router::fairing::guard_auth::GuardAuth;
+use crate::router::fairing::throttle::LOGIN_THROTTLE;
+use crate::router::{AppError, AppResult, GuardResult};
+
+const SECRET_KEY_PATH: &str = "./db/jwt_secret";
+
+/// Only sent to /post/refresh and /post/logout, and never readable from scripts
+const REFRESH_TOKEN_COOKIE: &str = "refresh_token";
 
 pub static JSON_WEB_TOKEN_SECRET_KEY: LazyLock<Vec<u8>> =
     LazyLock::new(|| match PRIVATE_CONFIG.auth_key.as_ref() {
         Some(auth_key) => auth_key.as_bytes().to_vec(),
-        _ => {
-            let mut secret = vec![0u8; 32];
-            OsRng
-                .try_fill_bytes(&mut secret)
-                .expect("Failed to generate random secret key");
-            secret
-        }
+        _ => load_or_create_secret_key().expect("Failed to load the JWT secret key"),
     });
 
-#[post("/post/authenticate", data = "<password>")]
-pub async fn authenticate(password: Json<String>) -> AppResult<Json<String>> {
-    let input_password = password.into_inner();
-    if input_password == PRIVATE_CONFIG.password {
-        let token = Claims::new_admin().encode();
-        Ok(Json(token))
-    } else {
-        Err(anyhow::anyhow!("Invalid password")
+/// Without AUTH_KEY a random key is kept on disk, so restarts do not sign everyone out
+fn load_or_create_secret_key() -> anyhow::Result<Vec<u8>> {
+    match fs::read(SECRET_KEY_PATH) {
+        Ok(secret) if secret.len() >= 32 => return Ok(secret),
+        Ok(_) => warn!("{} is too short, generating a new key", SECRET_KEY_PATH),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err).with_context(|| format!("Failed to read {}", SECRET_KEY_PATH)),
+    }
+
+    let mut secret = vec![0u8; 32];
+    OsRng
+        .try_fill_bytes(&mut secret)
+        .context("Failed to generate random secret key")?;
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(SECRET_KEY_PATH)
+        .and_then(|mut file| file.write_all(&secret))
+        .with_context(|| format!("Failed to write {}", SECRET_KEY_PATH))?;
+    info!("Generated a new JWT secret key at {}", SECRET_KEY_PATH);
+    Ok(secret)
+}
+
+fn set_refresh_cookie(cookies: &CookieJar<'_>, refresh_token: String) {
+    cookies.add(
+        Cookie::build((REFRESH_TOKEN_COOKIE, refresh_token))
+            .path("/post")
+            .http_only(true)
+            .same_site(SameSite::Strict)
+            .max_age(rocket::time::Duration::seconds(
+                REFRESH_TOKEN_LIFETIME_SECS as i64,
+            )),
+    );
+}
+
+/// A bare password signs in as the admin; a username and password as a user
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Credentials {
+    Admin(String),
+    User { username: String, password: String },
+}
+
+#[post("/post/authenticate", data = "<credentials>")]
+pub async fn authenticate(
+    client_ip: Option<IpAddr>,
+    cookies: &CookieJar<'_>,
+    credentials: Json<Credentials>,
+) -> AppResult<Json<String>> {
+    let Some(client_ip) = client_ip else {
+        return Err(AppError {
+            status: Status::BadRequest,
+            error: anyhow::anyhow!("Client address is unknown"),
+        });
+    };
+    let throttle_target = match &*credentials {
+        Credentials::Admin(_) => "admin".to_string(),
+        Credentials::User { username, .. } => format!("user:{}", username),
+    };
+    if let Err(retry_after) = LOGIN_THROTTLE.reserve(client_ip, &throttle_target) {
+        return Err(AppError {
+            status: Status::TooManyRequests,
+            error: anyhow::anyhow!(
+                "Too many failed attempts, try again in {} seconds",
+                retry_after.as_secs().max(1)
+            ),
+        });
+    }
+
+    // Password hashing is deliberately slow, so keep it off the async workers
+    let session_opt =
+        tokio::task::spawn_blocking(move || -> anyhow::Result<Option<(Session, String)>> {
+            let username_opt = match credentials.into_inner() {
+                Credentials::Admin(input_password) => {
+                    if !verify_admin_password(&input_password)? {
+                        return Ok(None);
+                    }
+                    None
+                }
+                Credentials::User { username, password } => match TREE.read_user(&username)? {
+                    Some(user) if verify_password(&password, &user.password_hash) => {
+                        Some(user.username)
+                    }
+                    _ => return Ok(None),
+                },
+            };
+
+            // Expired sessions are dropped whenever someone signs in
+            TREE.retain_sessions(|session| !session.is_expired())?;
+            let (session, refresh_token) = Session::new(username_opt, Some(client_ip.to_string()));
+            TREE.write_session(&session)?;
+            Ok(Some((session, refresh_token)))
+        })
+        .await??;
+
+    match session_opt {
+        Some((session, refresh_token)) => {
+            LOGIN_THROTTLE.record_success(client_ip, &throttle_target);
+            set_refresh_cookie(cookies, refresh_token);
+            Ok(Json(Claims::new_session(&session).encode()))
+        }
+        None => Err(anyhow::anyhow!("Invalid password")
             .context("Authentication failed")
-            .into())
+            .into()),
+    }
+}
+
+/// Serializes refreshes, so two requests with the same token cannot both rotate it
+static REFRESH_LOCK: Mutex<()> = Mutex::new(());
+
+/// Trade the refresh token cookie for a new access token; the refresh token is rotated each time,
+/// and the one it replaced is still accepted for `REFRESH_TOKEN_GRACE_SECS` without rotating again
+#[post("/post/refresh")]
+pub async fn refresh(cookies: &CookieJar<'_>) -> AppResult<Json<String>> {
+    let Some(refresh_token) = cookies
+        .get(REFRESH_TOKEN_COOKIE)
+        .map(|cookie| cookie.value().to_string())
+    else {
+        return Err(AppError {
+            status: Status::Unauthorized,
+            error: anyhow::anyhow!("Refresh token not found in cookies"),
+        });
+    };
+
+    let (session, refresh_token_opt) =
+        tokio::task::spawn_blocking(move || -> AppResult<(Session, Option<String>)> {
+            let _refresh_guard = REFRESH_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+            let unauthorized = |error| AppError {
+                status: Status::Unauthorized,
+                error,
+            };
+            let Some((jti, secret)) = Session::parse_refresh_token(&refresh_token) else {
+                return Err(unauthorized(anyhow::anyhow!("Malformed refresh token")));
+            };
+            let Some(mut session) = TREE.read_session(jti)? else {
+                return Err(unauthorized(anyhow::anyhow!(
+                    "Session {} has been revoked",
+                    jti
+                )));
+            };
+            let is_current = session.verify_refresh_secret(secret);
+            // Another tab may have rotated the token a moment ago; its new cookie is kept
+            if !is_current && !session.verify_previous_refresh_secret(secret) {
+                // A rotated-out refresh token coming back means it leaked, so end the session
+                warn!("Outdated refresh token for session {}, revoking it", jti);
+                TREE.remove_session(jti)?;
+                return Err(unauthorized(anyhow::anyhow!(
+                    "Refresh token is no longer valid"
+                )));
+            }
+            if session.is_expired() {
+                TREE.remove_session(jti)?;
+                return Err(unauthorized(anyhow::anyhow!("Session {} has expired", jti)));
+            }
+
+            if !is_current {
+                return Ok((session, None));
+            }
+            let refresh_token = session.rotate();
+            TREE.write_session(&session)?;
+            Ok((session, Some(refresh_token)))
+        })
+        .await??;
+
+    if let Some(refresh_token) = refresh_token_opt {
+        set_refresh_cookie(cookies, refresh_token);
+    }
+    Ok(Json(Claims::new_session(&session).encode()))
+}
+
+/// End the current session; both its access token and its refresh token stop working
+#[post("/post/logout")]
+pub async fn logout(auth: GuardResult<GuardAuth>, cookies: &CookieJar<'_>) -> AppResult<()> {
+    let auth = auth?;
+    if let Some(jti) = auth.claims.jti {
+        tokio::task::spawn_blocking(move || TREE.remove_session(&jti)).await??;
     }
+    cookies.remove(Cookie::build(REFRESH_TOKEN_COOKIE).path("/post"));
+    cookies.remove(Cookie::from("jwt"));
+    Ok(())
 }
@@ -12,7 +12,9 @@ use crate::operations::hash::generate_random_hash;
 use crate::operations::open_db::{open_data_table, open_tree_snapshot_table};
 use crate::process::transitor::index_to_abstract_data;
 use crate::public::db::tree_snapshot::read_tree_snapshot::MyCow;
+use crate::public::db::tree::TREE;
 use crate::public::structure::abstract_data::AbstractData;
+use crate::public::structure::expression::Expression;
 use crate::router::GuardResult;
 use crate::tasks::actor::album::AlbumSelfUpdateTask;
 
@@ -37,9 +39,10 @@ pub async fn create_empty_album(
     auth: GuardResult<GuardAuth>,
     read_only_mode: Result<GuardReadOnlyMode>,
 ) -> AppResult<String> {
-    let _ = auth?;
+    let auth = auth?;
     let _ = read_only_mode?;
-    let album_id = create_album_internal(None).await?;
+    let album_id = generate_random_hash();
+    create_album_internal(album_id, None, owner_of(&auth)).await?;
 
     Ok(album_id.to_string())
 }
@@ -50,27 +53,84 @@ pub async fn create_non_empty_album(
     read_only_mode: Result<GuardReadOnlyMode>,
     create_album: Json<CreateAlbum>,
 ) -> AppResult<String> {
-    let _ = auth?;
+    let auth = auth?;
     let _ = read_only_mode?;
     let create_album = create_album.into_inner();
-    let album_id = create_album_internal(create_album.title).await?;
-    create_album_elements(
-        album_id,
-        create_album.elements_index,
-        create_album.timestamp,
-    )
-    .await?;
+    let owner = owner_of(&auth);
+    // Elements are checked before the album is created so a refused request leaves nothing behind
+    let album_id = generate_random_hash();
+    let element_batch = tokio::task::spawn_blocking(move || -> Result<Vec<AbstractData>> {
+        let tree_snapshot = open_tree_snapshot_table(create_album.timestamp)?;
+        let data_table = open_data_table();
+        let element_batch: Vec<AbstractData> = create_album
+            .elements_index
+            .into_par_iter()
+            .map(|idx| index_edit_album_insert(&tree_snapshot, &data_table, idx, album_id))
+            .collect::<Result<_>>()?;
+        for abstract_data in &element_batch {
+            auth.claims.check_owner(abstract_data.object())?;
+        }
+        Ok(element_batch)
+    })
+    .await??;
+    create_album_internal(album_id, create_album.title, owner).await?;
+    create_album_elements(album_id, element_batch).await?;
 
     Ok(album_id.to_string())
 }
 
-async fn create_album_internal(title: Option<String>) -> Result<ArrayString<64>> {
-    let start_time = Instant::now();
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSmartAlbum {
+    pub title: Option<String>,
+    pub expression: Expression,
+}
+
+#[post("/post/create_smart_album", data = "<create_smart_album>")]
+pub async fn create_smart_album(
+    auth: GuardResult<GuardAuth>,
+    read_only_mode: Result<GuardReadOnlyMode>,
+    create_smart_album: Json<CreateSmartAlbum>,
+) -> AppResult<String> {
+    let auth = auth?;
+    let _ = read_only_mode?;
+    let create_smart_album = create_smart_album.into_inner();
 
+    // Store the expression first so the album is populated by its first tree update
     let album_id = generate_random_hash();
-    let album = Album::new(album_id, title);
+    tokio::task::spawn_blocking(move || {
+        TREE.write_smart_album(&album_id, Some(&create_smart_album.expression))
+    })
+    .await??;
+
+    let mut album = Album::new(album_id, create_smart_album.title).into_abstract_data();
+    album.object_mut().owner = owner_of(&auth);
     BATCH_COORDINATOR
-        .execute_batch_waiting(FlushTreeTask::insert(vec![album.into_abstract_data()]))
+        .execute_batch_waiting(FlushTreeTask::insert(vec![album]))
+        .await?;
+    BATCH_COORDINATOR
+        .execute_batch_waiting(UpdateTreeTask)
+        .await?;
+
+    Ok(album_id.to_string())
+}
+
+/// New albums belong to the user creating them, or to the admin's library
+fn owner_of(auth: &GuardAuth) -> Option<String> {
+    auth.claims.get_username().map(str::to_string)
+}
+
+async fn create_album_internal(
+    album_id: ArrayString<64>,
+    title: Option<String>,
+    owner: Option<String>,
+) -> Result<()> {
+    let start_time = Instant::now();
+
+    let mut album = Album::new(album_id, title).into_abstract_data();
+    album.object_mut().owner = owner;
+    BATCH_COORDINATOR
+        .execute_batch_waiting(FlushTreeTask::insert(vec![album]))
         .await?;
 
     BATCH_COORDINATOR
@@ -78,24 +138,13 @@ async fn create_album_internal(title: Option<String>) -> Result<ArrayString<64>>
         .await?;
 
     info!(duration = &*format!("{:?}", start_time.elapsed()); "Create album");
-    Ok(album_id)
+    Ok(())
 }
 
 async fn create_album_elements(
     album_id: ArrayString<64>,
-    elements_index: Vec<usize>,
-    timestamp: u128,
+    element_batch: Vec<AbstractData>,
 ) -> Result<()> {
-    let element_batch = tokio::task::spawn_blocking(move || -> Result<Vec<AbstractData>> {
-        let tree_snapshot = open_tree_snapshot_table(timestamp)?;
-        let data_table = open_data_table();
-        elements_index
-            .into_par_iter()
-            .map(|idx| index_edit_album_insert(&tree_snapshot, &data_table, idx, album_id))
-            .collect()
-    })
-    .await??;
-
     BATCH_COORDINATOR
         .execute_batch_waiting(FlushTreeTask::insert(element_batch))
         .await?;
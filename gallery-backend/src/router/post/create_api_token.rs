@@ -0,0 +1,54 @@
+use crate::public::db::tree::TREE;
+use crate::public::structure::api_token::{ApiScope, ApiToken};
+use crate::router::fairing::guard_auth::GuardAuth;
+use crate::router::{AccessDenied, AppError, AppResult, GuardResult};
+use anyhow::anyhow;
+use rocket::http::Status;
+use rocket::post;
+use rocket::serde::json::Json;
+use serde::Deserialize;
+use std::collections::HashSet;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiToken {
+    pub name: String,
+    pub scopes: HashSet<ApiScope>,
+}
+
+/// Create a token acting as the signed-in account; the returned token is only shown once
+#[post("/post/create_api_token", format = "json", data = "<create_api_token>")]
+pub async fn create_api_token(
+    auth: GuardResult<GuardAuth>,
+    create_api_token: Json<CreateApiToken>,
+) -> AppResult<Json<String>> {
+    let auth = auth?;
+    let create_api_token = create_api_token.into_inner();
+
+    // Tokens cannot mint further tokens
+    auth.require_session()?;
+    let username_opt = auth.claims.get_username().map(str::to_string);
+    if let Some(username) = &username_opt
+        && create_api_token.scopes.contains(&ApiScope::Admin)
+    {
+        return Err(AccessDenied(format!("{} cannot create admin API tokens", username)).into());
+    }
+    if create_api_token.scopes.is_empty() {
+        return Err(AppError {
+            status: Status::BadRequest,
+            error: anyhow!("An API token needs at least one scope"),
+        });
+    }
+
+    tokio::task::spawn_blocking(move || -> AppResult<Json<String>> {
+        let (api_token, token) =
+            ApiToken::new(create_api_token.name, username_opt, create_api_token.scopes);
+        TREE.write_api_token(&api_token)?;
+        info!(
+            "Created API token {} with scopes {:?}",
+            api_token.id, api_token.scopes
+        );
+        Ok(Json(token))
+    })
+    .await?
+}
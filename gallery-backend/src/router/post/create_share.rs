@@ -1,6 +1,8 @@
+use crate::operations::password::hash_share_password;
 use crate::public::db::tree::TREE;
 use crate::public::structure::abstract_data::AbstractData;
 use crate::public::structure::album::Share;
+use crate::router::claims::claims::Claims;
 use crate::router::AppResult;
 use crate::router::fairing::guard_auth::GuardAuth;
 use crate::router::fairing::guard_read_only_mode::GuardReadOnlyMode;
@@ -32,12 +34,14 @@ pub async fn create_share(
     read_only_mode: Result<GuardReadOnlyMode>,
     create_share: Json<CreateShare>,
 ) -> AppResult<String> {
-    let _ = auth?;
+    let auth = auth?;
     let _ = read_only_mode?;
     tokio::task::spawn_blocking(move || {
-        let create_share = create_share.into_inner();
+        let mut create_share = create_share.into_inner();
+        // Hash before opening the write transaction, hashing is slow
+        create_share.password = hash_share_password(create_share.password.take())?;
         let txn = TREE.in_disk.begin_write().unwrap();
-        match create_and_insert_share(&txn, create_share) {
+        match create_and_insert_share(&txn, create_share, &auth.claims) {
             Ok(link) => {
                 txn.commit().unwrap();
                 return Ok(link);
@@ -49,7 +53,11 @@ pub async fn create_share(
     .unwrap()
 }
 
-fn create_and_insert_share(txn: &WriteTransaction, create_share: CreateShare) -> AppResult<String> {
+fn create_and_insert_share(
+    txn: &WriteTransaction,
+    create_share: CreateShare,
+    claims: &Claims,
+) -> AppResult<String> {
     let mut data_table = txn.open_table(DATA_TABLE).unwrap();
 
     let album_opt = data_table
@@ -65,6 +73,7 @@ fn create_and_insert_share(txn: &WriteTransaction, create_share: CreateShare) ->
 
     match album_opt {
         Some(mut album) => {
+            claims.check_owner(&album.object)?;
             let link: String = rand::rng()
                 .sample_iter(&Alphanumeric)
                 .filter(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
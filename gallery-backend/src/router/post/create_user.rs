@@ -0,0 +1,77 @@
+use crate::operations::password::hash_password;
+use crate::public::db::tree::TREE;
+use crate::public::structure::user::User;
+use crate::router::fairing::guard_admin::GuardAdmin;
+use crate::router::fairing::guard_read_only_mode::GuardReadOnlyMode;
+use crate::router::{AppError, AppResult, GuardResult};
+use anyhow::{Result, anyhow};
+use rocket::http::Status;
+use rocket::post;
+use rocket::serde::json::Json;
+use serde::Deserialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateUser {
+    pub username: String,
+    pub password: String,
+}
+
+#[post("/post/create_user", format = "json", data = "<create_user>")]
+pub async fn create_user(
+    auth: GuardResult<GuardAdmin>,
+    read_only_mode: Result<GuardReadOnlyMode>,
+    create_user: Json<CreateUser>,
+) -> AppResult<()> {
+    let _ = auth?;
+    let _ = read_only_mode?;
+    let create_user = create_user.into_inner();
+    validate_username(&create_user.username)?;
+    validate_password(&create_user.password)?;
+
+    tokio::task::spawn_blocking(move || -> AppResult<()> {
+        if TREE.read_user(&create_user.username)?.is_some() {
+            return Err(AppError {
+                status: Status::Conflict,
+                error: anyhow!("User {} already exists", create_user.username),
+            });
+        }
+        let user = User {
+            username: create_user.username,
+            password_hash: hash_password(&create_user.password)?,
+            created_time: SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis(),
+        };
+        TREE.write_user(&user)?;
+        info!("Created user {}", user.username);
+        Ok(())
+    })
+    .await?
+}
+
+/// Usernames end up in tokens and URLs, so keep them to a plain character set
+pub fn validate_username(username: &str) -> AppResult<()> {
+    let is_valid = !username.is_empty()
+        && username.len() <= 64
+        && username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if is_valid {
+        Ok(())
+    } else {
+        Err(AppError {
+            status: Status::BadRequest,
+            error: anyhow!("Username must be 1 to 64 letters, digits, '-', '_' or '.'"),
+        })
+    }
+}
+
+pub fn validate_password(password: &str) -> AppResult<()> {
+    if password.is_empty() {
+        return Err(AppError {
+            status: Status::BadRequest,
+            error: anyhow!("Password must not be empty"),
+        });
+    }
+    Ok(())
+}
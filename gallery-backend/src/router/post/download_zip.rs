@@ -0,0 +1,60 @@
+use crate::operations::archive::{SidecarFormat, ZipDownload, stream_zip};
+use crate::operations::open_db::{open_data_table, open_tree_snapshot_table};
+use crate::operations::resolve_show_download_and_metadata;
+use crate::operations::transitor::{hash_to_abstract_data, index_to_hash};
+use crate::public::structure::abstract_data::AbstractData;
+use crate::router::fairing::guard_timestamp::GuardTimestamp;
+use crate::router::{AppError, AppResult, GuardResult};
+use anyhow::{Result, anyhow};
+use rocket::http::Status;
+use rocket::post;
+use rocket::response::stream::ByteStream;
+use rocket::serde::{Deserialize, json::Json};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadZipData {
+    index_array: Vec<usize>,
+    sidecar: Option<SidecarFormat>,
+}
+
+#[post(
+    "/post/download-zip?<timestamp>",
+    format = "json",
+    data = "<json_data>"
+)]
+pub async fn download_zip(
+    guard_timestamp: GuardResult<GuardTimestamp>,
+    timestamp: u128,
+    json_data: Json<DownloadZipData>,
+) -> AppResult<ZipDownload<ByteStream![Vec<u8>]>> {
+    let guard_timestamp = guard_timestamp?;
+    let (show_download, _) =
+        resolve_show_download_and_metadata(guard_timestamp.claims.resolved_share_opt);
+    if !show_download {
+        return Err(AppError {
+            status: Status::Forbidden,
+            error: anyhow!("Downloads are disabled for this share"),
+        });
+    }
+
+    let DownloadZipData {
+        index_array,
+        sidecar,
+    } = json_data.into_inner();
+    let data_list = tokio::task::spawn_blocking(move || -> Result<Vec<AbstractData>> {
+        let data_table = open_data_table();
+        let tree_snapshot = open_tree_snapshot_table(timestamp)?;
+        index_array
+            .into_iter()
+            .map(|index| {
+                let hash = index_to_hash(&tree_snapshot, index)?;
+                hash_to_abstract_data(&data_table, hash)
+            })
+            .collect()
+    })
+    .await??;
+
+    info!("Downloading {} items as ZIP", data_list.len());
+    Ok(stream_zip("urocissa", data_list, sidecar))
+}
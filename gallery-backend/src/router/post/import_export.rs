@@ -0,0 +1,45 @@
+use crate::operations::import_export::{ImportReport, merge_export};
+use crate::router::fairing::guard_admin::GuardAdmin;
+use crate::router::fairing::guard_read_only_mode::GuardReadOnlyMode;
+use crate::router::spool::spool_upload;
+use crate::router::{AppError, AppResult, GuardResult};
+use anyhow::Result;
+use rocket::data::{Data, Limits};
+use rocket::http::Status;
+use rocket::post;
+use rocket::serde::json::Json;
+use std::fs;
+
+/// Merge a file produced by `/get/get-export` into this instance.
+/// With `dry_run` the report lists the changes and conflicts without writing anything.
+#[post("/post/import-export?<dry_run>", data = "<data>")]
+pub async fn import_export(
+    auth: GuardResult<GuardAdmin>,
+    read_only_mode: Result<GuardReadOnlyMode>,
+    dry_run: Option<bool>,
+    limits: &Limits,
+    data: Data<'_>,
+) -> AppResult<Json<ImportReport>> {
+    let _ = auth?;
+    let dry_run = dry_run.unwrap_or(false);
+    if !dry_run {
+        let _ = read_only_mode?;
+    }
+
+    let temp_path = spool_upload(data, limits, "import").await?;
+    let import_result = merge_export(temp_path.clone(), dry_run).await;
+    let _ = fs::remove_file(&temp_path);
+    let report = import_result.map_err(|error| AppError {
+        status: Status::BadRequest,
+        error: error.context("Failed to import export"),
+    })?;
+
+    info!(
+        "Imported export: {} updated, {} albums created, {} missing, {} conflicts",
+        report.updated_count,
+        report.created_album_count,
+        report.missing.len(),
+        report.conflicts.len()
+    );
+    Ok(Json(report))
+}
@@ -1,15 +1,30 @@
 use rocket::Route;
 pub mod authenticate;
 pub mod create_album;
+pub mod create_api_token;
 pub mod create_share;
+pub mod create_user;
+pub mod download_zip;
+pub mod import_export;
 pub mod post_upload;
+pub mod restore;
+pub mod unlock_share;
 
 pub fn generate_post_routes() -> Vec<Route> {
     routes![
         authenticate::authenticate,
+        authenticate::refresh,
+        authenticate::logout,
         create_album::create_non_empty_album,
         create_album::create_empty_album,
+        create_album::create_smart_album,
         post_upload::upload,
-        create_share::create_share
+        restore::restore,
+        download_zip::download_zip,
+        import_export::import_export,
+        create_share::create_share,
+        create_user::create_user,
+        create_api_token::create_api_token,
+        unlock_share::unlock_share
     ]
 }
@@ -36,7 +36,7 @@ pub async fn upload(
     presigned_album_id_opt: Option<String>,
     form: Result<Form<UploadForm<'_>>, Errors<'_>>,
 ) -> AppResult<()> {
-    let _ = auth?;
+    let auth = auth?;
     let _ = read_only_mode?;
     let mut inner_form = match form {
         Ok(form) => form.into_inner(),
@@ -79,7 +79,12 @@ pub async fn upload(
             || VALID_VIDEO_EXTENSIONS.contains(&extension.as_str())
         {
             let final_path = save_file(file, filename, extension, last_modified_time).await?;
-            index_for_watch(PathBuf::from(final_path), presigned_album_id_opt).await?;
+            index_for_watch(
+                PathBuf::from(final_path),
+                presigned_album_id_opt,
+                auth.owner.clone(),
+            )
+            .await?;
         } else {
             error!("Invalid file type");
             return Err(anyhow::anyhow!("Invalid file type: {}", extension).into());
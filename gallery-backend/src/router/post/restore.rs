@@ -0,0 +1,49 @@
+use crate::operations::backup::restore_backup;
+use crate::router::fairing::guard_admin::GuardAdmin;
+use crate::router::fairing::guard_read_only_mode::GuardReadOnlyMode;
+use crate::router::spool::spool_upload;
+use crate::router::{AppError, AppResult, GuardResult};
+use crate::tasks::BATCH_COORDINATOR;
+use crate::tasks::batcher::update_tree::UpdateTreeTask;
+use anyhow::Result;
+use rocket::data::{Data, Limits};
+use rocket::http::Status;
+use rocket::post;
+use rocket::serde::json::Json;
+use std::fs::{self, File};
+use std::io::BufReader;
+
+/// Replace the database with a backup produced by `/get/backup`.
+/// Returns the number of restored entries; an invalid backup changes nothing.
+#[post("/post/restore", data = "<data>")]
+pub async fn restore(
+    auth: GuardResult<GuardAdmin>,
+    read_only_mode: Result<GuardReadOnlyMode>,
+    limits: &Limits,
+    data: Data<'_>,
+) -> AppResult<Json<usize>> {
+    let _ = auth?;
+    let _ = read_only_mode?;
+
+    // Spool the upload to disk first so a dropped connection cannot leave a half-restored table
+    let temp_path = spool_upload(data, limits, "backup").await?;
+
+    let restore_path = temp_path.clone();
+    let restore_result = tokio::task::spawn_blocking(move || -> Result<usize> {
+        let file = File::open(&restore_path)?;
+        restore_backup(BufReader::new(file))
+    })
+    .await?;
+    let _ = fs::remove_file(&temp_path);
+    let entry_count = restore_result.map_err(|error| AppError {
+        status: Status::BadRequest,
+        error: error.context("Failed to restore backup"),
+    })?;
+
+    BATCH_COORDINATOR
+        .execute_batch_waiting(UpdateTreeTask)
+        .await?;
+
+    info!("Restored {} entries from backup", entry_count);
+    Ok(Json(entry_count))
+}
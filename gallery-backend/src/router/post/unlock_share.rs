@@ -0,0 +1,140 @@
+use arrayvec::ArrayString;
+use redb::ReadableTable;
+use rocket::http::Status;
+use rocket::post;
+use rocket::serde::json::Json;
+use serde::Deserialize;
+use std::net::IpAddr;
+
+use crate::operations::password::{
+    hash_share_password, is_legacy_share_password, verify_share_password,
+};
+use crate::public::constant::redb::DATA_TABLE;
+use crate::public::db::tree::TREE;
+use crate::public::structure::abstract_data::AbstractData;
+use crate::public::structure::album::ResolvedShare;
+use crate::router::claims::claims::Claims;
+use crate::router::fairing::auth_utils::{ShareError, read_shared_album, validate_share_access};
+use crate::router::fairing::throttle::SHARE_PASSWORD_THROTTLE;
+use crate::router::{AppError, AppResult};
+use crate::tasks::BATCH_COORDINATOR;
+use crate::tasks::batcher::update_tree::UpdateTreeTask;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockShare {
+    pub album_id: ArrayString<64>,
+    pub share_id: ArrayString<64>,
+    pub password: String,
+}
+
+/// Exchange the share password for a short-lived share token, sent afterwards as
+/// the `x-share-token` header or the `shareToken` query parameter
+#[post("/post/share/unlock", data = "<unlock_share>")]
+pub async fn unlock_share(
+    client_ip: Option<IpAddr>,
+    unlock_share: Json<UnlockShare>,
+) -> AppResult<Json<String>> {
+    let Some(client_ip) = client_ip else {
+        return Err(AppError {
+            status: Status::BadRequest,
+            error: anyhow::anyhow!("Client address is unknown"),
+        });
+    };
+    let throttle_target = format!("{}/{}", unlock_share.album_id, unlock_share.share_id);
+    if let Err(retry_after) = SHARE_PASSWORD_THROTTLE.reserve(client_ip, &throttle_target) {
+        return Err(AppError {
+            status: Status::TooManyRequests,
+            error: anyhow::anyhow!(
+                "Too many failed attempts, try again in {} seconds",
+                retry_after.as_secs().max(1)
+            ),
+        });
+    }
+
+    let claims_opt = tokio::task::spawn_blocking(move || -> AppResult<Option<Claims>> {
+        let unlock_share = unlock_share.into_inner();
+        let (album, share) = read_shared_album(&unlock_share.album_id, &unlock_share.share_id)
+            .and_then(|(album, share)| {
+                validate_share_access(&share, true)?;
+                Ok((album, share))
+            })
+            .map_err(|err| {
+                let status = match err {
+                    ShareError::Expired => Status::Forbidden,
+                    _ => Status::NotFound,
+                };
+                AppError {
+                    status,
+                    error: anyhow::anyhow!("Share is not available: {:?}", err),
+                }
+            })?;
+
+        let is_match = match &share.password {
+            Some(stored_password) => verify_share_password(&unlock_share.password, stored_password),
+            None => true,
+        };
+        if is_match
+            && let Some(stored_password) = share
+                .password
+                .as_deref()
+                .filter(|stored_password| is_legacy_share_password(stored_password))
+            && let Err(err) = rehash_legacy_password(
+                &unlock_share.album_id,
+                &unlock_share.share_id,
+                stored_password,
+                &unlock_share.password,
+            )
+        {
+            error!("Failed to re-hash legacy share password: {:#}", err);
+        }
+        Ok(is_match.then(|| {
+            Claims::new_unlocked_share(ResolvedShare::new(
+                unlock_share.album_id,
+                album.metadata.title,
+                share,
+            ))
+        }))
+    })
+    .await??;
+
+    match claims_opt {
+        Some(claims) => {
+            SHARE_PASSWORD_THROTTLE.record_success(client_ip, &throttle_target);
+            Ok(Json(claims.encode()))
+        }
+        None => Err(AppError {
+            status: Status::Unauthorized,
+            error: anyhow::anyhow!("Invalid share password"),
+        }),
+    }
+}
+
+/// Replace a plaintext share password with its hash, unless the share was edited meanwhile
+fn rehash_legacy_password(
+    album_id: &str,
+    share_id: &str,
+    stored_password: &str,
+    password: &str,
+) -> anyhow::Result<()> {
+    let txn = TREE.in_disk.begin_write()?;
+    {
+        let mut data_table = txn.open_table(DATA_TABLE)?;
+        let album_opt = data_table
+            .get(album_id)?
+            .and_then(|guard| match guard.value() {
+                AbstractData::Album(album) => Some(album),
+                _ => None,
+            });
+        if let Some(mut album) = album_opt
+            && let Some(share) = album.metadata.share_list.get_mut(share_id)
+            && share.password.as_deref() == Some(stored_password)
+        {
+            share.password = hash_share_password(Some(password.to_string()))?;
+            data_table.insert(album_id, AbstractData::Album(album))?;
+        }
+    }
+    txn.commit()?;
+    BATCH_COORDINATOR.execute_batch_detached(UpdateTreeTask);
+    Ok(())
+}
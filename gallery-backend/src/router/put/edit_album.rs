@@ -3,10 +3,11 @@ use crate::process::transitor::index_to_abstract_data;
 use crate::public::constant::redb::DATA_TABLE;
 use crate::public::db::tree::TREE;
 use crate::public::structure::abstract_data::AbstractData;
+use crate::public::structure::expression::Expression;
 use crate::router::fairing::guard_auth::GuardAuth;
 use crate::router::fairing::guard_read_only_mode::GuardReadOnlyMode;
 use crate::router::fairing::guard_share::GuardShare;
-use crate::router::{AppResult, GuardResult};
+use crate::router::{AppError, AppResult, GuardResult};
 use crate::tasks::actor::album::AlbumSelfUpdateTask;
 use crate::tasks::batcher::flush_tree::FlushTreeTask;
 use crate::tasks::batcher::update_tree::UpdateTreeTask;
@@ -15,8 +16,10 @@ use anyhow::Result;
 use arrayvec::ArrayString;
 use futures::{StreamExt, TryStreamExt, stream};
 use redb::ReadableTable;
+use rocket::http::Status;
 use rocket::serde::{Deserialize, json::Json};
 use serde::Serialize;
+use std::collections::HashSet;
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EditAlbumsData {
@@ -32,7 +35,7 @@ pub async fn edit_album(
     read_only_mode: Result<GuardReadOnlyMode>,
     json_data: Json<EditAlbumsData>,
 ) -> AppResult<()> {
-    let _ = auth?;
+    let auth = auth?;
     let _ = read_only_mode?;
 
     // 在 blocking 執行緒產生所有要寫入的 payload 與受影響相簿
@@ -41,10 +44,22 @@ pub async fn edit_album(
             let tree_snapshot = open_tree_snapshot_table(json_data.timestamp)?;
             let data_table = open_data_table();
 
+            // Users can only file their own items into their own albums
+            for album_id in json_data
+                .add_albums_array
+                .iter()
+                .chain(json_data.remove_albums_array.iter())
+            {
+                if let Some(guard) = data_table.get(&**album_id)? {
+                    auth.claims.check_owner(guard.value().object())?;
+                }
+            }
+
             let mut to_flush = Vec::with_capacity(json_data.index_array.len());
             for &index in &json_data.index_array {
                 let mut abstract_data =
                     index_to_abstract_data(&tree_snapshot, &data_table, index)?;
+                auth.claims.check_owner(abstract_data.object())?;
                 if let Some(albums) = abstract_data.albums_mut() {
                     for album_id in &json_data.add_albums_array {
                         albums.insert(album_id.clone());
@@ -107,9 +122,9 @@ pub async fn set_album_cover(
     read_only_mode: Result<GuardReadOnlyMode>,
     set_album_cover: Json<SetAlbumCover>,
 ) -> AppResult<()> {
-    let _ = auth?;
+    let auth = auth?;
     let _ = read_only_mode?;
-    tokio::task::spawn_blocking(move || {
+    tokio::task::spawn_blocking(move || -> Result<()> {
         let set_album_cover_inner = set_album_cover.into_inner();
         let album_id = set_album_cover_inner.album_id;
         let cover_hash = set_album_cover_inner.cover_hash;
@@ -119,6 +134,7 @@ pub async fn set_album_cover(
             let mut data_table = txn.open_table(DATA_TABLE).unwrap();
 
             let album = data_table.get(&*album_id).unwrap().unwrap().value();
+            auth.claims.check_owner(album.object())?;
             let mut album = match album {
                 AbstractData::Album(album) => album,
                 _ => panic!("Expected Album but got different type"),
@@ -129,9 +145,9 @@ pub async fn set_album_cover(
             data_table.insert(&*album_id, AbstractData::Album(album)).unwrap();
         }
         txn.commit().unwrap();
+        Ok(())
     })
-    .await
-    .unwrap();
+    .await??;
     BATCH_COORDINATOR
         .execute_batch_waiting(UpdateTreeTask)
         .await
@@ -152,9 +168,9 @@ pub async fn set_album_title(
     read_only_mode: Result<GuardReadOnlyMode>,
     set_album_title: Json<SetAlbumTitle>,
 ) -> AppResult<()> {
-    let _ = auth?;
+    let auth = auth?;
     let _ = read_only_mode?;
-    tokio::task::spawn_blocking(move || {
+    tokio::task::spawn_blocking(move || -> Result<()> {
         let set_album_title_inner = set_album_title.into_inner();
         let album_id = set_album_title_inner.album_id;
 
@@ -163,6 +179,7 @@ pub async fn set_album_title(
             let mut data_table = txn.open_table(DATA_TABLE).unwrap();
 
             let album = data_table.get(&*album_id).unwrap().unwrap().value();
+            auth.claims.check_owner(album.object())?;
             let mut album = match album {
                 AbstractData::Album(album) => album,
                 _ => panic!("Expected Album but got different type"),
@@ -172,9 +189,9 @@ pub async fn set_album_title(
             data_table.insert(&*album_id, AbstractData::Album(album)).unwrap();
         }
         txn.commit().unwrap();
+        Ok(())
     })
-    .await
-    .unwrap();
+    .await??;
     BATCH_COORDINATOR
         .execute_batch_waiting(UpdateTreeTask)
         .await
@@ -182,3 +199,99 @@ pub async fn set_album_title(
 
     Ok(())
 }
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SetSmartAlbumExpression {
+    pub album_id: ArrayString<64>,
+    pub expression: Option<Expression>,
+}
+
+#[put("/put/set_smart_album_expression", data = "<set_smart_album_expression>")]
+pub async fn set_smart_album_expression(
+    auth: GuardResult<GuardAuth>,
+    read_only_mode: Result<GuardReadOnlyMode>,
+    set_smart_album_expression: Json<SetSmartAlbumExpression>,
+) -> AppResult<()> {
+    let auth = auth?;
+    let _ = read_only_mode?;
+    let set_smart_album_expression = set_smart_album_expression.into_inner();
+    let album_id = set_smart_album_expression.album_id;
+
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let data_table = open_data_table();
+        match data_table.get(&*album_id)?.map(|guard| guard.value()) {
+            Some(album @ AbstractData::Album(_)) => auth.claims.check_owner(album.object())?,
+            _ => return Err(anyhow::anyhow!("Album '{}' not found", album_id)),
+        }
+        TREE.write_smart_album(&album_id, set_smart_album_expression.expression.as_ref())
+    })
+    .await??;
+
+    BATCH_COORDINATOR
+        .execute_batch_waiting(UpdateTreeTask)
+        .await?;
+
+    // Refresh counts when the album stops being smart, since the tree update only
+    // refreshes albums that are still smart
+    INDEX_COORDINATOR
+        .execute_waiting(AlbumSelfUpdateTask::new(album_id))
+        .await??;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SetAlbumUsers {
+    pub album_id: ArrayString<64>,
+    pub granted_users: HashSet<String>,
+}
+
+/// Replace the accounts the album is granted to
+#[put("/put/set_album_users", data = "<set_album_users>")]
+pub async fn set_album_users(
+    auth: GuardResult<GuardAuth>,
+    read_only_mode: Result<GuardReadOnlyMode>,
+    set_album_users: Json<SetAlbumUsers>,
+) -> AppResult<()> {
+    let auth = auth?;
+    let _ = read_only_mode?;
+    let set_album_users = set_album_users.into_inner();
+
+    tokio::task::spawn_blocking(move || -> AppResult<()> {
+        for username in &set_album_users.granted_users {
+            if TREE.read_user(username)?.is_none() {
+                return Err(AppError {
+                    status: Status::BadRequest,
+                    error: anyhow::anyhow!("User {} not found", username),
+                });
+            }
+        }
+
+        let txn = TREE.in_disk.begin_write()?;
+        {
+            let mut data_table = txn.open_table(DATA_TABLE)?;
+            let album_opt = data_table
+                .get(&*set_album_users.album_id)?
+                .map(|guard| guard.value());
+            let Some(AbstractData::Album(mut album)) = album_opt else {
+                return Err(AppError {
+                    status: Status::NotFound,
+                    error: anyhow::anyhow!("Album '{}' not found", set_album_users.album_id),
+                });
+            };
+            auth.claims.check_owner(&album.object)?;
+            album.metadata.granted_users = set_album_users.granted_users;
+            data_table.insert(&*set_album_users.album_id, AbstractData::Album(album))?;
+        }
+        txn.commit()?;
+        Ok(())
+    })
+    .await??;
+
+    BATCH_COORDINATOR
+        .execute_batch_waiting(UpdateTreeTask)
+        .await?;
+    Ok(())
+}
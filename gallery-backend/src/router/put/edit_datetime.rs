@@ -0,0 +1,152 @@
+use crate::operations::indexation::write_exif::write_date_time_original;
+use crate::operations::open_db::{open_data_table, open_tree_snapshot_table};
+use crate::operations::transitor::index_to_hash;
+use crate::public::config::LIBRARY_CONFIG;
+use crate::public::db::tree::TREE;
+use crate::public::error_data::handle_error;
+use crate::public::storage::{OBJECT_ROOT, STORAGE, fetch_imported};
+use crate::public::structure::abstract_data::AbstractData;
+use crate::router::fairing::guard_auth::GuardAuth;
+use crate::router::fairing::guard_read_only_mode::GuardReadOnlyMode;
+use crate::router::{AppError, AppResult, GuardResult};
+use crate::tasks::BATCH_COORDINATOR;
+use crate::tasks::actor::album::AlbumSelfUpdateTask;
+use crate::tasks::batcher::flush_tree::FlushTreeTask;
+use crate::tasks::batcher::update_tree::UpdateTreeTask;
+use anyhow::{Result, anyhow};
+use arrayvec::ArrayString;
+use rocket::http::Status;
+use rocket::serde::{Deserialize, json::Json};
+use std::collections::HashSet;
+use std::path::Path;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditDatetimeData {
+    index_array: Vec<usize>,
+    timestamp: u128,
+    /// Absolute date in milliseconds since UNIX epoch
+    #[serde(default)]
+    date: Option<u128>,
+    /// Shift in milliseconds applied to each item's current date
+    #[serde(default)]
+    delta: Option<i64>,
+    /// Also write the new date into the EXIF of a copy of each JPEG, served for downloads
+    #[serde(default)]
+    write_exif: bool,
+}
+
+/// Originals are content-addressed and never rewritten, so the new date is stored as an
+/// override. With `writeExif`, JPEGs also get a copy with the new `DateTimeOriginal` that
+/// downloads hand out instead of the original.
+#[put("/put/edit_datetime", format = "json", data = "<json_data>")]
+pub async fn edit_datetime(
+    auth: GuardResult<GuardAuth>,
+    read_only_mode: Result<GuardReadOnlyMode>,
+    json_data: Json<EditDatetimeData>,
+) -> AppResult<Json<()>> {
+    let auth = auth?;
+    let _ = read_only_mode?;
+
+    if json_data.date.is_some() == json_data.delta.is_some() {
+        return Err(AppError {
+            status: Status::BadRequest,
+            error: anyhow!("Exactly one of date and delta must be given"),
+        });
+    }
+
+    let affected_album_ids =
+        tokio::task::spawn_blocking(move || -> Result<HashSet<ArrayString<64>>> {
+            let data_table = open_data_table();
+            let tree_snapshot = open_tree_snapshot_table(json_data.timestamp)?;
+
+            let mut affected_album_ids = HashSet::new();
+            let mut data_to_flush: Vec<AbstractData> = Vec::new();
+
+            for &index in &json_data.index_array {
+                let hash = index_to_hash(&tree_snapshot, index)?;
+
+                let mut abstract_data = data_table
+                    .get(&*hash)?
+                    .ok_or_else(|| anyhow!("No data found for hash {}", hash))?
+                    .value();
+                auth.claims.check_owner(abstract_data.object())?;
+
+                let new_date = match (json_data.date, json_data.delta) {
+                    (Some(date), _) => date,
+                    (None, Some(delta)) => {
+                        let priority_list = LIBRARY_CONFIG.priority_list(&abstract_data);
+                        let current_date = abstract_data.compute_timestamp(&priority_list);
+                        current_date
+                            .checked_add_signed(i128::from(delta))
+                            .ok_or_else(|| {
+                                anyhow!("Shifted date of {} is before UNIX epoch", hash)
+                            })?
+                    }
+                    (None, None) => unreachable!(),
+                };
+                abstract_data.object_mut().date_override = Some(new_date);
+
+                // A copy written for an earlier date would now disagree with the override
+                if json_data.write_exif {
+                    write_exif_copy(&abstract_data, new_date);
+                } else if let Some(key) = TREE.read_exif_copy(&abstract_data.hash())? {
+                    TREE.write_exif_copy(&abstract_data.hash(), None)?;
+                    STORAGE.delete(&key)?;
+                }
+
+                // Album start and end times follow the dates of their members
+                if let Some(albums) = abstract_data.albums() {
+                    affected_album_ids.extend(albums.iter().copied());
+                }
+
+                data_to_flush.push(abstract_data);
+            }
+
+            // Flush data
+            if !data_to_flush.is_empty() {
+                BATCH_COORDINATOR.execute_batch_detached(FlushTreeTask::insert(data_to_flush));
+            }
+
+            Ok(affected_album_ids)
+        })
+        .await??;
+
+    // Wait for the in-memory Tree to be updated
+    BATCH_COORDINATOR
+        .execute_batch_waiting(UpdateTreeTask)
+        .await?;
+
+    // After memory update, trigger album self-update
+    for album_id in affected_album_ids {
+        BATCH_COORDINATOR.execute_detached(AlbumSelfUpdateTask::new(album_id));
+    }
+
+    Ok(Json(()))
+}
+
+/// Write a copy of a JPEG original with `new_date` as its DateTimeOriginal and record it.
+/// Failures are reported but do not undo the override, which is already authoritative.
+fn write_exif_copy(abstract_data: &AbstractData, new_date: u128) {
+    let AbstractData::Image(_) = abstract_data else {
+        return;
+    };
+    if !matches!(
+        abstract_data.ext().to_ascii_lowercase().as_str(),
+        "jpg" | "jpeg"
+    ) {
+        return;
+    }
+
+    let hash = abstract_data.hash();
+    let exif_copy_key = abstract_data.exif_copy_key();
+    let result = fetch_imported(abstract_data).and_then(|imported_path| {
+        let exif_copy_path = Path::new(OBJECT_ROOT).join(&exif_copy_key);
+        write_date_time_original(&imported_path, &exif_copy_path, new_date)?;
+        STORAGE.put(&exif_copy_key, &exif_copy_path)?;
+        TREE.write_exif_copy(&hash, Some(&exif_copy_key))
+    });
+    if let Err(err) = result {
+        handle_error(err.context(format!("Failed to write DateTimeOriginal of {}", hash)));
+    }
+}
@@ -29,7 +29,7 @@ pub async fn set_user_defined_description(
     read_only_mode: Result<GuardReadOnlyMode>,
     set_user_defined_description: Json<SetUserDefinedDescription>,
 ) -> AppResult<()> {
-    let _ = auth?;
+    let auth = auth?;
     let _ = read_only_mode?;
     tokio::task::spawn_blocking(move || -> Result<()> {
         let data_table = open_data_table();
@@ -39,6 +39,7 @@ pub async fn set_user_defined_description(
 
         if let Some(guard) = data_table.get(&*hash).unwrap() {
             let mut abstract_data = guard.value();
+            auth.claims.check_owner(abstract_data.object())?;
 
             match &mut abstract_data {
                 AbstractData::Image(img) => {
@@ -32,7 +32,7 @@ pub async fn edit_flags(
     read_only_mode: Result<GuardReadOnlyMode>,
     json_data: Json<EditFlagsData>,
 ) -> AppResult<Json<()>> {
-    let _ = auth?;
+    let auth = auth?;
     let _ = read_only_mode?;
 
     // Check if trashed flag is being modified
@@ -51,6 +51,7 @@ pub async fn edit_flags(
 
                 if let Some(guard) = data_table.get(&*hash).unwrap() {
                     let mut abstract_data = guard.value();
+                    auth.claims.check_owner(abstract_data.object())?;
 
                     // If trashed is involved, record the albums this data belongs to
                     if is_trashed_involved {
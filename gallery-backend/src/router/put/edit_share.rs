@@ -1,3 +1,4 @@
+use crate::operations::password::hash_share_password;
 use crate::public::db::tree::TREE;
 use crate::public::structure::abstract_data::AbstractData;
 use crate::public::structure::album::Share;
@@ -24,15 +25,19 @@ pub async fn edit_share(
     read_only_mode: Result<GuardReadOnlyMode>,
     json_data: Json<EditShare>,
 ) -> AppResult<()> {
-    let _ = auth?;
+    let auth = auth?;
     let _ = read_only_mode?;
-    tokio::task::spawn_blocking(move || {
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let EditShare {
+            album_id,
+            mut share,
+        } = json_data.into_inner();
         let txn = TREE.in_disk.begin_write().unwrap();
         {
             let mut data_table = txn.open_table(DATA_TABLE).unwrap();
 
             let album_opt = data_table
-                .get(json_data.album_id.as_str())
+                .get(album_id.as_str())
                 .unwrap()
                 .and_then(|guard| {
                     let abstract_data = guard.value();
@@ -43,18 +48,26 @@ pub async fn edit_share(
                 });
 
             if let Some(mut album) = album_opt {
-                album
-                    .metadata.share_list
-                    .insert(json_data.share.url, json_data.share.clone());
+                auth.claims.check_owner(&album.object)?;
+                // Clients send back the stored hash when the password is left unchanged
+                let stored_password = album
+                    .metadata
+                    .share_list
+                    .get(&share.url)
+                    .and_then(|stored_share| stored_share.password.as_ref());
+                if share.password.as_ref() != stored_password {
+                    share.password = hash_share_password(share.password.take())?;
+                }
+                album.metadata.share_list.insert(share.url, share);
                 data_table
-                    .insert(json_data.album_id.as_str(), AbstractData::Album(album))
+                    .insert(album_id.as_str(), AbstractData::Album(album))
                     .unwrap();
             }
         }
         txn.commit().unwrap();
+        Ok(())
     })
-    .await
-    .unwrap();
+    .await??;
     BATCH_COORDINATOR
         .execute_batch_waiting(UpdateTreeTask)
         .await
@@ -75,9 +88,9 @@ pub async fn delete_share(
     read_only_mode: Result<GuardReadOnlyMode>,
     json_data: Json<DeleteShare>,
 ) -> AppResult<()> {
-    let _ = auth?;
+    let auth = auth?;
     let _ = read_only_mode?;
-    tokio::task::spawn_blocking(move || {
+    tokio::task::spawn_blocking(move || -> Result<()> {
         let txn = TREE.in_disk.begin_write().unwrap();
         {
             let mut data_table = txn.open_table(DATA_TABLE).unwrap();
@@ -94,6 +107,7 @@ pub async fn delete_share(
                 });
 
             if let Some(mut album) = album_opt {
+                auth.claims.check_owner(&album.object)?;
                 album.metadata.share_list.remove(&json_data.share_id);
                 data_table
                     .insert(json_data.album_id.as_str(), AbstractData::Album(album))
@@ -101,9 +115,9 @@ pub async fn delete_share(
             }
         }
         txn.commit().unwrap();
+        Ok(())
     })
-    .await
-    .unwrap();
+    .await??;
     BATCH_COORDINATOR
         .execute_batch_waiting(UpdateTreeTask)
         .await
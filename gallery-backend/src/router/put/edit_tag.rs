@@ -1,5 +1,6 @@
 use crate::operations::open_db::{open_data_table, open_tree_snapshot_table};
 use crate::operations::transitor::index_to_hash;
+use crate::public::db::tree::TREE;
 use crate::public::db::tree::read_tags::TagInfo;
 use crate::public::db::tree_snapshot::TREE_SNAPSHOT;
 use crate::public::structure::abstract_data::AbstractData;
@@ -27,10 +28,11 @@ pub async fn edit_tag(
     read_only_mode: Result<GuardReadOnlyMode>,
     json_data: Json<EditTagsData>,
 ) -> AppResult<Json<Vec<TagInfo>>> {
-    let _ = auth?;
+    let auth = auth?;
     let _ = read_only_mode?;
+    let username_opt = auth.claims.get_username().map(str::to_string);
 
-    let vec_tags_info = tokio::task::spawn_blocking(move || -> Result<Vec<TagInfo>> {
+    let mut vec_tags_info = tokio::task::spawn_blocking(move || -> Result<Vec<TagInfo>> {
         let data_table = open_data_table();
         let tree_snapshot = open_tree_snapshot_table(json_data.timestamp)?;
 
@@ -41,6 +43,7 @@ pub async fn edit_tag(
 
             if let Some(guard) = data_table.get(&*hash).unwrap() {
                 let mut abstract_data = guard.value();
+                auth.claims.check_owner(abstract_data.object())?;
 
                 // Apply tag additions and removals (only regular tags)
                 let tags = abstract_data.tag_mut();
@@ -72,5 +75,14 @@ pub async fn edit_tag(
         .await
         .unwrap();
 
+    // Users get the tags of their own scope, read after the update
+    if let Some(username) = username_opt {
+        vec_tags_info = tokio::task::spawn_blocking(move || {
+            let scope = TREE.user_scope(&username);
+            TREE.read_tags(Some(&scope))
+        })
+        .await?;
+    }
+
     Ok(Json(vec_tags_info))
 }
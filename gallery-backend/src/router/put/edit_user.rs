@@ -0,0 +1,67 @@
+use crate::operations::password::hash_password;
+use crate::public::db::tree::TREE;
+use crate::router::fairing::guard_auth::GuardAuth;
+use crate::router::fairing::guard_read_only_mode::GuardReadOnlyMode;
+use crate::router::post::create_user::validate_password;
+use crate::router::{AccessDenied, AppError, AppResult, GuardResult};
+use anyhow::{Result, anyhow};
+use rocket::http::Status;
+use rocket::serde::{Deserialize, json::Json};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditUserPassword {
+    /// Required for the admin; users can only change their own password
+    #[serde(default)]
+    username: Option<String>,
+    password: String,
+}
+
+#[put("/put/edit_user_password", format = "json", data = "<json_data>")]
+pub async fn edit_user_password(
+    auth: GuardResult<GuardAuth>,
+    read_only_mode: Result<GuardReadOnlyMode>,
+    json_data: Json<EditUserPassword>,
+) -> AppResult<()> {
+    let auth = auth?;
+    auth.require_session()?;
+    let _ = read_only_mode?;
+    let json_data = json_data.into_inner();
+    validate_password(&json_data.password)?;
+
+    let username = match (auth.claims.get_username(), json_data.username) {
+        (Some(own_username), Some(username)) if own_username != username => {
+            return Err(AccessDenied(format!(
+                "{} cannot change the password of {}",
+                own_username, username
+            ))
+            .into());
+        }
+        (Some(own_username), _) => own_username.to_string(),
+        (None, Some(username)) => username,
+        (None, None) => {
+            return Err(AppError {
+                status: Status::BadRequest,
+                error: anyhow!("The admin password is set in the configuration"),
+            });
+        }
+    };
+
+    let current_jti = auth.claims.jti.clone().unwrap_or_default();
+    tokio::task::spawn_blocking(move || -> AppResult<()> {
+        let Some(mut user) = TREE.read_user(&username)? else {
+            return Err(AppError {
+                status: Status::NotFound,
+                error: anyhow!("User {} not found", username),
+            });
+        };
+        user.password_hash = hash_password(&json_data.password)?;
+        TREE.write_user(&user)?;
+        // Sign the account out everywhere else
+        TREE.retain_sessions(|session| {
+            session.username.as_ref() != Some(&username) || session.jti == current_jti
+        })?;
+        Ok(())
+    })
+    .await?
+}
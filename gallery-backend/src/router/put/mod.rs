@@ -1,25 +1,33 @@
 use rocket::Route;
 
 pub mod edit_album;
+pub mod edit_datetime;
 pub mod edit_description;
 pub mod edit_flags;
 pub mod edit_share;
 pub mod edit_tag;
+pub mod edit_user;
 pub mod random;
 pub mod regenerate_thumbnail;
 pub mod reindex;
+pub mod rescan;
 pub fn generate_put_routes() -> Vec<Route> {
     routes![
         edit_album::edit_album,
         edit_album::set_album_cover,
         edit_album::set_album_title,
+        edit_album::set_smart_album_expression,
+        edit_album::set_album_users,
+        edit_datetime::edit_datetime,
         edit_description::set_user_defined_description,
         edit_flags::edit_flags,
         edit_share::edit_share,
         edit_share::delete_share,
         edit_tag::edit_tag,
+        edit_user::edit_user_password,
         random::generate_random_data,
         regenerate_thumbnail::regenerate_thumbnail_with_frame,
         reindex::reindex,
+        rescan::rescan,
     ]
 }
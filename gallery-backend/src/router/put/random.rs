@@ -1,4 +1,4 @@
-use crate::router::fairing::guard_auth::GuardAuth;
+use crate::router::fairing::guard_admin::GuardAdmin;
 use crate::router::fairing::guard_read_only_mode::GuardReadOnlyMode;
 use crate::router::{AppResult, GuardResult};
 use crate::tasks::BATCH_COORDINATOR;
@@ -10,7 +10,7 @@ use anyhow::Result;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 #[get("/put/generate_random_data?<number>")]
 pub async fn generate_random_data(
-    auth: GuardResult<GuardAuth>,
+    auth: GuardResult<GuardAdmin>,
     read_only_mode: Result<GuardReadOnlyMode>,
     number: usize,
 ) -> AppResult<()> {
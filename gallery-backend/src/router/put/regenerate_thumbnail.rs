@@ -1,6 +1,7 @@
 use crate::operations::indexation::generate_dynamic_image::generate_dynamic_image;
 use crate::operations::indexation::generate_image_hash::{generate_phash, generate_thumbhash};
 use crate::operations::open_db::open_data_table;
+use crate::public::storage::{OBJECT_ROOT, STORAGE};
 use crate::public::structure::abstract_data::AbstractData;
 use crate::router::{AppResult, GuardResult};
 use crate::tasks::batcher::flush_tree::FlushTreeTask;
@@ -14,6 +15,7 @@ use anyhow::anyhow;
 use arrayvec::ArrayString;
 use rocket::form::{Errors, Form};
 use rocket::fs::TempFile;
+use std::path::Path;
 
 #[derive(FromForm, Debug)]
 pub struct RegenerateThumbnailForm<'r> {
@@ -32,7 +34,7 @@ pub async fn regenerate_thumbnail_with_frame(
     read_only_mode: Result<GuardReadOnlyMode>,
     form: Result<Form<RegenerateThumbnailForm<'_>>, Errors<'_>>,
 ) -> AppResult<()> {
-    let _ = auth?;
+    let auth = auth?;
     let _ = read_only_mode?;
     let mut inner_form = match form {
         Ok(form) => form.into_inner(),
@@ -53,7 +55,23 @@ pub async fn regenerate_thumbnail_with_frame(
     let hash = ArrayString::<64>::from(&inner_form.hash)
         .map_err(|_| anyhow!("Invalid hash length or format"))?;
 
-    let file_path = format!("./object/compressed/{}/{}.jpg", &hash[0..2], hash.as_str());
+    // Check ownership before the frame overwrites the current thumbnail
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let data_table = open_data_table();
+        let access_guard = data_table
+            .get(&*hash)
+            .context("Failed to fetch DB record")?
+            .ok_or_else(|| anyhow!("Hash not found"))?;
+        auth.claims.check_owner(access_guard.value().object())
+    })
+    .await??;
+
+    let file_path = format!(
+        "{}/compressed/{}/{}.jpg",
+        OBJECT_ROOT,
+        &hash[0..2],
+        hash.as_str()
+    );
 
     inner_form
         .frame
@@ -69,6 +87,7 @@ pub async fn regenerate_thumbnail_with_frame(
             .ok_or_else(|| anyhow!("Hash not found"))?;
 
         let mut abstract_data = access_guard.value();
+        STORAGE.put(&abstract_data.thumbnail_key(), Path::new(&file_path))?;
 
         let dyn_img =
             generate_dynamic_image(&abstract_data).context("Failed to decode DynamicImage")?;
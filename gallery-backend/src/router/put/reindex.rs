@@ -33,10 +33,10 @@ pub async fn reindex(
     read_only_mode: Result<GuardReadOnlyMode>,
     json_data: Json<RegenerateData>,
 ) -> AppResult<Status> {
-    let _ = auth?;
+    let auth = auth?;
     let _ = read_only_mode?;
     let json_data = json_data.into_inner();
-    tokio::task::spawn_blocking(move || {
+    tokio::task::spawn_blocking(move || -> Result<()> {
         let data_table = open_data_table();
         let reduced_data_vec = TREE_SNAPSHOT
             .read_tree_snapshot(&json_data.timestamp)
@@ -46,6 +46,14 @@ pub async fn reindex(
             .par_iter()
             .map(|index| reduced_data_vec.get_hash(*index).unwrap())
             .collect();
+        // Check every item up front so a user's request is either done fully or not at all
+        if auth.claims.get_username().is_some() {
+            for hash in &hash_vec {
+                if let Some(guard) = data_table.get(&**hash)? {
+                    auth.claims.check_owner(guard.value().object())?;
+                }
+            }
+        }
         let total_batches = (hash_vec.len() + PROCESS_BATCH_NUMBER - 1) / PROCESS_BATCH_NUMBER;
 
         for (i, batch) in hash_vec.chunks(PROCESS_BATCH_NUMBER).enumerate() {
@@ -90,9 +98,9 @@ pub async fn reindex(
                 .collect();
             BATCH_COORDINATOR.execute_batch_detached(FlushTreeTask::insert(data_list));
         }
+        Ok(())
     })
-    .await
-    .unwrap();
+    .await??;
     BATCH_COORDINATOR.execute_batch_detached(UpdateTreeTask);
     Ok(Status::Ok)
 }
@@ -0,0 +1,19 @@
+use crate::router::fairing::guard_admin::GuardAdmin;
+use crate::router::fairing::guard_read_only_mode::GuardReadOnlyMode;
+use crate::router::{AppResult, GuardResult};
+use crate::tasks::BATCH_COORDINATOR;
+use crate::tasks::batcher::reconcile::ReconcileTask;
+use anyhow::Result;
+use rocket::serde::json::Json;
+
+/// Start a reconcile scan of every sync path in the background
+#[put("/put/rescan")]
+pub async fn rescan(
+    auth: GuardResult<GuardAdmin>,
+    read_only_mode: Result<GuardReadOnlyMode>,
+) -> AppResult<Json<()>> {
+    let _ = auth?;
+    let _ = read_only_mode?;
+    BATCH_COORDINATOR.execute_batch_detached(ReconcileTask);
+    Ok(Json(()))
+}
@@ -0,0 +1,32 @@
+use crate::router::{AppError, AppResult};
+use anyhow::anyhow;
+use rocket::data::{Data, Limits, ToByteUnit};
+use rocket::http::Status;
+use std::fs;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Write an uploaded body to a temporary file, capped by the "file" limit.
+/// The caller removes the file once it is done with it.
+pub async fn spool_upload(data: Data<'_>, limits: &Limits, name: &str) -> AppResult<PathBuf> {
+    let temp_path = std::env::temp_dir().join(format!("urocissa-{}-{}", name, Uuid::new_v4()));
+    let limit = limits.get("file").unwrap_or(10.gibibytes());
+    let capped_file = data.open(limit).into_file(&temp_path).await;
+    let is_complete = match capped_file {
+        Ok(capped_file) => capped_file.is_complete(),
+        Err(err) => {
+            let _ = fs::remove_file(&temp_path);
+            return Err(anyhow::Error::from(err)
+                .context(format!("Failed to receive {}", name))
+                .into());
+        }
+    };
+    if !is_complete {
+        let _ = fs::remove_file(&temp_path);
+        return Err(AppError {
+            status: Status::PayloadTooLarge,
+            error: anyhow!("Upload exceeds the limit of {}", limit),
+        });
+    }
+    Ok(temp_path)
+}
@@ -1,4 +1,4 @@
-use crate::public::constant::redb::DATA_TABLE;
+use crate::public::constant::redb::{DATA_TABLE, FOLDER_ALBUM_TABLE};
 use crate::public::db::tree::TREE;
 use crate::public::error_data::handle_error;
 use crate::public::structure::abstract_data::AbstractData;
@@ -88,6 +88,10 @@ pub fn album_task(album_id: ArrayString<64>) -> Result<()> {
                     }
                     data_table.insert(&*hash, abstract_data).unwrap();
                 });
+
+                // A folder whose album was deleted gets a new one on its next import
+                let mut folder_album_table = txn.open_table(FOLDER_ALBUM_TABLE)?;
+                folder_album_table.retain(|_, folder_album_id| folder_album_id != &*album_id)?;
             }
         }
     }
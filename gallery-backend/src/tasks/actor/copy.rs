@@ -6,8 +6,10 @@ use std::sync::LazyLock;
 use tokio::sync::Semaphore;
 use tokio::task::spawn_blocking;
 
+use crate::operations::utils::sync_filter::sync_path_config;
 use crate::process::io::copy_with_retry;
 use crate::public::error_data::handle_error;
+use crate::public::storage::STORAGE;
 use crate::public::structure::abstract_data::AbstractData;
 
 static COPY_LIMIT: LazyLock<Semaphore> = LazyLock::new(|| Semaphore::const_new(1));
@@ -36,8 +38,17 @@ impl Task for CopyTask {
     }
 }
 
-fn copy_task(abstract_data: AbstractData) -> Result<AbstractData> {
+fn copy_task(mut abstract_data: AbstractData) -> Result<AbstractData> {
     let source_path = abstract_data.source_path();
+
+    // Originals under a reference-only sync path are served from where they are
+    if sync_path_config(&source_path).is_some_and(|config| config.reference_only) {
+        if let Some(alias) = abstract_data.alias_mut() {
+            alias[0].reference = true;
+        }
+        return Ok(abstract_data);
+    }
+
     let dest_path = abstract_data.imported_path();
 
     if let Some(parent) = dest_path.parent() {
@@ -52,5 +63,8 @@ fn copy_task(abstract_data: AbstractData) -> Result<AbstractData> {
         )
     })?; // If it fails three times, it goes into the Err branch
 
+    // Indexing works on the local copy; remote storage receives the original right away
+    STORAGE.put(&abstract_data.imported_key(), &dest_path)?;
+
     Ok(abstract_data)
 }
@@ -16,6 +16,8 @@ pub struct DeduplicateTask {
     pub path: PathBuf,
     pub hash: ArrayString<64>,
     pub presigned_album_id_opt: Option<ArrayString<64>>,
+    /// Owner of the file if it is new to the database
+    pub owner_opt: Option<String>,
 }
 
 impl DeduplicateTask {
@@ -23,17 +25,29 @@ impl DeduplicateTask {
         path: PathBuf,
         hash: ArrayString<64>,
         presigned_album_id_opt: Option<ArrayString<64>>,
+        owner_opt: Option<String>,
     ) -> Self {
         Self {
             path,
             hash,
             presigned_album_id_opt,
+            owner_opt,
         }
     }
 }
 
+/// What to do with a hashed file
+pub enum Deduplicated {
+    /// New to the database; keep indexing it
+    New(Box<AbstractData>),
+    /// Already indexed; the existing record has been updated
+    Existing,
+    /// Already indexed under another account; the existing record is left untouched
+    NotOwned,
+}
+
 impl Task for DeduplicateTask {
-    type Output = Result<Option<AbstractData>>;
+    type Output = Result<Deduplicated>;
 
     fn run(self) -> impl Future<Output = Self::Output> + Send {
         async move {
@@ -46,7 +60,10 @@ impl Task for DeduplicateTask {
     }
 }
 
-fn deduplicate_task(task: DeduplicateTask) -> Result<Option<AbstractData>> {
+/// A file belongs to whoever indexed it first. Finding it again only updates the existing record
+/// when the uploader owns it, or is the admin or the watcher (both have no owner); a user
+/// uploading someone else's file is refused, so the other record is neither changed nor revealed.
+fn deduplicate_task(task: DeduplicateTask) -> Result<Deduplicated> {
     let mut abstract_data = AbstractData::new(&task.path, task.hash)?;
 
     let data_table = open_data_table();
@@ -54,9 +71,23 @@ fn deduplicate_task(task: DeduplicateTask) -> Result<Option<AbstractData>> {
 
     if let Some(guard) = data_table.get(&*task.hash).unwrap() {
         let mut data_exist = guard.value();
+        if task.owner_opt.is_some() && data_exist.object().owner != task.owner_opt {
+            warn!(
+                "{:?} is already in the database under another account",
+                task.path
+            );
+            return Ok(Deduplicated::NotOwned);
+        }
         if let Some(alias_mut) = abstract_data.alias_mut() {
-            let file_modify = mem::take(&mut alias_mut[0]);
+            let mut file_modify = mem::take(&mut alias_mut[0]);
             if let Some(exist_alias) = data_exist.alias_mut() {
+                // A referenced original found again, at its old path or after moving, stays the reference
+                file_modify.reference = exist_alias.iter().any(|exist_file_modify| {
+                    exist_file_modify.reference
+                        && (exist_file_modify.file == file_modify.file || exist_file_modify.missing)
+                });
+                // Seen again at a known path (e.g. after a rename or rescan): refresh that entry
+                exist_alias.retain(|exist_file_modify| exist_file_modify.file != file_modify.file);
                 exist_alias.push(file_modify);
             }
         }
@@ -67,13 +98,14 @@ fn deduplicate_task(task: DeduplicateTask) -> Result<Option<AbstractData>> {
         }
         BATCH_COORDINATOR.execute_batch_detached(FlushTreeTask::insert(vec![data_exist]));
         warn!("File already exists in the database:\n{:#?}", abstract_data);
-        Ok(None)
+        Ok(Deduplicated::Existing)
     } else {
+        abstract_data.object_mut().owner = task.owner_opt;
         if let Some(album_id) = task.presigned_album_id_opt {
             if let Some(albums) = abstract_data.albums_mut() {
                 albums.insert(album_id);
             }
         }
-        Ok(Some(abstract_data))
+        Ok(Deduplicated::New(Box::new(abstract_data)))
     }
 }
@@ -55,5 +55,6 @@ fn remove_task(timestamp: u128) -> Result<()> {
     );
 
     write_txn.commit().unwrap();
+    TREE_SNAPSHOT.undated.remove(&timestamp);
     Ok(())
 }
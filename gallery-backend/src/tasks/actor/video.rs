@@ -27,7 +27,8 @@ impl VideoTask {
 }
 
 impl Task for VideoTask {
-    type Output = Result<()>;
+    /// The record as stored, which is an image again for GIFs that turned out to be static
+    type Output = Result<AbstractData>;
 
     fn run(self) -> impl Future<Output = Self::Output> + Send {
         async move {
@@ -40,7 +41,7 @@ impl Task for VideoTask {
     }
 }
 
-pub fn video_task(mut abstract_data: AbstractData) -> Result<()> {
+pub fn video_task(mut abstract_data: AbstractData) -> Result<AbstractData> {
     let hash = abstract_data.hash();
     match generate_compressed_video(&mut abstract_data) {
         Ok(_) => {
@@ -54,5 +55,5 @@ pub fn video_task(mut abstract_data: AbstractData) -> Result<()> {
             hash
         ))?,
     }
-    Ok(())
+    Ok(abstract_data)
 }
@@ -1,9 +1,14 @@
+use std::collections::HashSet;
+
 use mini_executor::BatchTask;
+use tokio::task::spawn_blocking;
 
 use crate::{
     public::{
-        constant::redb::DATA_TABLE,
+        constant::redb::{DATA_TABLE, EXIF_COPY_TABLE, SMART_ALBUM_TABLE},
         db::tree::TREE,
+        error_data::handle_error,
+        storage::STORAGE,
         structure::abstract_data::AbstractData,
     },
     tasks::{BATCH_COORDINATOR, batcher::update_tree::UpdateTreeTask},
@@ -39,7 +44,46 @@ impl BatchTask for FlushTreeTask {
                 all_insert_data.extend(task.insert_list);
                 all_remove_abstract_data.extend(task.remove_list);
             }
+            let stale_key_list = stale_object_keys(&all_insert_data, &all_remove_abstract_data);
             flush_tree_task(all_insert_data, all_remove_abstract_data);
+            if !stale_key_list.is_empty() {
+                spawn_blocking(move || delete_objects(stale_key_list))
+                    .await
+                    .expect("blocking task panicked");
+            }
+        }
+    }
+}
+
+/// Storage keys of the files that only the removed records used
+fn stale_object_keys(insert_list: &[AbstractData], remove_list: &[AbstractData]) -> Vec<String> {
+    let kept_hash_set: HashSet<_> = insert_list.iter().map(|data| data.hash()).collect();
+    let mut key_list = Vec::new();
+    for abstract_data in remove_list {
+        if matches!(abstract_data, AbstractData::Album(_))
+            || kept_hash_set.contains(&abstract_data.hash())
+        {
+            continue;
+        }
+        // Referenced originals belong to the user, not to the library
+        if abstract_data.reference_alias().is_none() {
+            key_list.push(abstract_data.imported_key());
+        }
+        let compressed_key = abstract_data.compressed_key();
+        let thumbnail_key = abstract_data.thumbnail_key();
+        if thumbnail_key != compressed_key {
+            key_list.push(thumbnail_key);
+        }
+        key_list.push(compressed_key);
+        key_list.push(abstract_data.exif_copy_key());
+    }
+    key_list
+}
+
+fn delete_objects(key_list: Vec<String>) {
+    for key in key_list {
+        if let Err(e) = STORAGE.delete(&key) {
+            handle_error(e.context(format!("Failed to delete object {}", key)));
         }
     }
 }
@@ -48,6 +92,8 @@ fn flush_tree_task(insert_list: Vec<AbstractData>, remove_list: Vec<AbstractData
     let write_txn = TREE.in_disk.begin_write().unwrap();
     {
         let mut data_table = write_txn.open_table(DATA_TABLE).unwrap();
+        let mut smart_album_table = write_txn.open_table(SMART_ALBUM_TABLE).unwrap();
+        let mut exif_copy_table = write_txn.open_table(EXIF_COPY_TABLE).unwrap();
 
         insert_list
             .iter()
@@ -60,6 +106,11 @@ fn flush_tree_task(insert_list: Vec<AbstractData>, remove_list: Vec<AbstractData
             .for_each(|abstract_data| {
                 let hash = abstract_data.hash();
                 data_table.remove(&*hash).unwrap();
+                exif_copy_table.remove(&*hash).unwrap();
+                // Drop the definition along with a deleted smart album
+                if let AbstractData::Album(_) = abstract_data {
+                    smart_album_table.remove(&*hash).unwrap();
+                }
             });
     };
     write_txn.commit().unwrap();
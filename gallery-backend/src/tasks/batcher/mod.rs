@@ -2,6 +2,10 @@ pub mod expire_check;
 pub mod flush_query_snapshot;
 pub mod flush_tree;
 pub mod flush_tree_snapshot;
+pub mod reconcile;
 pub mod start_watcher;
+pub mod update_alias;
 pub mod update_expire;
+pub mod update_similar;
 pub mod update_tree;
+pub mod verify_reference;
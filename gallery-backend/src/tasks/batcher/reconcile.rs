@@ -0,0 +1,127 @@
+use crate::operations::open_db::open_data_table;
+use crate::operations::utils::sync_filter::{is_excluded, should_index};
+use crate::public::config::PRIVATE_CONFIG;
+use crate::public::constant::runtime::CURRENT_NUM_THREADS;
+use crate::public::error_data::handle_error;
+use crate::public::tui::DASHBOARD;
+use crate::workflow::index_for_watch;
+use anyhow::{Context, Result};
+use futures::{StreamExt, stream};
+use log::info;
+use mini_executor::BatchTask;
+use path_clean::PathClean;
+use redb::ReadableTable;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    time::{Instant, UNIX_EPOCH},
+};
+use tokio::task::spawn_blocking;
+use walkdir::WalkDir;
+
+/// Walk every sync path and index files added or changed while nobody was watching.
+/// Requests arriving during a scan are merged into a single follow-up scan.
+pub struct ReconcileTask;
+
+impl BatchTask for ReconcileTask {
+    async fn batch_run(_: Vec<Self>) {
+        if let Err(e) = reconcile_task().await {
+            handle_error(e.context("Failed to run reconcile task"));
+        }
+    }
+}
+
+async fn reconcile_task() -> Result<()> {
+    let start_time = Instant::now();
+
+    let path_list = spawn_blocking(find_unindexed_files)
+        .await
+        .expect("blocking task panicked")?;
+
+    let total = path_list.len();
+    info!(duration = &*format!("{:?}", start_time.elapsed()); "Reconcile found {} new or changed files", total);
+    if total == 0 {
+        return Ok(());
+    }
+
+    DASHBOARD.start_scan(total as u64);
+    stream::iter(path_list)
+        .for_each_concurrent(*CURRENT_NUM_THREADS, |path| async move {
+            if let Err(e) = index_for_watch(path, None, None).await {
+                handle_error(e);
+            }
+            DASHBOARD.advance_scan();
+        })
+        .await;
+    DASHBOARD.finish_scan();
+
+    Ok(())
+}
+
+/// Media files under the sync paths whose path and mtime match no alias in the database
+fn find_unindexed_files() -> Result<Vec<PathBuf>> {
+    let mut known: HashMap<PathBuf, HashSet<u128>> = HashMap::new();
+    let data_table = open_data_table();
+    for entry in data_table.iter()? {
+        let (_, guard) = entry?;
+        let abstract_data = guard.value();
+        for file_modify in abstract_data.alias() {
+            if file_modify.missing {
+                continue;
+            }
+            known
+                .entry(PathBuf::from(&file_modify.file).clean())
+                .or_default()
+                .insert(file_modify.modified);
+        }
+    }
+
+    let mut path_list = Vec::new();
+    for sync_path in &PRIVATE_CONFIG.sync_path {
+        for dir_entry in WalkDir::new(sync_path)
+            .into_iter()
+            .filter_entry(|dir_entry| {
+                !is_excluded(dir_entry.path(), dir_entry.file_type().is_dir())
+            })
+            .filter_map(|dir_entry| dir_entry.ok())
+            .filter(|dir_entry| dir_entry.file_type().is_file())
+        {
+            let path = dir_entry.into_path().clean();
+            if !should_index(&path) {
+                continue;
+            }
+
+            // An unreadable file should not abort the whole scan
+            let modified = match modified_millis(&path) {
+                Ok(modified) => modified,
+                Err(e) => {
+                    handle_error(e);
+                    continue;
+                }
+            };
+
+            let is_known = known
+                .get(&path)
+                .is_some_and(|modified_set| modified_set.contains(&modified));
+            if !is_known {
+                path_list.push(path);
+            }
+        }
+    }
+
+    Ok(path_list)
+}
+
+fn modified_millis(path: &Path) -> Result<u128> {
+    let modified = path
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .context(format!("Failed to read modification time of {:?}", path))?
+        .duration_since(UNIX_EPOCH)
+        .context(format!(
+            "Modification time is before UNIX_EPOCH: {:?}",
+            path
+        ))?
+        .as_millis();
+    Ok(modified)
+}
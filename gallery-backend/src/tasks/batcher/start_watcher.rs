@@ -1,14 +1,22 @@
+use crate::operations::utils::sync_filter::{
+    is_excluded, is_included, should_index, sync_path_config,
+};
 use crate::public::constant::runtime::INDEX_RUNTIME;
-use crate::public::constant::{VALID_IMAGE_EXTENSIONS, VALID_VIDEO_EXTENSIONS};
+use crate::public::tui::DASHBOARD;
+use crate::tasks::BATCH_COORDINATOR;
+use crate::tasks::batcher::reconcile::ReconcileTask;
+use crate::tasks::batcher::update_alias::UpdateAliasTask;
 use crate::{
     public::config::PRIVATE_CONFIG, public::error_data::handle_error, workflow::index_for_watch,
 };
 use anyhow::Result;
 use log::info;
 use mini_executor::BatchTask;
-use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Config, Event, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
 use std::{
     collections::{HashMap, HashSet},
+    fs,
     path::{Path, PathBuf},
     sync::{
         LazyLock, Mutex,
@@ -21,13 +29,24 @@ use walkdir::WalkDir;
 
 static IS_WATCHING: AtomicBool = AtomicBool::new(false);
 
-static WATCHER_HANDLE: LazyLock<Mutex<Option<RecommendedWatcher>>> =
-    LazyLock::new(|| Mutex::new(None));
+static WATCHER_HANDLE: LazyLock<Mutex<Vec<Box<dyn Watcher + Send>>>> =
+    LazyLock::new(|| Mutex::new(Vec::new()));
+
+/// The last time any event arrived from each sync path
+static LAST_EVENT: LazyLock<Mutex<HashMap<PathBuf, Instant>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
 
 /// The last trigger time for each path
 static DEBOUNCE_POOL: LazyLock<Mutex<HashMap<PathBuf, Instant>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
 
+/// Sync paths that could not be watched at startup, retried by the health check
+static UNWATCHED: LazyLock<Mutex<HashSet<PathBuf>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// Paths moved away, waiting to see whether a matching rename event follows
+static REMOVE_POOL: LazyLock<Mutex<HashMap<PathBuf, Instant>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
 pub struct StartWatcherTask;
 
 impl BatchTask for StartWatcherTask {
@@ -46,29 +65,135 @@ fn start_watcher_task() -> Result<()> {
         return Ok(());
     }
 
-    // Build the watcher.
-    let mut watcher = new_watcher()?;
+    // Paths without a poll interval share one event-based watcher; polled paths
+    // each get their own because the interval is per watcher.
+    let mut recommended_watcher = new_watcher()?;
+    let mut watcher_list: Vec<Box<dyn Watcher + Send>> = Vec::new();
     for path in &PRIVATE_CONFIG.sync_path {
-        watcher
-            .watch(path, RecursiveMode::Recursive)
-            .map_err(|e| anyhow::anyhow!("Failed to watch path {:?}: {}", path, e))?;
-        info!("Watching path {:?}", path);
+        LAST_EVENT
+            .lock()
+            .unwrap()
+            .insert(path.clone(), Instant::now());
+
+        // One unreachable mount should not stop the others; the health check retries it
+        if let Err(e) = watch_path(path, &mut recommended_watcher, &mut watcher_list) {
+            UNWATCHED.lock().unwrap().insert(path.clone());
+            handle_error(e.context(format!("Failed to watch path {:?}", path)));
+        }
     }
+    watcher_list.push(Box::new(recommended_watcher));
+
+    // Store them globally to keep them alive.
+    *WATCHER_HANDLE.lock().unwrap() = watcher_list;
+    Ok(())
+}
 
-    // Store it globally to keep it alive.
-    *WATCHER_HANDLE.lock().unwrap() = Some(watcher);
+/// Watch `path` with `recommended_watcher`, or with a new poll watcher added to `watcher_list`
+/// if the path has a poll interval
+fn watch_path(
+    path: &Path,
+    recommended_watcher: &mut RecommendedWatcher,
+    watcher_list: &mut Vec<Box<dyn Watcher + Send>>,
+) -> Result<()> {
+    match sync_path_config(path).and_then(|config| config.poll_interval_secs) {
+        Some(secs) => {
+            let mut watcher = new_poll_watcher(Duration::from_secs(secs))?;
+            watcher.watch(path, RecursiveMode::Recursive)?;
+            watcher_list.push(Box::new(watcher));
+            info!("Polling path {:?} every {}s", path, secs);
+        }
+        None => {
+            recommended_watcher.watch(path, RecursiveMode::Recursive)?;
+            info!("Watching path {:?}", path);
+        }
+    }
     Ok(())
 }
 
-fn is_valid_media_file(path: &Path) -> bool {
-    path.extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| ext.to_lowercase())
-        .map(|ext| {
-            VALID_IMAGE_EXTENSIONS.contains(&ext.as_str())
-                || VALID_VIDEO_EXTENSIONS.contains(&ext.as_str())
-        })
-        .unwrap_or(false)
+/// Attach a watcher to a sync path that could not be watched at startup, then rescan to pick up
+/// whatever changed while it was unwatched
+fn reattach_watcher(path: &Path) -> Result<()> {
+    let mut recommended_watcher = new_watcher()?;
+    let mut watcher_list: Vec<Box<dyn Watcher + Send>> = Vec::new();
+    watch_path(path, &mut recommended_watcher, &mut watcher_list)?;
+    watcher_list.push(Box::new(recommended_watcher));
+
+    WATCHER_HANDLE.lock().unwrap().extend(watcher_list);
+    UNWATCHED.lock().unwrap().remove(path);
+    BATCH_COORDINATOR.execute_batch_detached(ReconcileTask);
+    Ok(())
+}
+
+/// Warn on the dashboard and through `handle_error` about sync paths that are unreachable
+/// or have been silent for longer than their `maxSilenceHours`, and start watching paths
+/// that have become reachable since startup
+pub fn check_watcher_health() {
+    let last_event_map = LAST_EVENT.lock().unwrap().clone();
+    for path in &PRIVATE_CONFIG.sync_path {
+        let key = path.to_string_lossy();
+
+        let is_unwatched = UNWATCHED.lock().unwrap().contains(path);
+        let warning = if let Err(e) = fs::read_dir(path) {
+            Some(format!("Sync path {:?} is unreachable: {}", path, e))
+        } else if is_unwatched && let Err(e) = reattach_watcher(path) {
+            Some(format!("Sync path {:?} could not be watched: {}", path, e))
+        } else if let Some(max_silence_hours) =
+            sync_path_config(path).and_then(|config| config.max_silence_hours)
+            && let Some(last_event) = last_event_map.get(path)
+            && last_event.elapsed() > Duration::from_secs(max_silence_hours * 60 * 60)
+        {
+            Some(format!(
+                "Sync path {:?} has produced no events for over {} hours",
+                path, max_silence_hours
+            ))
+        } else {
+            None
+        };
+
+        match warning {
+            // Only report a problem once, not on every check
+            Some(warning) => {
+                if DASHBOARD.set_warning(&key, &warning) {
+                    handle_error(anyhow::anyhow!(warning));
+                }
+            }
+            None => DASHBOARD.clear_warning(&key),
+        }
+    }
+}
+
+fn record_event(path_list: &[PathBuf]) {
+    let now = Instant::now();
+    let mut last_event_map = LAST_EVENT.lock().unwrap();
+    for sync_path in &PRIVATE_CONFIG.sync_path {
+        if path_list.iter().any(|path| path.starts_with(sync_path)) {
+            last_event_map.insert(sync_path.clone(), now);
+        }
+    }
+}
+
+/// The path itself if it is a media file, or every media file below it if it is a directory
+pub fn collect_media_files(path: &Path) -> Vec<PathBuf> {
+    if path.is_file() {
+        if is_included(path) {
+            vec![path.to_path_buf()]
+        } else {
+            Vec::new()
+        }
+    } else if path.is_dir() {
+        WalkDir::new(path)
+            .into_iter()
+            .filter_entry(|dir_entry| {
+                !is_excluded(dir_entry.path(), dir_entry.file_type().is_dir())
+            })
+            .filter_map(|dir_entry| dir_entry.ok())
+            .filter(|dir_entry| dir_entry.file_type().is_file())
+            .map(|dir_entry| dir_entry.into_path())
+            .filter(|path| is_included(path))
+            .collect()
+    } else {
+        Vec::new()
+    }
 }
 
 /// Push the path into the debounce pool: if there is no later event for the same path within 1 second, trigger indexing
@@ -97,39 +222,102 @@ fn submit_to_debounce_pool(path: PathBuf) {
             }
         };
 
-        if should_run && is_valid_media_file(&path) {
+        // Size and dimension thresholds are only meaningful once the file is fully written
+        if should_run && should_index(&path) {
             // Really need to do indexing
-            if let Err(e) = index_for_watch(path, None).await {
+            if let Err(e) = index_for_watch(path, None, None).await {
                 handle_error(e);
             }
         }
     });
 }
 
+/// Backends report the source of a rename separately before the paired event, so only
+/// treat it as removed if no rename claims it within 1 second
+fn submit_to_remove_pool(path: PathBuf) {
+    let now = Instant::now();
+    REMOVE_POOL.lock().unwrap().insert(path.clone(), now);
+
+    INDEX_RUNTIME.spawn(async move {
+        sleep(Duration::from_secs(1)).await;
+
+        let should_run = {
+            let mut pool = REMOVE_POOL.lock().unwrap();
+            match pool.get(&path).copied() {
+                Some(last) if last == now => {
+                    pool.remove(&path);
+                    true
+                }
+                _ => false,
+            }
+        };
+
+        if should_run {
+            BATCH_COORDINATOR.execute_batch_detached(UpdateAliasTask::remove(path));
+        }
+    });
+}
+
 fn new_watcher() -> Result<RecommendedWatcher> {
-    notify::recommended_watcher(move |result: Result<Event, notify::Error>| match result {
+    notify::recommended_watcher(handle_event)
+        .map_err(|e| anyhow::anyhow!("Failed to create watcher: {}", e))
+}
+
+fn new_poll_watcher(poll_interval: Duration) -> Result<PollWatcher> {
+    PollWatcher::new(
+        handle_event,
+        Config::default().with_poll_interval(poll_interval),
+    )
+    .map_err(|e| anyhow::anyhow!("Failed to create poll watcher: {}", e))
+}
+
+fn handle_event(result: Result<Event, notify::Error>) {
+    match result {
         Ok(event) => {
+            record_event(&event.paths);
             match event.kind {
-                EventKind::Create(_) => {
-                    let mut path_list: HashSet<PathBuf> = HashSet::new();
+                EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                    let path_list: HashSet<PathBuf> = event
+                        .paths
+                        .iter()
+                        .flat_map(|path| collect_media_files(path))
+                        .collect();
 
+                    for path in path_list {
+                        submit_to_debounce_pool(path);
+                    }
+                }
+
+                // A rename inside the watched tree: move the aliases along with the file
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+                    let mut paths = event.paths.into_iter();
+                    if let (Some(from), Some(to)) = (paths.next(), paths.next()) {
+                        REMOVE_POOL.lock().unwrap().remove(&from);
+                        BATCH_COORDINATOR.execute_batch_detached(UpdateAliasTask::rename(from, to));
+                    }
+                }
+
+                EventKind::Remove(_) => {
                     for path in event.paths {
-                        if path.is_file() {
-                            path_list.insert(path);
-                        } else if path.is_dir() {
-                            WalkDir::new(&path)
-                                .into_iter()
-                                .filter_map(|dir_entry| dir_entry.ok())
-                                .filter(|dir_entry| dir_entry.file_type().is_file())
-                                .for_each(|dir_entry| {
-                                    path_list.insert(dir_entry.into_path());
-                                });
-                        }
+                        BATCH_COORDINATOR.execute_batch_detached(UpdateAliasTask::remove(path));
                     }
+                }
 
-                    for path in path_list {
-                        if is_valid_media_file(&path) {
-                            submit_to_debounce_pool(path);
+                EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                    for path in event.paths {
+                        submit_to_remove_pool(path);
+                    }
+                }
+
+                // The backend could not tell which side of a rename this is
+                EventKind::Modify(ModifyKind::Name(_)) => {
+                    for path in event.paths {
+                        if path.exists() {
+                            for path in collect_media_files(&path) {
+                                submit_to_debounce_pool(path);
+                            }
+                        } else {
+                            submit_to_remove_pool(path);
                         }
                     }
                 }
@@ -144,7 +332,7 @@ fn new_watcher() -> Result<RecommendedWatcher> {
                     }
 
                     for path in path_list {
-                        if is_valid_media_file(&path) {
+                        if is_included(&path) {
                             submit_to_debounce_pool(path);
                         }
                     }
@@ -156,6 +344,5 @@ fn new_watcher() -> Result<RecommendedWatcher> {
         Err(err) => {
             handle_error(anyhow::anyhow!("Watch error: {:#?}", err));
         }
-    })
-    .map_err(|e| anyhow::anyhow!("Failed to create watcher: {}", e))
+    }
 }
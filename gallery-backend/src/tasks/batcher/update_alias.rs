@@ -0,0 +1,209 @@
+use crate::operations::open_db::open_data_table;
+use crate::operations::utils::path::canonical_path;
+use crate::operations::utils::sync_filter::sync_path_config;
+use crate::public::config::RemovePolicy;
+use crate::public::constant::runtime::INDEX_RUNTIME;
+use crate::public::error_data::handle_error;
+use crate::public::structure::abstract_data::AbstractData;
+use crate::tasks::BATCH_COORDINATOR;
+use crate::tasks::actor::album::AlbumSelfUpdateTask;
+use crate::tasks::batcher::flush_tree::FlushTreeTask;
+use crate::tasks::batcher::start_watcher::collect_media_files;
+use crate::tasks::batcher::update_tree::UpdateTreeTask;
+use crate::workflow::index_for_watch;
+use anyhow::Result;
+use arrayvec::ArrayString;
+use log::info;
+use mini_executor::BatchTask;
+use redb::ReadableTable;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tokio::task::spawn_blocking;
+
+/// A file or directory under a sync path that was removed or renamed
+pub enum AliasChange {
+    Remove(PathBuf),
+    Rename(PathBuf, PathBuf),
+}
+
+/// Keep `alias` entries in line with removals and renames seen by the watcher
+pub struct UpdateAliasTask {
+    pub change_list: Vec<AliasChange>,
+}
+
+impl UpdateAliasTask {
+    pub fn remove(path: PathBuf) -> Self {
+        Self {
+            change_list: vec![AliasChange::Remove(canonical_path(&path))],
+        }
+    }
+
+    pub fn rename(from: PathBuf, to: PathBuf) -> Self {
+        Self {
+            change_list: vec![AliasChange::Rename(
+                canonical_path(&from),
+                canonical_path(&to),
+            )],
+        }
+    }
+}
+
+impl BatchTask for UpdateAliasTask {
+    async fn batch_run(list: Vec<Self>) {
+        let change_list: Vec<AliasChange> =
+            list.into_iter().flat_map(|task| task.change_list).collect();
+        if let Err(e) = update_alias_task(change_list).await {
+            handle_error(e.context("Failed to run update alias task"));
+        }
+    }
+}
+
+async fn update_alias_task(change_list: Vec<AliasChange>) -> Result<()> {
+    let alias_update = spawn_blocking(move || apply_alias_changes(change_list))
+        .await
+        .expect("blocking task panicked")?;
+
+    if !alias_update.data_to_flush.is_empty() {
+        BATCH_COORDINATOR
+            .execute_batch_waiting(FlushTreeTask::insert(alias_update.data_to_flush))
+            .await?;
+    }
+    if !alias_update.data_to_remove.is_empty() {
+        BATCH_COORDINATOR
+            .execute_batch_waiting(FlushTreeTask::remove(alias_update.data_to_remove))
+            .await?;
+    }
+
+    // Album statistics are computed from the in-memory tree, so wait for it first
+    if !alias_update.affected_album_ids.is_empty() {
+        BATCH_COORDINATOR
+            .execute_batch_waiting(UpdateTreeTask)
+            .await?;
+        for album_id in alias_update.affected_album_ids {
+            BATCH_COORDINATOR.execute_detached(AlbumSelfUpdateTask::new(album_id));
+        }
+    }
+
+    // Renamed files that were never indexed, e.g. because of a new extension
+    for path in alias_update.unmatched_rename_list {
+        INDEX_RUNTIME.spawn(async move {
+            if let Err(e) = index_for_watch(path, None, None).await {
+                handle_error(e);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+struct AliasUpdate {
+    data_to_flush: Vec<AbstractData>,
+    data_to_remove: Vec<AbstractData>,
+    affected_album_ids: HashSet<ArrayString<64>>,
+    unmatched_rename_list: Vec<PathBuf>,
+}
+
+/// Apply every change in order to the aliases stored in the database
+fn apply_alias_changes(change_list: Vec<AliasChange>) -> Result<AliasUpdate> {
+    let data_table = open_data_table();
+
+    let mut data_to_flush = Vec::new();
+    let mut data_to_remove = Vec::new();
+    let mut affected_album_ids: HashSet<ArrayString<64>> = HashSet::new();
+    let mut matched_renames = vec![false; change_list.len()];
+
+    for entry in data_table.iter()? {
+        let (_, guard) = entry?;
+        let mut abstract_data = guard.value();
+        let Some(alias) = abstract_data.alias_mut() else {
+            continue;
+        };
+
+        let mut changed = false;
+        let mut removed_paths = Vec::new();
+        for (index, change) in change_list.iter().enumerate() {
+            for file_modify in alias.iter_mut() {
+                let file = PathBuf::from(&file_modify.file);
+                match change {
+                    AliasChange::Remove(path) if file.starts_with(path) && !file_modify.missing => {
+                        file_modify.missing = true;
+                        removed_paths.push(file);
+                        changed = true;
+                    }
+                    AliasChange::Rename(from, to) if file.starts_with(from) => {
+                        let new_file = match file.strip_prefix(from) {
+                            Ok(relative) if !relative.as_os_str().is_empty() => to.join(relative),
+                            _ => to.clone(),
+                        };
+                        file_modify.file = new_file.to_string_lossy().into_owned();
+                        file_modify.missing = false;
+                        matched_renames[index] = true;
+                        changed = true;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if !changed {
+            continue;
+        }
+
+        // A rename onto a path that was also indexed on its own leaves two entries for it
+        let mut seen_files = HashSet::new();
+        for index in (0..alias.len()).rev() {
+            if !seen_files.insert(alias[index].file.clone()) {
+                alias.remove(index);
+            }
+        }
+
+        // The policy only applies once no copy of the file is left in any sync path
+        let all_missing = alias.iter().all(|file_modify| file_modify.missing);
+        let policy = if all_missing {
+            removed_paths
+                .iter()
+                .find_map(|path| sync_path_config(path))
+                .map(|sync_path_config| sync_path_config.on_remove)
+                .unwrap_or_default()
+        } else {
+            RemovePolicy::Keep
+        };
+
+        if policy != RemovePolicy::Keep
+            && let Some(albums) = abstract_data.albums()
+        {
+            affected_album_ids.extend(albums.iter().cloned());
+        }
+        match policy {
+            RemovePolicy::Keep => data_to_flush.push(abstract_data),
+            RemovePolicy::Trash => {
+                info!(
+                    "Source of {} removed, moving to trash",
+                    abstract_data.hash()
+                );
+                abstract_data.set_trashed(true);
+                data_to_flush.push(abstract_data);
+            }
+            RemovePolicy::Delete => {
+                info!("Source of {} removed, deleting", abstract_data.hash());
+                data_to_remove.push(abstract_data);
+            }
+        }
+    }
+
+    let unmatched_rename_list = change_list
+        .iter()
+        .zip(matched_renames)
+        .filter_map(|(change, matched)| match change {
+            AliasChange::Rename(_, to) if !matched => Some(collect_media_files(to)),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+
+    Ok(AliasUpdate {
+        data_to_flush,
+        data_to_remove,
+        affected_album_ids,
+        unmatched_rename_list,
+    })
+}
@@ -0,0 +1,109 @@
+use crate::operations::utils::bk_tree::BkTree;
+use crate::public::db::similar::{SIMILAR, SimilarSnapshot};
+use crate::public::db::tree::{TREE, VERSION_COUNT_TIMESTAMP};
+use crate::public::structure::abstract_data::AbstractData;
+use crate::public::structure::response::reduced_data::ReducedData;
+use mini_executor::BatchTask;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+pub struct UpdateSimilarTask {
+    pub threshold: u32,
+}
+
+impl UpdateSimilarTask {
+    pub fn new(threshold: u32) -> Self {
+        Self { threshold }
+    }
+}
+
+impl BatchTask for UpdateSimilarTask {
+    async fn batch_run(list: Vec<Self>) {
+        let thresholds: HashSet<u32> = list.into_iter().map(|task| task.threshold).collect();
+        for threshold in thresholds {
+            update_similar_task(threshold);
+        }
+    }
+}
+
+fn update_similar_task(threshold: u32) {
+    let start_time = Instant::now();
+    let version = VERSION_COUNT_TIMESTAMP.load(Ordering::SeqCst);
+
+    let tree_guard = TREE.in_memory.read().unwrap();
+
+    // (position in tree, phash) of every image that can still be cleaned up
+    let candidates: Vec<(usize, &Vec<u8>)> = tree_guard
+        .iter()
+        .enumerate()
+        .filter_map(
+            |(position, database_timestamp)| match &database_timestamp.abstract_data {
+                AbstractData::Image(img) if !img.object.is_trashed => {
+                    img.metadata.phash.as_ref().map(|phash| (position, phash))
+                }
+                _ => None,
+            },
+        )
+        .collect();
+
+    let mut bk_tree = BkTree::new();
+    for (candidate_index, (_, phash)) in candidates.iter().enumerate() {
+        bk_tree.insert((*phash).clone(), candidate_index);
+    }
+
+    let neighbours: Vec<Vec<usize>> = candidates
+        .par_iter()
+        .map(|(_, phash)| {
+            bk_tree
+                .find(phash, threshold)
+                .into_iter()
+                .map(|(candidate_index, _)| candidate_index)
+                .collect()
+        })
+        .collect();
+
+    // Union-find so that chains of near-duplicates end up in the same cluster
+    let mut parent: Vec<usize> = (0..candidates.len()).collect();
+    fn find_root(parent: &mut [usize], mut index: usize) -> usize {
+        while parent[index] != index {
+            parent[index] = parent[parent[index]];
+            index = parent[index];
+        }
+        index
+    }
+    for (candidate_index, neighbour_list) in neighbours.iter().enumerate() {
+        for &neighbour in neighbour_list {
+            let root_a = find_root(&mut parent, candidate_index);
+            let root_b = find_root(&mut parent, neighbour);
+            if root_a != root_b {
+                parent[root_a.max(root_b)] = root_a.min(root_b);
+            }
+        }
+    }
+
+    // Keyed by root so clusters keep the tree order (newest first)
+    let mut cluster_map: BTreeMap<usize, Vec<ReducedData>> = BTreeMap::new();
+    for (candidate_index, (position, _)) in candidates.iter().enumerate() {
+        let root = find_root(&mut parent, candidate_index);
+        cluster_map
+            .entry(root)
+            .or_default()
+            .push((&tree_guard[*position]).into());
+    }
+    drop(tree_guard);
+
+    let clusters: Vec<Vec<ReducedData>> = cluster_map
+        .into_values()
+        .filter(|cluster| cluster.len() > 1)
+        .collect();
+    let cluster_count = clusters.len();
+
+    SIMILAR
+        .in_memory
+        .insert(threshold, SimilarSnapshot { version, clusters });
+
+    let duration = format!("{:?}", start_time.elapsed());
+    info!(duration = &*duration; "Similar clusters updated ({} clusters, threshold {}).", cluster_count, threshold);
+}
@@ -1,14 +1,22 @@
 use crate::operations::open_db::open_data_table;
 use crate::operations::utils::timestamp::get_current_timestamp_u64;
+use crate::public::config::LIBRARY_CONFIG;
+use crate::public::constant::redb::DATA_TABLE;
 use crate::public::db::tree::TREE;
+use crate::public::db::tree::phash_index::PhashIndex;
+use crate::public::db::tree::smart_album::SmartAlbumIndex;
+use crate::public::db::tree::text_index::TextIndex;
+use crate::public::error_data::handle_error;
+use crate::public::structure::abstract_data::AbstractData;
 use crate::public::structure::response::database_timestamp::DatabaseTimestamp;
 use crate::tasks::BATCH_COORDINATOR;
 use crate::tasks::batcher::update_expire::UpdateExpireTask;
+use arrayvec::ArrayString;
 use mini_executor::BatchTask;
 use rayon::iter::{ParallelBridge, ParallelIterator};
 use rayon::prelude::ParallelSliceMut;
 use redb::ReadableTable;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::LazyLock;
 use std::time::Instant;
 
@@ -43,8 +51,6 @@ fn update_tree_task() {
     let start_time = Instant::now();
     let data_table = open_data_table();
 
-    let priority_list = vec!["DateTimeOriginal", "filename", "modified", "scan_time"];
-
     let mut database_timestamp_vec: Vec<DatabaseTimestamp> = data_table
         .iter()
         .unwrap()
@@ -56,12 +62,32 @@ fn update_tree_task() {
             if let Some(exif_vec) = abstract_data.exif_vec_mut() {
                 exif_vec.retain(|k, _| ALLOWED_KEYS.contains(&k.as_str()));
             }
+            let priority_list = LIBRARY_CONFIG.priority_list(&abstract_data);
             DatabaseTimestamp::new(abstract_data, &priority_list)
         })
         .collect();
 
     database_timestamp_vec.par_sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
 
+    // Rebuild the indexes together with the tree so queries never see stale entries
+    let phash_index = PhashIndex::build(&database_timestamp_vec);
+    let text_index = TextIndex::build(&database_timestamp_vec);
+    *TREE.phash_index.write().unwrap() = phash_index;
+    *TREE.text_index.write().unwrap() = text_index;
+
+    // Smart album membership is evaluated after the indexes its expressions rely on
+    let smart_albums = TREE.read_smart_albums().unwrap_or_else(|err| {
+        handle_error(err.context("Failed to read smart albums"));
+        HashMap::new()
+    });
+    let smart_album_index = SmartAlbumIndex::build(smart_albums, &database_timestamp_vec);
+    let changed_album_vec =
+        smart_album_index.changed_since(&TREE.smart_album_index.read().unwrap());
+    *TREE.smart_album_index.write().unwrap() = smart_album_index;
+    if !changed_album_vec.is_empty() {
+        refresh_smart_albums(&mut database_timestamp_vec, &changed_album_vec);
+    }
+
     *TREE.in_memory.write().unwrap() = database_timestamp_vec;
 
     BATCH_COORDINATOR.execute_batch_detached(UpdateExpireTask);
@@ -70,3 +96,45 @@ fn update_tree_task() {
     let duration = format!("{:?}", start_time.elapsed());
     info!(duration = &*duration; "In-memory cache updated ({}).", current_timestamp);
 }
+
+/// Persist the new count, cover and time range of smart albums whose members changed
+fn refresh_smart_albums(
+    database_timestamp_vec: &mut [DatabaseTimestamp],
+    changed_album_vec: &[ArrayString<64>],
+) {
+    let txn = TREE.in_disk.begin_write().unwrap();
+    {
+        let mut data_table = txn.open_table(DATA_TABLE).unwrap();
+        for album_id in changed_album_vec {
+            let album_opt =
+                data_table
+                    .get(&**album_id)
+                    .unwrap()
+                    .and_then(|guard| match guard.value() {
+                        AbstractData::Album(album) => Some(album),
+                        _ => None,
+                    });
+            let Some(mut album) = album_opt else {
+                continue;
+            };
+            album.update_from(database_timestamp_vec, &TREE.smart_album_members(album_id));
+            let abstract_data = AbstractData::Album(album);
+            data_table
+                .insert(&**album_id, abstract_data.clone())
+                .unwrap();
+
+            if let Some(position) = database_timestamp_vec
+                .iter()
+                .position(|database_timestamp| database_timestamp.abstract_data.hash() == *album_id)
+            {
+                let priority_list = LIBRARY_CONFIG.priority_list(&abstract_data);
+                database_timestamp_vec[position] =
+                    DatabaseTimestamp::new(abstract_data, &priority_list);
+            }
+        }
+    }
+    txn.commit().unwrap();
+
+    // Album timestamps follow their items, so the order may have changed
+    database_timestamp_vec.par_sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+}
@@ -0,0 +1,112 @@
+use crate::operations::hash::blake3_hasher;
+use crate::operations::open_db::open_data_table;
+use crate::public::error_data::handle_error;
+use crate::public::structure::abstract_data::AbstractData;
+use crate::public::structure::common::FileModify;
+use crate::tasks::BATCH_COORDINATOR;
+use crate::tasks::batcher::flush_tree::FlushTreeTask;
+use anyhow::Result;
+use arrayvec::ArrayString;
+use log::{info, warn};
+use mini_executor::BatchTask;
+use redb::ReadableTable;
+use std::{fs::File, time::UNIX_EPOCH};
+use tokio::task::spawn_blocking;
+
+/// Check that every original referenced in place still exists and matches its hash,
+/// flagging the alias as `missing` or `changed` otherwise
+pub struct VerifyReferenceTask;
+
+impl BatchTask for VerifyReferenceTask {
+    async fn batch_run(_: Vec<Self>) {
+        if let Err(e) = verify_reference_task().await {
+            handle_error(e.context("Failed to run verify reference task"));
+        }
+    }
+}
+
+async fn verify_reference_task() -> Result<()> {
+    let data_to_flush = spawn_blocking(verify_references)
+        .await
+        .expect("blocking task panicked")?;
+
+    if !data_to_flush.is_empty() {
+        info!("Updated {} referenced originals", data_to_flush.len());
+        BATCH_COORDINATOR
+            .execute_batch_waiting(FlushTreeTask::insert(data_to_flush))
+            .await?;
+    }
+    Ok(())
+}
+
+/// Records whose referenced aliases changed state since the last verification
+fn verify_references() -> Result<Vec<AbstractData>> {
+    let data_table = open_data_table();
+    let mut data_to_flush = Vec::new();
+
+    for entry in data_table.iter()? {
+        let (_, guard) = entry?;
+        let mut abstract_data = guard.value();
+        let hash = abstract_data.hash();
+        let Some(alias) = abstract_data.alias_mut() else {
+            continue;
+        };
+
+        let mut updated = false;
+        for file_modify in alias.iter_mut().filter(|file_modify| file_modify.reference) {
+            updated |= verify_reference(file_modify, hash);
+        }
+        if updated {
+            data_to_flush.push(abstract_data);
+        }
+    }
+
+    Ok(data_to_flush)
+}
+
+/// Refresh the flags of one referenced file; returns whether anything changed
+fn verify_reference(file_modify: &mut FileModify, hash: ArrayString<64>) -> bool {
+    let Ok(file) = File::open(&file_modify.file) else {
+        if file_modify.missing {
+            return false;
+        }
+        warn!("Referenced original {} is missing", file_modify.file);
+        file_modify.missing = true;
+        return true;
+    };
+
+    let modified = file
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis());
+
+    // Hashing a whole archive is slow, so only rehash files that were touched
+    let (changed, modified) = if !file_modify.missing && modified == Some(file_modify.modified) {
+        (file_modify.changed, file_modify.modified)
+    } else {
+        match blake3_hasher(file) {
+            // Keep the old time on a mismatch so the reconcile scan still picks up the new content
+            Ok(current_hash) if current_hash != hash => (true, file_modify.modified),
+            Ok(_) => (false, modified.unwrap_or(file_modify.modified)),
+            Err(e) => {
+                handle_error(e.context(format!("Failed to hash {}", file_modify.file)));
+                return false;
+            }
+        }
+    };
+
+    if changed && !file_modify.changed {
+        warn!(
+            "Referenced original {} no longer matches its hash",
+            file_modify.file
+        );
+    }
+    let updated =
+        file_modify.missing || changed != file_modify.changed || modified != file_modify.modified;
+    file_modify.missing = false;
+    file_modify.changed = changed;
+    file_modify.modified = modified;
+    updated
+}
@@ -1,6 +1,14 @@
-use crate::public::constant::{SNAPSHOT_MAX_LIFETIME_MS, runtime::INDEX_RUNTIME};
+use crate::operations::backup::run_scheduled_backup;
+use crate::public::config::PRIVATE_CONFIG;
+use crate::public::constant::{
+    REFERENCE_VERIFY_INTERVAL_SECS, SNAPSHOT_MAX_LIFETIME_MS, WATCHER_HEALTH_CHECK_INTERVAL_SECS,
+    runtime::INDEX_RUNTIME,
+};
+use crate::public::error_data::handle_error;
 use crate::tasks::BATCH_COORDINATOR;
 use crate::tasks::batcher::expire_check::ExpireCheckTask;
+use crate::tasks::batcher::start_watcher::check_watcher_health;
+use crate::tasks::batcher::verify_reference::VerifyReferenceTask;
 use std::sync::{Arc, LazyLock};
 use tokio::sync::mpsc;
 use tokio::time::{Duration, sleep};
@@ -43,3 +51,49 @@ pub async fn reset_expire_check_timer() {
         let _ = sender.send(());
     }
 }
+
+pub fn start_watcher_health_check_loop() {
+    INDEX_RUNTIME.spawn(async {
+        loop {
+            sleep(Duration::from_secs(WATCHER_HEALTH_CHECK_INTERVAL_SECS)).await;
+            // Reading a hung network mount can block, so keep it off the async workers
+            if let Err(e) = tokio::task::spawn_blocking(check_watcher_health).await {
+                error!("Watcher health check panicked: {}", e);
+            }
+        }
+    });
+}
+
+pub fn start_reference_verify_loop() {
+    INDEX_RUNTIME.spawn(async {
+        loop {
+            BATCH_COORDINATOR.execute_batch_detached(VerifyReferenceTask);
+            sleep(Duration::from_secs(REFERENCE_VERIFY_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+pub fn start_backup_loop() {
+    let Some(backup_path) = PRIVATE_CONFIG.backup_path.as_ref() else {
+        return;
+    };
+    info!(
+        "Backing up to {:?} every {} hours",
+        backup_path, PRIVATE_CONFIG.backup_interval_hours
+    );
+    INDEX_RUNTIME.spawn(async move {
+        loop {
+            sleep(Duration::from_secs(
+                PRIVATE_CONFIG.backup_interval_hours.max(1) * 60 * 60,
+            ))
+            .await;
+            match tokio::task::spawn_blocking(move || run_scheduled_backup(backup_path)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => {
+                    handle_error(e.context("Scheduled backup failed"));
+                }
+                Err(e) => error!("Scheduled backup panicked: {}", e),
+            }
+        }
+    });
+}
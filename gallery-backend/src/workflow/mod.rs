@@ -1,15 +1,26 @@
+use crate::operations::utils::folder_album::folder_album_id;
+use crate::operations::utils::path::canonical_path;
+use crate::public::error_data::handle_error;
+use crate::public::storage::STORAGE;
+use crate::router::AccessDenied;
 use crate::tasks::{
-    INDEX_COORDINATOR,
+    BATCH_COORDINATOR, INDEX_COORDINATOR,
     actor::{
-        copy::CopyTask, deduplicate::DeduplicateTask, delete_in_update::DeleteTask, hash::HashTask,
-        index::IndexTask, open_file::OpenFileTask, video::VideoTask,
+        album::AlbumSelfUpdateTask,
+        copy::CopyTask,
+        deduplicate::{DeduplicateTask, Deduplicated},
+        delete_in_update::DeleteTask,
+        hash::HashTask,
+        index::IndexTask,
+        open_file::OpenFileTask,
+        video::VideoTask,
     },
+    batcher::update_tree::UpdateTreeTask,
 };
 use anyhow::Result;
 use arrayvec::ArrayString;
 use dashmap::DashSet;
 use log::warn;
-use path_clean::PathClean;
 use std::{path::PathBuf, sync::LazyLock};
 
 static IN_PROGRESS: LazyLock<DashSet<ArrayString<64>>> = LazyLock::new(DashSet::new);
@@ -32,8 +43,18 @@ fn try_acquire(hash: ArrayString<64>) -> Option<ProcessingGuard> {
 pub async fn index_for_watch(
     path: PathBuf,
     presigned_album_id_opt: Option<ArrayString<64>>,
+    owner_opt: Option<String>,
 ) -> Result<()> {
-    let path = path.clean();
+    // Aliases are stored canonical, matching the sync paths they are looked up by
+    let path = canonical_path(&path);
+
+    // An explicit album from the uploader wins over the folder album
+    let folder_album_id_opt = match presigned_album_id_opt {
+        Some(_) => None,
+        None => folder_album_id(&path).await?,
+    };
+    let presigned_album_id_opt = presigned_album_id_opt.or(folder_album_id_opt);
+
     let file = INDEX_COORDINATOR
         .execute_waiting(OpenFileTask::new(path.clone()))
         .await??;
@@ -53,21 +74,31 @@ pub async fn index_for_watch(
         }
     };
 
-    let abstract_data_opt = INDEX_COORDINATOR
+    let deduplicated = INDEX_COORDINATOR
         .execute_waiting(DeduplicateTask::new(
             path.clone(),
             hash,
             presigned_album_id_opt,
+            owner_opt,
         ))
         .await??;
 
     // If the file is already in the database, we can skip further processing.
-    let mut abstract_data = match abstract_data_opt {
-        Some(data) => data,
-        None => {
+    let mut abstract_data = match deduplicated {
+        Deduplicated::New(data) => *data,
+        Deduplicated::Existing => {
             INDEX_COORDINATOR.execute_detached(DeleteTask::new(path));
+            refresh_folder_album(folder_album_id_opt).await?;
             return Ok(());
         }
+        Deduplicated::NotOwned => {
+            INDEX_COORDINATOR.execute_detached(DeleteTask::new(path));
+            return Err(AccessDenied(format!(
+                "{} is already in the library under another account",
+                hash
+            ))
+            .into());
+        }
     };
 
     abstract_data = INDEX_COORDINATOR
@@ -79,10 +110,36 @@ pub async fn index_for_watch(
 
     INDEX_COORDINATOR.execute_detached(DeleteTask::new(PathBuf::from(&path)));
     if abstract_data.is_video() {
-        INDEX_COORDINATOR
+        abstract_data = INDEX_COORDINATOR
             .execute_waiting(VideoTask::new(abstract_data))
             .await??;
     }
 
+    // Static GIFs only become images in VideoTask, which changes their compressed key
+    let working_keys = [
+        abstract_data.imported_key(),
+        abstract_data.compressed_key(),
+        abstract_data.thumbnail_key(),
+    ];
+
+    // Everything has been stored by now, so remote backends no longer need the local copies
+    for key in working_keys {
+        if let Err(e) = STORAGE.evict(&key) {
+            handle_error(e);
+        }
+    }
+    refresh_folder_album(folder_album_id_opt).await?;
+
+    Ok(())
+}
+
+/// Folder albums are filled without anyone editing them, so update their count and cover here
+async fn refresh_folder_album(folder_album_id_opt: Option<ArrayString<64>>) -> Result<()> {
+    if let Some(album_id) = folder_album_id_opt {
+        BATCH_COORDINATOR
+            .execute_batch_waiting(UpdateTreeTask)
+            .await?;
+        BATCH_COORDINATOR.execute_detached(AlbumSelfUpdateTask::new(album_id));
+    }
     Ok(())
 }
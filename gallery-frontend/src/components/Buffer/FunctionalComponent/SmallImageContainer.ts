@@ -149,7 +149,7 @@ async function checkAndFetch(
       devicePixelRatio: window.devicePixelRatio,
       albumId: shareStore.albumId,
       shareId: shareStore.shareId,
-      password: shareStore.password,
+      shareToken: shareStore.shareToken,
       timestampToken,
       hashToken
     })
@@ -170,7 +170,7 @@ async function checkAndFetch(
       albumMode: true,
       albumId: shareStore.albumId,
       shareId: shareStore.shareId,
-      password: shareStore.password,
+      shareToken: shareStore.shareToken,
       timestampToken,
       hashToken
     })
@@ -112,7 +112,7 @@ export async function deleteHashToken(hash: string): Promise<void> {
 export interface ShareInfo {
   albumId: string | null
   shareId: string | null
-  password: string | null
+  shareToken: string | null
 }
 
 // Use composite key: albumId_shareId to support multiple shares
@@ -18,6 +18,7 @@ import { createVuetify } from 'vuetify'
 import * as components from 'vuetify/components'
 import * as directives from 'vuetify/directives'
 import axios, { AxiosError, InternalAxiosRequestConfig } from 'axios'
+import { REFRESH_URL, refreshAccessToken, startSessionRefresh } from '@/script/utils/sessionRefresh'
 import { useRedirectionStore } from '@/store/redirectionStore'
 import { useShareStore } from '@/store/shareStore'
 import { useConstStore } from '@/store/constStore'
@@ -32,15 +33,18 @@ axios.interceptors.request.use((config: InternalAxiosRequestConfig) => {
     config.headers.set('x-album-id', shareStore.albumId)
     config.headers.set('x-share-id', shareStore.shareId)
 
-    // Add password header if it exists
-    if (shareStore.password) {
-      config.headers.set('x-share-password', shareStore.password)
+    // Add the share token from /post/share/unlock if the share was unlocked
+    if (shareStore.shareToken) {
+      config.headers.set('x-share-token', shareStore.shareToken)
     }
   }
 
   return config
 })
 
+// Marks a request that was already retried after refreshing the access token
+type RetriableConfig = InternalAxiosRequestConfig & { _retried?: boolean }
+
 // Response interceptor
 axios.interceptors.response.use(
   (response) => {
@@ -74,7 +78,21 @@ axios.interceptors.response.use(
         }
       } else {
         // 一般頁面邏輯 (Home/Admin)
-        if (status === 401) {
+        const config = error.config as RetriableConfig | undefined
+        if (
+          status === 401 &&
+          config !== undefined &&
+          !config._retried &&
+          config.url !== REFRESH_URL &&
+          config.url !== '/post/authenticate'
+        ) {
+          // The access token may have expired in between scheduled refreshes: refresh once and retry
+          config._retried = true
+          if (await refreshAccessToken()) {
+            return axios(config)
+          }
+          await redirectionStore.redirectionToLogin()
+        } else if (status === 401) {
           await redirectionStore.redirectionToLogin()
         } else if (status === 403) {
           messageStore.error('Access denied.')
@@ -106,6 +124,9 @@ const vuetify = createVuetify({
   }
 })
 
+// Keep an existing sign-in alive
+startSessionRefresh()
+
 // Apply necessary plugins and mount the app
 app.use(router)
 app.use(vuetify)
@@ -64,7 +64,7 @@ export async function refreshAlbumMetadata(albumId: string) {
         devicePixelRatio: window.devicePixelRatio,
         albumId: shareStore.albumId,
         shareId: shareStore.shareId,
-        password: shareStore.password,
+        shareToken: shareStore.shareToken,
         timestampToken,
         hashToken
       })
@@ -78,7 +78,7 @@ export async function refreshAlbumMetadata(albumId: string) {
         albumMode: true,
         albumId: shareStore.albumId,
         shareId: shareStore.shareId,
-        password: shareStore.password,
+        shareToken: shareStore.shareToken,
         timestampToken,
         hashToken
       })
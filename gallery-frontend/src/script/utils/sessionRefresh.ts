@@ -0,0 +1,73 @@
+// Keeps the sign-in alive. Access tokens from /post/authenticate only last a few minutes, so
+// they are traded for a new one at /post/refresh, which reads the httpOnly refresh token cookie.
+// Refreshing ahead of expiry also covers requests made by workers and <img> tags, which never
+// pass through the axios interceptors.
+import axios from 'axios'
+import Cookies from 'js-cookie'
+import { jwtDecode } from 'jwt-decode'
+import { z } from 'zod'
+
+// Refresh this long before the access token expires
+const REFRESH_MARGIN_SECONDS = 60
+
+export const REFRESH_URL = '/post/refresh'
+
+let refreshTimer: ReturnType<typeof setTimeout> | null = null
+let refreshing: Promise<boolean> | null = null
+
+function scheduleRefresh(token: string) {
+  if (refreshTimer !== null) {
+    clearTimeout(refreshTimer)
+    refreshTimer = null
+  }
+
+  let exp: number | undefined
+  try {
+    exp = jwtDecode<{ exp?: number }>(token).exp
+  } catch (err) {
+    console.warn('Invalid JWT:', err)
+    return
+  }
+  if (exp === undefined) return
+
+  const delay = Math.max(0, (exp - REFRESH_MARGIN_SECONDS) * 1000 - Date.now())
+  refreshTimer = setTimeout(() => {
+    void refreshAccessToken()
+  }, delay)
+}
+
+// Store the access token in the cookie read by the server and schedule its refresh
+export function storeAccessToken(token: string) {
+  Cookies.set('jwt', token, {
+    secure: true, // Ensure it's only sent over HTTPS
+    sameSite: 'Strict', // Prevent CSRF attacks
+    expires: 14 // Matches the refresh token lifetime
+  })
+  scheduleRefresh(token)
+}
+
+// Trade the refresh token for a new access token; concurrent callers share one request
+export function refreshAccessToken(): Promise<boolean> {
+  refreshing ??= axios
+    .post(REFRESH_URL)
+    .then((response) => {
+      storeAccessToken(z.string().parse(response.data))
+      return true
+    })
+    .catch((err: unknown) => {
+      console.warn('Failed to refresh the access token:', err)
+      return false
+    })
+    .finally(() => {
+      refreshing = null
+    })
+  return refreshing
+}
+
+// Resume refreshing after a page load if a token from an earlier sign-in is present
+export function startSessionRefresh() {
+  const token = Cookies.get('jwt')
+  if (token !== undefined) {
+    scheduleRefresh(token)
+  }
+}
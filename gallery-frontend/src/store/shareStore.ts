@@ -9,7 +9,7 @@ export const useShareStore = (isolationId: IsolationId) =>
     state: (): {
       albumId: null | string
       shareId: null | string
-      password: null | string
+      shareToken: null | string
       isAuthFailed: boolean
       isLinkExpired: boolean
       resolvedShare: null | ResolvedShare
@@ -18,7 +18,7 @@ export const useShareStore = (isolationId: IsolationId) =>
     } => ({
       albumId: null,
       shareId: null,
-      password: null,
+      shareToken: null,
       isAuthFailed: false,
       isLinkExpired: false,
       resolvedShare: null,
@@ -43,7 +43,7 @@ export const useShareStore = (isolationId: IsolationId) =>
           await storeShareInfo({
             albumId: this.albumId,
             shareId: this.shareId,
-            password: this.password
+            shareToken: this.shareToken
           })
         }
       },
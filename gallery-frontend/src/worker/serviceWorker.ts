@@ -120,8 +120,8 @@ async function handleMediaRequest(request: Request): Promise<Response> {
       if (shareInfo.shareId) {
         headers.set('x-share-id', shareInfo.shareId)
       }
-      if (shareInfo.password) {
-        headers.set('x-share-password', shareInfo.password)
+      if (shareInfo.shareToken) {
+        headers.set('x-share-token', shareInfo.shareToken)
       }
     }
   }
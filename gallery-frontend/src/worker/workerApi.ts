@@ -14,7 +14,7 @@ export interface ProcessSmallImagePayload {
   albumMode?: boolean
   albumId: null | string
   shareId: null | string
-  password: null | string
+  shareToken: null | string
   timestampToken: string
   hashToken: string
 }
@@ -25,7 +25,7 @@ export interface ProcessImagePayload {
   devicePixelRatio: number
   albumId: null | string
   shareId: null | string
-  password: null | string
+  shareToken: null | string
   timestampToken: string
   hashToken: string
 }